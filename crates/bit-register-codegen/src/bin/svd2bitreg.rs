@@ -0,0 +1,83 @@
+//! Generate `bit_register!` definitions from a CMSIS-SVD file
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use bit_register_codegen::Build;
+
+const USAGE: &str = "\
+Usage: svd2bitreg <FILE> [--peripheral <NAME>]... [--output <FILE>]
+
+Generate bit_register! definitions from a CMSIS-SVD file.
+
+Options:
+  -p, --peripheral <NAME>  Only generate the named peripheral, can be repeated
+  -o, --output <FILE>      Write to a file instead of standard output
+  -h, --help               Print this message";
+
+struct Args {
+    input: PathBuf,
+    peripherals: Vec<String>,
+    output: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut input = None;
+    let mut peripherals = Vec::new();
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-p" | "--peripheral" => {
+                peripherals.push(args.next().ok_or("--peripheral requires a name")?);
+            }
+            "-o" | "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or("--output requires a file")?,
+                ));
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let input = input.ok_or("missing SVD file")?;
+    Ok(Some(Args {
+        input,
+        peripherals,
+        output,
+    }))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let build = args
+        .peripherals
+        .into_iter()
+        .fold(Build::svd(args.input), Build::peripheral);
+    let result = match &args.output {
+        Some(output) => build.generate_to(output),
+        None => build.generate_string().map(|source| print!("{}", source)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
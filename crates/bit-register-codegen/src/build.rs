@@ -0,0 +1,133 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{description, generate_rust, rdl, svd, Device, Error};
+
+/// Format of a register description file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Svd,
+    Rdl,
+    Toml,
+    Json,
+}
+
+/// Generates `bit_register!` definitions from a register description in a build script
+///
+/// The generated file is written to `OUT_DIR` and can be included with
+/// `include!(concat!(env!("OUT_DIR"), "/<output>"))`. Cargo is told to rerun the build script
+/// when the description changes.
+///
+/// ```no_run
+/// // In build.rs
+/// bit_register_codegen::Build::svd("device.svd")
+///     .peripheral("TIMER0")
+///     .output("registers.rs")
+///     .generate()
+///     .unwrap_or_else(|error| panic!("{}", error));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Build {
+    path: PathBuf,
+    format: Format,
+    peripherals: Vec<String>,
+    output: Option<String>,
+}
+
+impl Build {
+    /// Generate definitions from a CMSIS-SVD file
+    pub fn svd(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), Format::Svd)
+    }
+
+    /// Generate definitions from a SystemRDL file
+    pub fn rdl(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), Format::Rdl)
+    }
+
+    /// Generate definitions from a TOML description, see [`description`] for the schema
+    pub fn toml(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), Format::Toml)
+    }
+
+    /// Generate definitions from a JSON description, see [`description`] for the schema
+    pub fn json(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), Format::Json)
+    }
+
+    fn new(path: PathBuf, format: Format) -> Self {
+        Self {
+            path,
+            format,
+            peripherals: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// Only generate definitions for the named peripheral, can be called multiple times
+    ///
+    /// All peripherals are generated if none are selected.
+    pub fn peripheral(mut self, name: impl Into<String>) -> Self {
+        self.peripherals.push(name.into());
+        self
+    }
+
+    /// Name of the generated file in `OUT_DIR`, defaults to the description's file name with
+    /// an `.rs` extension
+    pub fn output(mut self, file_name: impl Into<String>) -> Self {
+        self.output = Some(file_name.into());
+        self
+    }
+
+    /// Read the description and select its peripherals
+    pub fn device(&self) -> Result<Device, Error> {
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|error| Error::new(error.to_string()).with_path(&self.path))?;
+        let mut device = match self.format {
+            Format::Svd => svd::parse(&contents),
+            Format::Rdl => rdl::parse(&contents),
+            Format::Toml => description::parse_toml(&contents),
+            Format::Json => description::parse_json(&contents),
+        }
+        .map_err(|error| error.with_path(&self.path))?;
+        device.select(&self.peripherals)?;
+        Ok(device)
+    }
+
+    /// Generate the definitions as Rust source
+    pub fn generate_string(&self) -> Result<String, Error> {
+        generate_rust(&self.device()?).map_err(|error| error.with_path(&self.path))
+    }
+
+    /// Generate the definitions into a file at `path`
+    pub fn generate_to(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let source = self.generate_string()?;
+        fs::write(path.as_ref(), source)
+            .map_err(|error| Error::new(error.to_string()).with_path(path.as_ref()))
+    }
+
+    /// Generate the definitions into `OUT_DIR`, returning the path of the generated file
+    ///
+    /// Must be called from a build script.
+    pub fn generate(&self) -> Result<PathBuf, Error> {
+        println!("cargo:rerun-if-changed={}", self.path.display());
+
+        let out_dir = env::var_os("OUT_DIR").ok_or_else(|| {
+            Error::new("OUT_DIR is not set, generate() must be called from a build script")
+        })?;
+        let file_name = match &self.output {
+            Some(file_name) => file_name.clone(),
+            None => {
+                let stem = self.path.file_stem().ok_or_else(|| {
+                    Error::new("description path has no file name").with_path(&self.path)
+                })?;
+                format!("{}.rs", stem.to_string_lossy())
+            }
+        };
+
+        let output = Path::new(&out_dir).join(file_name);
+        self.generate_to(&output)?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,419 @@
+use std::fmt::Write;
+
+use crate::rust::words;
+use crate::{Device, Error, Field, Peripheral, Register};
+
+/// Options for [`generate_c_header`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CHeaderOptions {
+    /// Also emit a union of a bitfield struct and the raw value for every register
+    ///
+    /// C leaves the order of bitfields to the compiler. The structs assume the first field is
+    /// the least significant, as GCC, Clang and MSVC do on little-endian targets.
+    pub bitfields: bool,
+}
+
+/// Generate a C header with the layout of every register
+///
+/// Every peripheral gets a `<PERIPHERAL>_BASE_ADDRESS` define, and every register
+/// `<PERIPHERAL>_<REGISTER>_OFFSET`, `_ADDRESS` and `_RESET_VALUE` defines. Every field gets
+/// `_SHIFT` and `_MASK` defines, with the mask shifted into place, and every named value of a
+/// field is defined unshifted as `<PERIPHERAL>_<REGISTER>_<FIELD>_<VALUE>`. Optional fields also
+/// get a `_NONE` define with the unshifted raw value which reads as no value, and the raw values
+/// of fields with a lookup table are defined like named values, named after their values as
+/// `<FIELD>_VALUE_16` or `<FIELD>_VALUE_MINUS_4`. The device is
+/// validated first, and names which collide after conversion to C identifiers are an error.
+pub fn generate_c_header(device: &Device, options: &CHeaderOptions) -> Result<String, Error> {
+    device.validate()?;
+
+    let guard = format!("{}_REGISTERS_H", upper_case(&[&device.name]));
+    let mut header = Header {
+        out: String::new(),
+        names: Vec::new(),
+    };
+    writeln!(
+        header.out,
+        "/* Generated by bit-register-codegen from {}. Do not edit. */",
+        comment_text(&device.name)
+    )
+    .unwrap();
+    writeln!(header.out).unwrap();
+    writeln!(header.out, "#ifndef {}", guard).unwrap();
+    writeln!(header.out, "#define {}", guard).unwrap();
+    writeln!(header.out).unwrap();
+    writeln!(header.out, "#include <stdint.h>").unwrap();
+
+    for peripheral in &device.peripherals {
+        header.peripheral(peripheral, options)?;
+    }
+
+    writeln!(header.out).unwrap();
+    writeln!(header.out, "#endif /* {} */", guard).unwrap();
+    Ok(header.out)
+}
+
+struct Header {
+    out: String,
+    /// Every identifier defined so far, to catch names which collide in C
+    names: Vec<String>,
+}
+
+impl Header {
+    fn peripheral(
+        &mut self,
+        peripheral: &Peripheral,
+        options: &CHeaderOptions,
+    ) -> Result<(), Error> {
+        let prefix = upper_case(&[&peripheral.name]);
+        writeln!(self.out).unwrap();
+        write_comment(&mut self.out, peripheral.description.as_deref(), &[]);
+        self.define(
+            &peripheral.name,
+            format!("{}_BASE_ADDRESS", prefix),
+            literal(peripheral.base_address, 32),
+        )?;
+
+        for register in &peripheral.registers {
+            self.register(peripheral, register, options)?;
+        }
+        Ok(())
+    }
+
+    fn register(
+        &mut self,
+        peripheral: &Peripheral,
+        register: &Register,
+        options: &CHeaderOptions,
+    ) -> Result<(), Error> {
+        let context = format!("{}.{}", peripheral.name, register.name);
+        let prefix = upper_case(&[&peripheral.name, &register.name]);
+        let access = register.access.map(|access| format!("Access: {}", access));
+
+        writeln!(self.out).unwrap();
+        write_comment(
+            &mut self.out,
+            register.description.as_deref(),
+            access.as_deref().into_iter().collect::<Vec<_>>().as_slice(),
+        );
+        self.define(
+            &context,
+            format!("{}_OFFSET", prefix),
+            literal(register.offset, 8),
+        )?;
+        self.define(
+            &context,
+            format!("{}_ADDRESS", prefix),
+            format!(
+                "({}_BASE_ADDRESS + {}_OFFSET)",
+                upper_case(&[&peripheral.name]),
+                prefix
+            ),
+        )?;
+        if let Some(reset_value) = register.reset_value {
+            self.define(
+                &context,
+                format!("{}_RESET_VALUE", prefix),
+                literal(reset_value, register.size),
+            )?;
+        }
+
+        for field in &register.fields {
+            self.field(&context, &prefix, register, field)?;
+        }
+
+        if options.bitfields {
+            self.bitfields(&context, peripheral, register)?;
+        }
+        Ok(())
+    }
+
+    fn field(
+        &mut self,
+        context: &str,
+        prefix: &str,
+        register: &Register,
+        field: &Field,
+    ) -> Result<(), Error> {
+        let context = format!("{}.{}", context, field.name);
+        let prefix = format!("{}_{}", prefix, upper_case(&[&field.name]));
+        let access = field.access.map(|access| format!("Access: {}", access));
+
+        writeln!(self.out).unwrap();
+        write_comment(
+            &mut self.out,
+            field.description.as_deref(),
+            access.as_deref().into_iter().collect::<Vec<_>>().as_slice(),
+        );
+        self.define(&context, format!("{}_SHIFT", prefix), field.lsb.to_string())?;
+        self.define(
+            &context,
+            format!("{}_MASK", prefix),
+            literal(field.max_value() << field.lsb, register.size),
+        )?;
+        if let Some(none) = field.none {
+            self.define(
+                &context,
+                format!("{}_NONE", prefix),
+                literal(none, field.width()),
+            )?;
+        }
+
+        let enumeration = field
+            .enumeration
+            .clone()
+            .or_else(|| field.table_enumeration());
+        if let Some(enumeration) = &enumeration {
+            for variant in &enumeration.variants {
+                write_comment(&mut self.out, variant.description.as_deref(), &[]);
+                self.define(
+                    &format!("{}.{}", context, variant.name),
+                    format!("{}_{}", prefix, upper_case(&[&variant.name])),
+                    literal(variant.value, field.width()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a union of a bitfield struct and the raw value, with unnamed padding in the gaps
+    fn bitfields(
+        &mut self,
+        context: &str,
+        peripheral: &Peripheral,
+        register: &Register,
+    ) -> Result<(), Error> {
+        let type_name = format!(
+            "{}_t",
+            upper_case(&[&peripheral.name, &register.name]).to_ascii_lowercase()
+        );
+        self.claim(context, &type_name)?;
+
+        let storage = format!("uint{}_t", register.size);
+        let mut fields: Vec<&Field> = register.fields.iter().collect();
+        fields.sort_by_key(|field| field.lsb);
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "typedef union {{").unwrap();
+        writeln!(self.out, "    struct {{").unwrap();
+        let mut next_bit = 0;
+        let mut members: Vec<String> = Vec::new();
+        for field in fields {
+            if field.lsb > next_bit {
+                writeln!(self.out, "        {} : {};", storage, field.lsb - next_bit).unwrap();
+            }
+            let member = member_name(&field.name);
+            if members.contains(&member) || member == "raw" {
+                return Err(Error::new(format!(
+                    "{}.{}: member name {} is used by another field",
+                    context, field.name, member
+                )));
+            }
+            writeln!(
+                self.out,
+                "        {} {} : {};",
+                storage,
+                member,
+                field.width()
+            )
+            .unwrap();
+            members.push(member);
+            next_bit = field.msb + 1;
+        }
+        if next_bit < register.size {
+            writeln!(
+                self.out,
+                "        {} : {};",
+                storage,
+                register.size - next_bit
+            )
+            .unwrap();
+        }
+        writeln!(self.out, "    }} bits;").unwrap();
+        writeln!(self.out, "    {} raw;", storage).unwrap();
+        writeln!(self.out, "}} {};", type_name).unwrap();
+        Ok(())
+    }
+
+    fn define(&mut self, context: &str, name: String, value: String) -> Result<(), Error> {
+        self.claim(context, &name)?;
+        writeln!(self.out, "#define {} {}", name, value).unwrap();
+        Ok(())
+    }
+
+    fn claim(&mut self, context: &str, name: &str) -> Result<(), Error> {
+        if self.names.iter().any(|existing| existing == name) {
+            return Err(Error::new(format!(
+                "{}: C name {} is used by another definition",
+                context, name
+            )));
+        }
+        self.names.push(name.to_string());
+        Ok(())
+    }
+}
+
+/// Write a C comment from a description and extra paragraphs
+fn write_comment(out: &mut String, description: Option<&str>, extra: &[&str]) {
+    let mut paragraphs: Vec<String> = Vec::new();
+    if let Some(description) = description {
+        let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !description.is_empty() {
+            paragraphs.push(comment_text(&description));
+        }
+    }
+    paragraphs.extend(extra.iter().map(|line| comment_text(line)));
+
+    match paragraphs.as_slice() {
+        [] => {}
+        [paragraph] => writeln!(out, "/* {} */", paragraph).unwrap(),
+        paragraphs => {
+            writeln!(out, "/*").unwrap();
+            for (index, paragraph) in paragraphs.iter().enumerate() {
+                if index > 0 {
+                    writeln!(out, " *").unwrap();
+                }
+                writeln!(out, " * {}", paragraph).unwrap();
+            }
+            writeln!(out, " */").unwrap();
+        }
+    }
+}
+
+/// Keep text from ending the comment it is written in
+fn comment_text(text: &str) -> String {
+    text.replace("*/", "* /")
+}
+
+/// Join names into an `UPPER_SNAKE_CASE` identifier
+fn upper_case(names: &[&str]) -> String {
+    let mut ident = names
+        .iter()
+        .flat_map(|name| words(name))
+        .map(|word| word.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+/// Convert a field name to a `snake_case` struct member, avoiding C keywords
+fn member_name(name: &str) -> String {
+    let mut ident = upper_case(&[name]).to_ascii_lowercase();
+    if is_c_keyword(&ident) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// Format an unsigned literal in hex with enough digits for `bits`
+fn literal(value: u64, bits: u32) -> String {
+    let bits = bits.max(64 - value.leading_zeros());
+    let suffix = if bits > 32 { "ull" } else { "u" };
+    format!(
+        "0x{:0width$X}{}",
+        value,
+        suffix,
+        width = bits.div_ceil(4) as usize
+    )
+}
+
+fn is_c_keyword(ident: &str) -> bool {
+    matches!(
+        ident,
+        "auto"
+            | "bool"
+            | "break"
+            | "case"
+            | "char"
+            | "const"
+            | "continue"
+            | "default"
+            | "do"
+            | "double"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "float"
+            | "for"
+            | "goto"
+            | "if"
+            | "inline"
+            | "int"
+            | "long"
+            | "register"
+            | "restrict"
+            | "return"
+            | "short"
+            | "signed"
+            | "sizeof"
+            | "static"
+            | "struct"
+            | "switch"
+            | "true"
+            | "typedef"
+            | "union"
+            | "unsigned"
+            | "void"
+            | "volatile"
+            | "while"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Enumeration, Variant};
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            upper_case(&["TIMER0", "CtrlReg", "en"]),
+            "TIMER0_CTRL_REG_EN"
+        );
+        assert_eq!(upper_case(&["2ND"]), "_2ND");
+        assert_eq!(member_name("Default"), "default_");
+        assert_eq!(literal(1, 16), "0x0001u");
+        assert_eq!(literal(0x1_0000_0000, 8), "0x100000000ull");
+        assert_eq!(comment_text("a */ b"), "a * / b");
+    }
+
+    #[test]
+    fn test_name_collisions() {
+        let mode = Field {
+            name: "MODE".into(),
+            lsb: 0,
+            msb: 1,
+            enumeration: Some(Enumeration {
+                name: None,
+                variants: vec![Variant {
+                    name: "SHIFT".into(),
+                    description: None,
+                    value: 1,
+                }],
+            }),
+            ..Default::default()
+        };
+        let device = Device {
+            name: "MCU".into(),
+            peripherals: vec![Peripheral {
+                name: "TIMER0".into(),
+                registers: vec![Register {
+                    name: "CTRL".into(),
+                    size: 8,
+                    fields: vec![mode],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let error = generate_c_header(&device, &CHeaderOptions::default()).unwrap_err();
+        assert_eq!(
+            error.message(),
+            "TIMER0.CTRL.MODE.SHIFT: C name TIMER0_CTRL_MODE_SHIFT is used by another definition"
+        );
+    }
+}
@@ -0,0 +1,899 @@
+//! Reading register descriptions from TOML or JSON files
+//!
+//! Both formats share one schema, so a description can be written in whichever is more
+//! convenient. A device has a `name`, an optional `description`, shared `enums` and a list of
+//! `peripherals`:
+//!
+//! ```toml
+//! name = "EXAMPLE_MCU"
+//!
+//! [[enums]]
+//! name = "CountMode"
+//! values = [
+//!     { name = "ONE_SHOT", value = 0, description = "Stop at the end of the period" },
+//!     { name = "PERIODIC", value = 1 },
+//! ]
+//!
+//! [[peripherals]]
+//! name = "TIMER0"
+//! base_address = 0x4001_0000
+//!
+//! [[peripherals.registers]]
+//! name = "CTRL"
+//! description = "Control register"
+//! offset = 0x0
+//! size = 16
+//! reset = 0x0002
+//! fields = [
+//!     { name = "EN", bits = 0, description = "Start the timer" },
+//!     { name = "MODE", bits = [1, 2], enum = "CountMode" },
+//!     { name = "PRESCALER", bits = [4, 7], access = "write-only" },
+//! ]
+//! ```
+//!
+//! The keys of each table are:
+//!
+//! | Table      | Required                 | Optional                                        |
+//! |------------|--------------------------|-------------------------------------------------|
+//! | device     | `name`                   | `description`, `enums`, `peripherals`, `registers`, `base_address` |
+//! | enum       | `name`, `values`         |                                                 |
+//! | value      | `name`, `value`          | `description`                                   |
+//! | peripheral | `name`, `base_address`   | `description`, `registers`                      |
+//! | register   | `name`, `offset`         | `description`, `size`, `access`, `reset`, `fields` |
+//! | field      | `name`, `bits`           | `description`, `access`, `enum`, `values`       |
+//!
+//! Registers are 32 bits wide unless they have a `size` of 8, 16, 32 or 64. A field's `bits`
+//! is a single bit, or `[lsb, msb]` like the `[start:end]` ranges of `bit_register!`. Its named
+//! values are either a shared enum named by `enum`, or its own list of `values`. Access is one
+//! of `read-only`, `write-only`, `read-write`, `write-once` or `read-write-once`.
+//!
+//! Integers may also be written as strings, e.g. `"0x4001_0000"`, since JSON has no hex
+//! literals. A device which is a single block of registers, such as an I²C sensor, can list its
+//! `registers` directly, and they become a peripheral named after the device at its
+//! `base_address`, which defaults to 0.
+//!
+//! Unknown keys are rejected so that typos are not silently ignored, and every error points at
+//! the line and column of the offending key or value.
+
+use std::borrow::Borrow;
+
+use toml::de::{DeTable, DeValue};
+
+use crate::{Access, Device, Enumeration, Error, Field, Location, Peripheral, Register, Variant};
+
+/// Read a device from the contents of a TOML description
+pub fn parse_toml(source: &str) -> Result<Device, Error> {
+    let table = DeTable::parse(source).map_err(|error| {
+        let location = error
+            .span()
+            .map_or(Location { line: 1, column: 1 }, |span| {
+                location(source, span.start)
+            });
+        Error::at(location, error.message().trim_end())
+    })?;
+    let node = toml_node(source, &DeValue::Table(table.get_ref().clone()), 0);
+    device(&node)
+}
+
+/// Read a device from the contents of a JSON description
+pub fn parse_json(source: &str) -> Result<Device, Error> {
+    let mut parser = JsonParser {
+        chars: source.chars().collect(),
+        index: 0,
+        line: 1,
+        column: 1,
+    };
+    let node = parser.value()?;
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(Error::at(
+            parser.location(),
+            "unexpected data after the JSON value",
+        ));
+    }
+    device(&node)
+}
+
+/// A value of either format, with its position in the file
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    value: Value,
+    location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Table(Vec<(String, Location, Node)>),
+    Array(Vec<Node>),
+    String(String),
+    Integer(i128),
+    Bool(bool),
+    /// Any other value, named for error messages
+    Other(&'static str),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Table(_) => "a table",
+            Self::Array(_) => "an array",
+            Self::String(_) => "a string",
+            Self::Integer(_) => "an integer",
+            Self::Bool(_) => "a boolean",
+            Self::Other(kind) => kind,
+        }
+    }
+}
+
+/// Line and column of a byte offset
+fn location(source: &str, offset: usize) -> Location {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Location {
+        line: before.matches('\n').count() as u32 + 1,
+        column: before[line_start..].chars().count() as u32 + 1,
+    }
+}
+
+fn toml_node(source: &str, value: &DeValue, offset: usize) -> Node {
+    let location = location(source, offset);
+    let value = match value {
+        DeValue::Table(table) => Value::Table(
+            table
+                .iter()
+                .map(|(key, value)| {
+                    let key_location = self::location(source, key.span().start);
+                    let key: &str = key.get_ref().borrow();
+                    (
+                        key.to_string(),
+                        key_location,
+                        toml_node(source, value.get_ref(), value.span().start),
+                    )
+                })
+                .collect(),
+        ),
+        DeValue::Array(array) => Value::Array(
+            array
+                .iter()
+                .map(|value| toml_node(source, value.get_ref(), value.span().start))
+                .collect(),
+        ),
+        DeValue::String(string) => Value::String(string.to_string()),
+        DeValue::Integer(integer) => i128::from_str_radix(integer.as_str(), integer.radix())
+            .map_or(Value::Other("an integer out of range"), Value::Integer),
+        DeValue::Boolean(boolean) => Value::Bool(*boolean),
+        DeValue::Float(_) => Value::Other("a float"),
+        DeValue::Datetime(_) => Value::Other("a date"),
+    };
+    Node { value, location }
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    index: usize,
+    line: u32,
+    column: u32,
+}
+
+impl JsonParser {
+    fn location(&self) -> Location {
+        Location {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expected(&self, what: &str) -> Error {
+        let found = match self.peek() {
+            Some(c) => format!("`{}`", c),
+            None => "the end of the file".into(),
+        };
+        Error::at(
+            self.location(),
+            format!("expected {}, found {}", what, found),
+        )
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", expected)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, Error> {
+        self.skip_whitespace();
+        let location = self.location();
+        let value = match self.peek() {
+            Some('{') => self.object()?,
+            Some('[') => self.array()?,
+            Some('"') => Value::String(self.string()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+                    word.push(c);
+                    self.next();
+                }
+                match word.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Other("null"),
+                    _ => return Err(Error::at(location, format!("unexpected `{}`", word))),
+                }
+            }
+            _ => return Err(self.expected("a JSON value")),
+        };
+        Ok(Node { value, location })
+    }
+
+    fn object(&mut self) -> Result<Value, Error> {
+        self.next();
+        let mut entries: Vec<(String, Location, Node)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Value::Table(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let location = self.location();
+            if self.peek() != Some('"') {
+                return Err(self.expected("a key"));
+            }
+            let key = self.string()?;
+            if entries.iter().any(|(existing, _, _)| *existing == key) {
+                return Err(Error::at(location, format!("duplicate key `{}`", key)));
+            }
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, location, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some('}') => {
+                    self.next();
+                    return Ok(Value::Table(entries));
+                }
+                _ => return Err(self.expected("`,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, Error> {
+        self.next();
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Value::Array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some(']') => {
+                    self.next();
+                    return Ok(Value::Array(elements));
+                }
+                _ => return Err(self.expected("`,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let location = self.location();
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let digits: String = (0..4).filter_map(|_| self.next()).collect();
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        Some(c) => c,
+                        None => break,
+                    };
+                    string.push(escaped);
+                }
+                Some(c) => string.push(c),
+                None => break,
+            }
+        }
+        Err(Error::at(location, "unterminated string"))
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let mut number = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c);
+            self.next();
+        }
+        Ok(match number.parse() {
+            Ok(integer) => Value::Integer(integer),
+            Err(_) if number.parse::<f64>().is_ok() => Value::Other("a float"),
+            Err(_) => Value::Other("an invalid number"),
+        })
+    }
+}
+
+// Schema
+
+/// A table of the schema, named for error messages
+struct Table<'a> {
+    entries: &'a [(String, Location, Node)],
+    location: Location,
+    what: &'static str,
+}
+
+impl<'a> Table<'a> {
+    fn new(node: &'a Node, what: &'static str, keys: &[&str]) -> Result<Self, Error> {
+        let Value::Table(entries) = &node.value else {
+            return Err(Error::at(
+                node.location,
+                format!("expected a {} table, found {}", what, node.value.kind()),
+            ));
+        };
+        if let Some((key, location, _)) = entries
+            .iter()
+            .find(|(key, _, _)| !keys.contains(&key.as_str()))
+        {
+            return Err(Error::at(
+                *location,
+                format!(
+                    "unknown key `{}` in {}, expected one of {}",
+                    key,
+                    what,
+                    keys.join(", ")
+                ),
+            ));
+        }
+        Ok(Self {
+            entries,
+            location: node.location,
+            what,
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Node> {
+        self.entries
+            .iter()
+            .find(|(existing, _, _)| existing == key)
+            .map(|(_, _, node)| node)
+    }
+
+    fn required(&self, key: &str) -> Result<&'a Node, Error> {
+        self.get(key)
+            .ok_or_else(|| Error::at(self.location, format!("missing `{}` in {}", key, self.what)))
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>, Error> {
+        self.get(key).map(|node| string(node, key)).transpose()
+    }
+
+    fn integer(&self, key: &str) -> Result<Option<u64>, Error> {
+        self.get(key).map(|node| integer(node, key)).transpose()
+    }
+
+    fn array(&self, key: &str) -> Result<&'a [Node], Error> {
+        match self.get(key) {
+            None => Ok(&[]),
+            Some(Node {
+                value: Value::Array(elements),
+                ..
+            }) => Ok(elements),
+            Some(node) => Err(Error::at(
+                node.location,
+                format!("`{}` must be an array, not {}", key, node.value.kind()),
+            )),
+        }
+    }
+
+    fn access(&self) -> Result<Option<Access>, Error> {
+        let Some(node) = self.get("access") else {
+            return Ok(None);
+        };
+        let access = match string(node, "access")?.as_str() {
+            "read-only" => Access::ReadOnly,
+            "write-only" => Access::WriteOnly,
+            "read-write" => Access::ReadWrite,
+            "write-once" => Access::WriteOnce,
+            "read-write-once" => Access::ReadWriteOnce,
+            other => {
+                return Err(Error::at(
+                    node.location,
+                    format!(
+                        "invalid access `{}`, expected read-only, write-only, read-write, \
+                         write-once or read-write-once",
+                        other
+                    ),
+                ))
+            }
+        };
+        Ok(Some(access))
+    }
+}
+
+fn string(node: &Node, key: &str) -> Result<String, Error> {
+    match &node.value {
+        Value::String(string) => Ok(string.clone()),
+        other => Err(Error::at(
+            node.location,
+            format!("`{}` must be a string, not {}", key, other.kind()),
+        )),
+    }
+}
+
+fn integer(node: &Node, key: &str) -> Result<u64, Error> {
+    let value = match &node.value {
+        Value::Integer(integer) => u64::try_from(*integer).ok(),
+        Value::String(string) => parse_number(string),
+        other => {
+            return Err(Error::at(
+                node.location,
+                format!("`{}` must be an integer, not {}", key, other.kind()),
+            ))
+        }
+    };
+    value.ok_or_else(|| {
+        Error::at(
+            node.location,
+            format!("`{}` must be an unsigned 64 bit integer", key),
+        )
+    })
+}
+
+/// Parse an integer written as a string, which may be decimal, `0x` hex or `0b` binary with
+/// `_` separators
+fn parse_number(raw: &str) -> Option<u64> {
+    let raw = raw.trim().replace('_', "");
+    if let Some(hex) = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = raw.strip_prefix("0b").or_else(|| raw.strip_prefix("0B")) {
+        u64::from_str_radix(binary, 2).ok()
+    } else {
+        raw.parse().ok()
+    }
+}
+
+fn device(node: &Node) -> Result<Device, Error> {
+    let table = Table::new(
+        node,
+        "device",
+        &[
+            "name",
+            "description",
+            "enums",
+            "peripherals",
+            "registers",
+            "base_address",
+        ],
+    )?;
+    let name = string(table.required("name")?, "name")?;
+
+    let mut enums: Vec<Enumeration> = Vec::new();
+    for node in table.array("enums")? {
+        let enum_table = Table::new(node, "enum", &["name", "values"])?;
+        let name_node = enum_table.required("name")?;
+        let enum_name = string(name_node, "name")?;
+        if enums
+            .iter()
+            .any(|existing| existing.name.as_deref() == Some(enum_name.as_str()))
+        {
+            return Err(Error::at(
+                name_node.location,
+                format!("enum {} is defined more than once", enum_name),
+            ));
+        }
+        let enumeration = Enumeration {
+            name: Some(enum_name),
+            variants: variants(enum_table.required("values")?)?,
+        };
+        enums.push(enumeration);
+    }
+
+    let mut peripherals = Vec::new();
+    let device_registers = registers(&table, &enums)?;
+    if !device_registers.is_empty() {
+        peripherals.push(Peripheral {
+            name: name.clone(),
+            description: None,
+            base_address: table.integer("base_address")?.unwrap_or(0),
+            registers: device_registers,
+        });
+    } else if let Some(node) = table.get("base_address") {
+        return Err(Error::at(
+            node.location,
+            "`base_address` of a device requires `registers`",
+        ));
+    }
+
+    for node in table.array("peripherals")? {
+        let peripheral = Table::new(
+            node,
+            "peripheral",
+            &["name", "description", "base_address", "registers"],
+        )?;
+        let name_node = peripheral.required("name")?;
+        let peripheral_name = string(name_node, "name")?;
+        if peripherals
+            .iter()
+            .any(|existing: &Peripheral| existing.name == peripheral_name)
+        {
+            return Err(Error::at(
+                name_node.location,
+                format!("peripheral {} is defined more than once", peripheral_name),
+            ));
+        }
+        peripherals.push(Peripheral {
+            name: peripheral_name,
+            description: peripheral.string("description")?,
+            base_address: integer(peripheral.required("base_address")?, "base_address")?,
+            registers: registers(&peripheral, &enums)?,
+        });
+    }
+
+    Ok(Device {
+        name,
+        description: table.string("description")?,
+        peripherals,
+    })
+}
+
+/// Read the `registers` of a device or peripheral, checking that they do not overlap
+fn registers(table: &Table, enums: &[Enumeration]) -> Result<Vec<Register>, Error> {
+    let mut registers: Vec<Register> = Vec::new();
+    for node in table.array("registers")? {
+        let register = register(node, enums)?;
+        let end = register.offset + u64::from(register.size / 8);
+        if let Some(other) = registers.iter().find(|other| {
+            other.name == register.name
+                || (register.offset < other.offset + u64::from(other.size / 8)
+                    && other.offset < end)
+        }) {
+            let message = if other.name == register.name {
+                format!("register {} is defined more than once", register.name)
+            } else {
+                format!(
+                    "{} at {:#x} overlaps register {} at {:#x}",
+                    register.name, register.offset, other.name, other.offset
+                )
+            };
+            return Err(Error::at(node.location, message));
+        }
+        registers.push(register);
+    }
+    Ok(registers)
+}
+
+fn register(node: &Node, enums: &[Enumeration]) -> Result<Register, Error> {
+    let table = Table::new(
+        node,
+        "register",
+        &[
+            "name",
+            "description",
+            "offset",
+            "size",
+            "access",
+            "reset",
+            "fields",
+        ],
+    )?;
+
+    let size = match table.get("size") {
+        Some(node) => {
+            let size = integer(node, "size")?;
+            u32::try_from(size)
+                .ok()
+                .filter(|size| matches!(size, 8 | 16 | 32 | 64))
+                .ok_or_else(|| {
+                    Error::at(
+                        node.location,
+                        format!("`size` must be 8, 16, 32 or 64, not {}", size),
+                    )
+                })?
+        }
+        None => 32,
+    };
+
+    let mut register = Register {
+        name: string(table.required("name")?, "name")?,
+        description: table.string("description")?,
+        offset: integer(table.required("offset")?, "offset")?,
+        size,
+        access: table.access()?,
+        reset_value: None,
+        fields: Vec::new(),
+    };
+    if let Some(node) = table.get("reset") {
+        let reset = integer(node, "reset")?;
+        if size < 64 && reset >> size != 0 {
+            return Err(Error::at(
+                node.location,
+                format!("reset value {:#x} does not fit in {} bits", reset, size),
+            ));
+        }
+        register.reset_value = Some(reset);
+    }
+
+    for node in table.array("fields")? {
+        let field = field(node, register.access, enums)?;
+        register.fields.push(field);
+        // Check each field as it is added, so errors point at the offending field
+        register
+            .validate()
+            .map_err(|error| Error::at(node.location, error.message()))?;
+    }
+    Ok(register)
+}
+
+fn field(
+    node: &Node,
+    register_access: Option<Access>,
+    enums: &[Enumeration],
+) -> Result<Field, Error> {
+    let table = Table::new(
+        node,
+        "field",
+        &["name", "description", "bits", "access", "enum", "values"],
+    )?;
+
+    let bits = table.required("bits")?;
+    let (lsb, msb) = match &bits.value {
+        Value::Array(range) => match range.as_slice() {
+            [lsb, msb] => (integer(lsb, "bits")?, integer(msb, "bits")?),
+            _ => {
+                return Err(Error::at(
+                    bits.location,
+                    "`bits` must be a single bit or [lsb, msb]",
+                ))
+            }
+        },
+        _ => {
+            let bit = integer(bits, "bits")?;
+            (bit, bit)
+        }
+    };
+    let out_of_range = || Error::at(bits.location, "bit position out of range");
+    let lsb = u32::try_from(lsb).map_err(|_| out_of_range())?;
+    let msb = u32::try_from(msb).map_err(|_| out_of_range())?;
+    if lsb > msb {
+        return Err(Error::at(
+            bits.location,
+            format!("`bits` must be [lsb, msb], found [{}, {}]", lsb, msb),
+        ));
+    }
+
+    let enumeration = match (table.get("enum"), table.get("values")) {
+        (Some(_), Some(values)) => {
+            return Err(Error::at(
+                values.location,
+                "a field can have either `enum` or `values`, not both",
+            ))
+        }
+        (Some(node), None) => {
+            let name = string(node, "enum")?;
+            let enumeration = enums
+                .iter()
+                .find(|enumeration| enumeration.name.as_deref() == Some(name.as_str()))
+                .ok_or_else(|| Error::at(node.location, format!("unknown enum {}", name)))?;
+            Some(enumeration.clone())
+        }
+        (None, Some(values)) => Some(Enumeration {
+            name: None,
+            variants: variants(values)?,
+        }),
+        (None, None) => None,
+    };
+
+    Ok(Field {
+        name: string(table.required("name")?, "name")?,
+        description: table.string("description")?,
+        lsb,
+        msb,
+        access: table
+            .access()?
+            .filter(|access| Some(*access) != register_access),
+        hardware_access: None,
+        enumeration,
+        valid: Vec::new(),
+        scale: None,
+        none: None,
+        table: Vec::new(),
+    })
+}
+
+fn variants(node: &Node) -> Result<Vec<Variant>, Error> {
+    let Value::Array(elements) = &node.value else {
+        return Err(Error::at(
+            node.location,
+            format!("`values` must be an array, not {}", node.value.kind()),
+        ));
+    };
+
+    let mut variants: Vec<Variant> = Vec::new();
+    for node in elements {
+        let table = Table::new(node, "value", &["name", "value", "description"])?;
+        let name_node = table.required("name")?;
+        let name = string(name_node, "name")?;
+        if variants.iter().any(|variant| variant.name == name) {
+            return Err(Error::at(
+                name_node.location,
+                format!("value {} is defined more than once", name),
+            ));
+        }
+        variants.push(Variant {
+            name,
+            description: table.string("description")?,
+            value: integer(table.required("value")?, "value")?,
+        });
+    }
+    Ok(variants)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn toml_error(source: &str) -> String {
+        let error = parse_toml(source).unwrap_err();
+        format!("{}: {}", error.location().unwrap(), error.message())
+    }
+
+    fn json_error(source: &str) -> String {
+        let error = parse_json(source).unwrap_err();
+        format!("{}: {}", error.location().unwrap(), error.message())
+    }
+
+    #[test]
+    fn test_device_registers() {
+        let device = parse_toml(
+            r#"
+            name = "EEPROM"
+            base_address = 0x50
+            registers = [
+                { name = "STATUS", offset = 0, size = 8, fields = [{ name = "BUSY", bits = 0 }] },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(device.peripherals.len(), 1);
+        let peripheral = &device.peripherals[0];
+        assert_eq!(
+            (peripheral.name.as_str(), peripheral.base_address),
+            ("EEPROM", 0x50)
+        );
+        assert_eq!(peripheral.registers[0].fields[0].name, "BUSY");
+    }
+
+    #[test]
+    fn test_toml_errors() {
+        assert_eq!(
+            toml_error("name = \"MCU\"\n[[peripherals]]\nname = \"A\"\nbase_adress = 0\n"),
+            "4:1: unknown key `base_adress` in peripheral, expected one of name, description, \
+             base_address, registers"
+        );
+        assert_eq!(
+            toml_error("name = \"MCU\"\n[[peripherals]]\nname = \"A\"\nbase_address = 1.5\n"),
+            "4:16: `base_address` must be an integer, not a float"
+        );
+        assert_eq!(
+            toml_error(
+                "name = \"MCU\"\nregisters = [\n  { name = \"A\", offset = 0, fields = [\n    \
+                 { name = \"X\", bits = [0, 3] },\n    { name = \"Y\", bits = [3, 4] },\n  ] },\n]\n"
+            ),
+            "5:5: A.Y: bits [3:4] overlap field X"
+        );
+        assert_eq!(
+            toml_error(
+                "name = \"MCU\"\nregisters = [\n  { name = \"A\", offset = 0, fields = [\n    \
+                 { name = \"X\", bits = 0, enum = \"Mode\" },\n  ] },\n]\n"
+            ),
+            "4:36: unknown enum Mode"
+        );
+        assert_eq!(
+            toml_error(
+                "name = \"MCU\"\nregisters = [\n  { name = \"A\", offset = 0 },\n  \
+                 { name = \"B\", offset = 2, size = 16 },\n]\n"
+            ),
+            "4:3: B at 0x2 overlaps register A at 0x0"
+        );
+        assert_eq!(
+            toml_error("name = \"MCU\"\nregisters = [{ name = \"A\", offset = 0, size = 12 }]\n"),
+            "2:47: `size` must be 8, 16, 32 or 64, not 12"
+        );
+        assert_eq!(
+            toml_error("description = \"MCU\"\n"),
+            "1:1: missing `name` in device"
+        );
+        assert!(toml_error("name = \n").starts_with("1:8: "));
+    }
+
+    #[test]
+    fn test_json_errors() {
+        assert_eq!(
+            json_error(
+                "{\n  \"name\": \"MCU\",\n  \"registers\": [\n    { \"name\": \"A\" }\n  ]\n}"
+            ),
+            "4:5: missing `offset` in register"
+        );
+        assert_eq!(
+            json_error(
+                "{\n  \"name\": \"MCU\",\n  \"registers\": [\n    \
+                 { \"name\": \"A\", \"offset\": \"0x1g\" }\n  ]\n}"
+            ),
+            "4:30: `offset` must be an unsigned 64 bit integer"
+        );
+        assert_eq!(
+            json_error("{\n  \"name\": \"MCU\",\n  \"name\": \"MCU\"\n}"),
+            "3:3: duplicate key `name`"
+        );
+        assert_eq!(
+            json_error("{\n  \"name\": \"MCU\"\n  \"registers\": []\n}"),
+            "3:3: expected `,` or `}`, found `\"`"
+        );
+        assert_eq!(
+            json_error(
+                "{ \"name\": \"MCU\", \"registers\": [{ \"name\": \"A\", \"offset\": -4 }] }"
+            ),
+            "1:57: `offset` must be an unsigned 64 bit integer"
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("0x4001_0000"), Some(0x4001_0000));
+        assert_eq!(parse_number("0b1010"), Some(10));
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("0xg"), None);
+    }
+}
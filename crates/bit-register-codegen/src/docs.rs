@@ -0,0 +1,547 @@
+use std::fmt::Write;
+
+use crate::rust::hex;
+use crate::{Access, Device, Error, Field, Peripheral, Register};
+
+/// Generate Markdown documentation of every register
+///
+/// Each register gets its address, size, access and reset value, a table of its bits from the
+/// most significant down with reserved gaps filled in, and a table of named values or lookup
+/// table values for every field which has them. Descriptions are written as they are, so they may contain Markdown.
+/// The device is validated first.
+pub fn generate_markdown(device: &Device) -> Result<String, Error> {
+    device.validate()?;
+
+    let mut out = String::new();
+    writeln!(out, "# {}", device.name).unwrap();
+    if let Some(description) = &device.description {
+        writeln!(out).unwrap();
+        writeln!(out, "{}", description).unwrap();
+    }
+
+    for peripheral in &device.peripherals {
+        writeln!(out).unwrap();
+        writeln!(out, "## {}", peripheral.name).unwrap();
+        if let Some(description) = &peripheral.description {
+            writeln!(out).unwrap();
+            writeln!(out, "{}", description).unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "Base address: `{}`",
+            hex(
+                peripheral.base_address,
+                address_bits(peripheral.base_address)
+            )
+        )
+        .unwrap();
+
+        for register in &peripheral.registers {
+            markdown_register(&mut out, peripheral, register);
+        }
+    }
+    Ok(out)
+}
+
+fn markdown_register(out: &mut String, peripheral: &Peripheral, register: &Register) {
+    writeln!(out).unwrap();
+    writeln!(out, "### {}", register.name).unwrap();
+    if let Some(description) = &register.description {
+        writeln!(out).unwrap();
+        writeln!(out, "{}", description).unwrap();
+    }
+
+    let summary = Summary::new(peripheral, register);
+    writeln!(out).unwrap();
+    writeln!(out, "| Address | Offset | Size | Access | Reset |").unwrap();
+    writeln!(out, "|---------|--------|------|--------|-------|").unwrap();
+    writeln!(
+        out,
+        "| `{}` | `{}` | {} bits | {} | {} |",
+        summary.address,
+        summary.offset,
+        register.size,
+        summary.access,
+        code_or_dash(summary.reset.as_deref())
+    )
+    .unwrap();
+
+    writeln!(out).unwrap();
+    writeln!(out, "| Bits | Field | Access | Reset | Description |").unwrap();
+    writeln!(out, "|------|-------|--------|-------|-------------|").unwrap();
+    for segment in segments(register) {
+        match segment.field {
+            Some(field) => writeln!(
+                out,
+                "| {} | `{}` | {} | {} | {} |",
+                segment.bits(),
+                field.name,
+                access_text(field.access.or(register.access)),
+                field_reset(register, field, |reset| format!("`{}`", reset)),
+                markdown_cell(&field_description(field))
+            ),
+            None => writeln!(out, "| {} | *Reserved* | | | |", segment.bits()),
+        }
+        .unwrap();
+    }
+
+    for field in &register.fields {
+        if let Some(enumeration) = &field.enumeration {
+            writeln!(out).unwrap();
+            writeln!(out, "#### `{}` values", field.name).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "| Value | Name | Description |").unwrap();
+            writeln!(out, "|-------|------|-------------|").unwrap();
+            for variant in &enumeration.variants {
+                writeln!(
+                    out,
+                    "| `{}` | `{}` | {} |",
+                    hex(variant.value, field.width()),
+                    variant.name,
+                    markdown_cell(variant.description.as_deref().unwrap_or(""))
+                )
+                .unwrap();
+            }
+        } else if !field.table.is_empty() {
+            writeln!(out).unwrap();
+            writeln!(out, "#### `{}` values", field.name).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "| Raw value | Value |").unwrap();
+            writeln!(out, "|-----------|-------|").unwrap();
+            for (raw, value) in field.table.iter().enumerate() {
+                writeln!(out, "| `{}` | {} |", hex(raw as u64, field.width()), value).unwrap();
+            }
+        }
+    }
+}
+
+/// Generate a static HTML page documenting every register
+///
+/// The page has the same content as [`generate_markdown`], with a diagram of each register's
+/// bits in rows of up to 16 bits in place of the Markdown bit numbers. Descriptions are
+/// escaped, with blank lines starting new paragraphs. The device is validated first.
+pub fn generate_html(device: &Device) -> Result<String, Error> {
+    device.validate()?;
+
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html lang=\"en\">").unwrap();
+    writeln!(out, "<head>").unwrap();
+    writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>{} registers</title>", escape(&device.name)).unwrap();
+    writeln!(out, "<style>").unwrap();
+    writeln!(
+        out,
+        "body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }}"
+    )
+    .unwrap();
+    writeln!(out, "table {{ border-collapse: collapse; margin: 1em 0; }}").unwrap();
+    writeln!(
+        out,
+        "th, td {{ border: 1px solid #999; padding: 0.2em 0.5em; text-align: left; }}"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "table.bits td {{ text-align: center; min-width: 2em; }}"
+    )
+    .unwrap();
+    writeln!(out, "td.reserved {{ background: #ddd; }}").unwrap();
+    writeln!(out, "</style>").unwrap();
+    writeln!(out, "</head>").unwrap();
+    writeln!(out, "<body>").unwrap();
+    writeln!(out, "<h1>{}</h1>", escape(&device.name)).unwrap();
+    html_description(&mut out, device.description.as_deref());
+
+    for peripheral in &device.peripherals {
+        writeln!(
+            out,
+            "<h2 id=\"{}\">{}</h2>",
+            escape(&peripheral.name),
+            escape(&peripheral.name)
+        )
+        .unwrap();
+        html_description(&mut out, peripheral.description.as_deref());
+        writeln!(
+            out,
+            "<p>Base address: <code>{}</code></p>",
+            hex(
+                peripheral.base_address,
+                address_bits(peripheral.base_address)
+            )
+        )
+        .unwrap();
+
+        for register in &peripheral.registers {
+            html_register(&mut out, peripheral, register);
+        }
+    }
+
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    Ok(out)
+}
+
+fn html_register(out: &mut String, peripheral: &Peripheral, register: &Register) {
+    writeln!(
+        out,
+        "<h3 id=\"{}-{}\">{}</h3>",
+        escape(&peripheral.name),
+        escape(&register.name),
+        escape(&register.name)
+    )
+    .unwrap();
+    html_description(out, register.description.as_deref());
+
+    let summary = Summary::new(peripheral, register);
+    writeln!(out, "<table>").unwrap();
+    writeln!(
+        out,
+        "<tr><th>Address</th><th>Offset</th><th>Size</th><th>Access</th><th>Reset</th></tr>"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{} bits</td><td>{}</td><td>{}</td></tr>",
+        summary.address,
+        summary.offset,
+        register.size,
+        summary.access,
+        summary
+            .reset
+            .map(|reset| format!("<code>{}</code>", reset))
+            .unwrap_or_else(|| "-".into())
+    )
+    .unwrap();
+    writeln!(out, "</table>").unwrap();
+
+    // Bit diagram, most significant bits first
+    let segments = segments(register);
+    writeln!(out, "<table class=\"bits\">").unwrap();
+    let row_bits = register.size.min(16);
+    for row in (0..register.size / row_bits).rev() {
+        let row_lsb = row * row_bits;
+        let row_msb = row_lsb + row_bits - 1;
+        write!(out, "<tr>").unwrap();
+        for bit in (row_lsb..=row_msb).rev() {
+            write!(out, "<th>{}</th>", bit).unwrap();
+        }
+        writeln!(out, "</tr>").unwrap();
+
+        write!(out, "<tr>").unwrap();
+        for segment in &segments {
+            if segment.msb < row_lsb || segment.lsb > row_msb {
+                continue;
+            }
+            let span = segment.msb.min(row_msb) - segment.lsb.max(row_lsb) + 1;
+            match segment.field {
+                Some(field) => write!(out, "<td colspan=\"{}\">{}</td>", span, escape(&field.name)),
+                None => write!(out, "<td colspan=\"{}\" class=\"reserved\"></td>", span),
+            }
+            .unwrap();
+        }
+        writeln!(out, "</tr>").unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+
+    writeln!(out, "<table>").unwrap();
+    writeln!(
+        out,
+        "<tr><th>Bits</th><th>Field</th><th>Access</th><th>Reset</th><th>Description</th></tr>"
+    )
+    .unwrap();
+    for segment in &segments {
+        match segment.field {
+            Some(field) => writeln!(
+                out,
+                "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                segment.bits(),
+                escape(&field.name),
+                access_text(field.access.or(register.access)),
+                field_reset(register, field, |reset| format!("<code>{}</code>", reset)),
+                escape(&field_description(field))
+            ),
+            None => writeln!(
+                out,
+                "<tr><td>{}</td><td><em>Reserved</em></td><td></td><td></td><td></td></tr>",
+                segment.bits()
+            ),
+        }
+        .unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+
+    for field in &register.fields {
+        if let Some(enumeration) = &field.enumeration {
+            writeln!(out, "<h4><code>{}</code> values</h4>", escape(&field.name)).unwrap();
+            writeln!(out, "<table>").unwrap();
+            writeln!(
+                out,
+                "<tr><th>Value</th><th>Name</th><th>Description</th></tr>"
+            )
+            .unwrap();
+            for variant in &enumeration.variants {
+                writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+                    hex(variant.value, field.width()),
+                    escape(&variant.name),
+                    escape(variant.description.as_deref().unwrap_or(""))
+                )
+                .unwrap();
+            }
+            writeln!(out, "</table>").unwrap();
+        } else if !field.table.is_empty() {
+            writeln!(out, "<h4><code>{}</code> values</h4>", escape(&field.name)).unwrap();
+            writeln!(out, "<table>").unwrap();
+            writeln!(out, "<tr><th>Raw value</th><th>Value</th></tr>").unwrap();
+            for (raw, value) in field.table.iter().enumerate() {
+                writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td>{}</td></tr>",
+                    hex(raw as u64, field.width()),
+                    value
+                )
+                .unwrap();
+            }
+            writeln!(out, "</table>").unwrap();
+        }
+    }
+}
+
+/// Write a description as paragraphs, split at blank lines
+fn html_description(out: &mut String, description: Option<&str>) {
+    let Some(description) = description else {
+        return;
+    };
+    for paragraph in description.split("\n\n") {
+        let paragraph = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !paragraph.is_empty() {
+            writeln!(out, "<p>{}</p>", escape(&paragraph)).unwrap();
+        }
+    }
+}
+
+/// Formatted address, offset, access and reset value of a register
+struct Summary {
+    address: String,
+    offset: String,
+    access: String,
+    reset: Option<String>,
+}
+
+impl Summary {
+    fn new(peripheral: &Peripheral, register: &Register) -> Self {
+        // Validation does not bound addresses, so wrap rather than fail the whole document
+        let address = peripheral.base_address.wrapping_add(register.offset);
+        Self {
+            address: hex(address, address_bits(address)),
+            offset: hex(register.offset, 8.max(64 - register.offset.leading_zeros())),
+            access: access_text(register.access),
+            reset: register.reset_value.map(|reset| hex(reset, register.size)),
+        }
+    }
+}
+
+/// A field or a reserved gap between fields
+struct Segment<'a> {
+    lsb: u32,
+    msb: u32,
+    field: Option<&'a Field>,
+}
+
+impl Segment<'_> {
+    fn bits(&self) -> String {
+        if self.lsb == self.msb {
+            self.lsb.to_string()
+        } else {
+            format!("{}:{}", self.msb, self.lsb)
+        }
+    }
+}
+
+/// Every bit of a register as fields and reserved gaps, most significant first
+fn segments(register: &Register) -> Vec<Segment<'_>> {
+    let mut fields: Vec<&Field> = register.fields.iter().collect();
+    fields.sort_by_key(|field| field.lsb);
+
+    let mut segments = Vec::new();
+    let mut next_bit = 0;
+    for field in fields {
+        if field.lsb > next_bit {
+            segments.push(Segment {
+                lsb: next_bit,
+                msb: field.lsb - 1,
+                field: None,
+            });
+        }
+        segments.push(Segment {
+            lsb: field.lsb,
+            msb: field.msb,
+            field: Some(field),
+        });
+        next_bit = field.msb + 1;
+    }
+    if next_bit < register.size {
+        segments.push(Segment {
+            lsb: next_bit,
+            msb: register.size - 1,
+            field: None,
+        });
+    }
+    segments.reverse();
+    segments
+}
+
+/// Reset value of a field formatted by `code`, followed by the name of the matching value
+fn field_reset(register: &Register, field: &Field, code: impl Fn(&str) -> String) -> String {
+    let Some(register_reset) = register.reset_value else {
+        return "-".into();
+    };
+    let reset = (register_reset >> field.lsb) & field.max_value();
+    let variant = field.enumeration.as_ref().and_then(|enumeration| {
+        enumeration
+            .variants
+            .iter()
+            .find(|variant| variant.value == reset)
+    });
+    let value = code(&hex(reset, field.width()));
+    match (variant, field.table.get(reset as usize)) {
+        (Some(variant), _) => format!("{} ({})", value, escape(&variant.name)),
+        (None, Some(table_value)) => format!("{} ({})", value, table_value),
+        (None, None) => value,
+    }
+}
+
+fn access_text(access: Option<Access>) -> String {
+    access.map_or_else(|| "-".into(), |access| access.to_string())
+}
+
+fn address_bits(address: u64) -> u32 {
+    32.max(64 - address.leading_zeros())
+}
+
+fn code_or_dash(text: Option<&str>) -> String {
+    text.map_or_else(|| "-".into(), |text| format!("`{}`", text))
+}
+
+/// The description of a field, followed by its valid values and scale if it has them
+fn field_description(field: &Field) -> String {
+    let mut description = field.description.clone().unwrap_or_default();
+    if !field.valid.is_empty() {
+        let valid: Vec<String> = field
+            .valid
+            .iter()
+            .map(|range| {
+                if range.start() == range.end() {
+                    range.start().to_string()
+                } else {
+                    format!("{} to {}", range.start(), range.end())
+                }
+            })
+            .collect();
+        start_sentence(&mut description);
+        write!(description, "Valid values: {}.", valid.join(", ")).unwrap();
+    }
+    if let Some(none) = field.none {
+        start_sentence(&mut description);
+        write!(description, "{} means no value.", none).unwrap();
+    }
+    if let Some(scale) = &field.scale {
+        start_sentence(&mut description);
+        write!(description, "Physical value: raw * {}", scale.scale).unwrap();
+        if scale.offset != 0 {
+            write!(description, " + {}", scale.offset).unwrap();
+        }
+        if !scale.unit.is_empty() {
+            write!(description, " {}", scale.unit).unwrap();
+        }
+        description.push('.');
+    }
+    description
+}
+
+/// End the sentence a description ends with, so another can follow it
+fn start_sentence(description: &mut String) {
+    if !description.is_empty() {
+        if !description.ends_with('.') {
+            description.push('.');
+        }
+        description.push(' ');
+    }
+}
+
+/// Keep a description on one line of a table, without ending its cell
+fn markdown_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let register = Register {
+            name: "CTRL".into(),
+            size: 8,
+            fields: vec![
+                Field {
+                    name: "MODE".into(),
+                    lsb: 4,
+                    msb: 5,
+                    ..Default::default()
+                },
+                Field {
+                    name: "EN".into(),
+                    lsb: 0,
+                    msb: 0,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let bits: Vec<_> = segments(&register)
+            .iter()
+            .map(|segment| {
+                (
+                    segment.bits(),
+                    segment.field.map(|field| field.name.as_str()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            bits,
+            [
+                ("7:6".to_string(), None),
+                ("5:4".to_string(), Some("MODE")),
+                ("3:1".to_string(), None),
+                ("0".to_string(), Some("EN")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+        assert_eq!(markdown_cell("a | b\n  c"), "a \\| b c");
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Position of a problem within a register description file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// Line number, starting at 1
+    pub line: u32,
+    /// Column number, starting at 1
+    pub column: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// An error reading a register description or generating code from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    location: Option<Location>,
+    path: Option<PathBuf>,
+}
+
+impl Error {
+    /// Create an error which is not tied to a position in the input
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: None,
+            path: None,
+        }
+    }
+
+    /// Create an error at a position in the input
+    pub fn at(location: Location, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: Some(location),
+            path: None,
+        }
+    }
+
+    /// Attach the path of the file the error occurred in
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Description of the error, without its location
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Position of the error in the input, if known
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+
+    /// Path of the file the error occurred in, if known
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.location) {
+            (Some(path), Some(location)) => {
+                write!(f, "{}:{}: {}", path.display(), location, self.message)
+            }
+            (Some(path), None) => write!(f, "{}: {}", path.display(), self.message),
+            (None, Some(location)) => write!(f, "{}: {}", location, self.message),
+            (None, None) => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::new(error.to_string())
+    }
+}
@@ -0,0 +1,155 @@
+//! Writing register descriptions as IP-XACT components
+//!
+//! [`write()`] produces an IEEE 1685-2014 component with a single memory map, in which every
+//! peripheral is an address block. Register reset values are split into the reset values of
+//! their fields, since IP-XACT 2014 only records resets per field. Fields with a single range of
+//! valid values get a `writeValueConstraint`, and fields with a lookup table get enumerated values
+//! named after the values in the table, such as `VALUE_16`.
+
+use crate::xml::{access_name, XmlWriter};
+use crate::{Device, Error, Field, Peripheral, Register};
+
+/// Vendor, library and version which identify a component alongside its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vlnv<'a> {
+    /// Organisation which owns the component, usually a domain name
+    pub vendor: &'a str,
+    /// Collection the component belongs to
+    pub library: &'a str,
+    /// Version of the component
+    pub version: &'a str,
+}
+
+/// Write a device as an IP-XACT component named after the device
+///
+/// The device is validated first.
+pub fn write(device: &Device, vlnv: Vlnv) -> Result<String, Error> {
+    device.validate()?;
+
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "ipxact:component \
+         xmlns:ipxact=\"http://www.accellera.org/XMLSchema/IPXACT/1685-2014\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.accellera.org/XMLSchema/IPXACT/1685-2014 \
+         http://www.accellera.org/XMLSchema/IPXACT/1685-2014/index.xsd\"",
+    );
+    xml.element("ipxact:vendor", vlnv.vendor);
+    xml.element("ipxact:library", vlnv.library);
+    xml.element("ipxact:name", &device.name);
+    xml.element("ipxact:version", vlnv.version);
+    if !device.peripherals.is_empty() {
+        xml.open("ipxact:memoryMaps");
+        xml.open("ipxact:memoryMap");
+        xml.element("ipxact:name", &device.name);
+        for peripheral in &device.peripherals {
+            write_peripheral(&mut xml, peripheral);
+        }
+        xml.element("ipxact:addressUnitBits", 8);
+        xml.close("ipxact:memoryMap");
+        xml.close("ipxact:memoryMaps");
+    }
+    if let Some(description) = &device.description {
+        xml.element("ipxact:description", description);
+    }
+    xml.close("ipxact:component");
+    Ok(xml.finish())
+}
+
+fn write_peripheral(xml: &mut XmlWriter, peripheral: &Peripheral) {
+    let range = peripheral
+        .registers
+        .iter()
+        .map(|register| register.offset + u64::from(register.size / 8))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let width = peripheral
+        .registers
+        .iter()
+        .map(|register| register.size)
+        .max()
+        .unwrap_or(32);
+
+    xml.open("ipxact:addressBlock");
+    xml.element("ipxact:name", &peripheral.name);
+    if let Some(description) = &peripheral.description {
+        xml.element("ipxact:description", description);
+    }
+    xml.element(
+        "ipxact:baseAddress",
+        format!("'h{:X}", peripheral.base_address),
+    );
+    xml.element("ipxact:range", format!("'h{:X}", range));
+    xml.element("ipxact:width", width);
+    xml.element("ipxact:usage", "register");
+    for register in &peripheral.registers {
+        write_register(xml, register);
+    }
+    xml.close("ipxact:addressBlock");
+}
+
+fn write_register(xml: &mut XmlWriter, register: &Register) {
+    xml.open("ipxact:register");
+    xml.element("ipxact:name", &register.name);
+    if let Some(description) = &register.description {
+        xml.element("ipxact:description", description);
+    }
+    xml.element("ipxact:addressOffset", format!("'h{:X}", register.offset));
+    xml.element("ipxact:size", register.size);
+    if let Some(access) = register.access {
+        xml.element("ipxact:access", access_name(access));
+    }
+    for field in &register.fields {
+        write_field(xml, field, register.reset_value);
+    }
+    xml.close("ipxact:register");
+}
+
+fn write_field(xml: &mut XmlWriter, field: &Field, register_reset: Option<u64>) {
+    xml.open("ipxact:field");
+    xml.element("ipxact:name", &field.name);
+    if let Some(description) = &field.description {
+        xml.element("ipxact:description", description);
+    }
+    xml.element("ipxact:bitOffset", field.lsb);
+    if let Some(reset_value) = register_reset {
+        xml.open("ipxact:resets");
+        xml.open("ipxact:reset");
+        xml.element(
+            "ipxact:value",
+            format!("'h{:X}", (reset_value >> field.lsb) & field.max_value()),
+        );
+        xml.close("ipxact:reset");
+        xml.close("ipxact:resets");
+    }
+    xml.element("ipxact:bitWidth", field.width());
+    if let Some(access) = field.access {
+        xml.element("ipxact:access", access_name(access));
+    }
+    let enumeration = field
+        .enumeration
+        .clone()
+        .or_else(|| field.table_enumeration());
+    if let Some(enumeration) = &enumeration {
+        xml.open("ipxact:enumeratedValues");
+        for variant in &enumeration.variants {
+            xml.open("ipxact:enumeratedValue");
+            xml.element("ipxact:name", &variant.name);
+            if let Some(description) = &variant.description {
+                xml.element("ipxact:description", description);
+            }
+            xml.element("ipxact:value", format!("'h{:X}", variant.value));
+            xml.close("ipxact:enumeratedValue");
+        }
+        xml.close("ipxact:enumeratedValues");
+    }
+    // IP-XACT can only constrain writes to a single range
+    if let [range] = field.valid.as_slice() {
+        xml.open("ipxact:writeValueConstraint");
+        xml.element("ipxact:minimum", format!("'h{:X}", range.start()));
+        xml.element("ipxact:maximum", format!("'h{:X}", range.end()));
+        xml.close("ipxact:writeValueConstraint");
+    }
+    xml.close("ipxact:field");
+}
@@ -0,0 +1,75 @@
+//! # Bit Register Codegen
+//!
+//! Generates [`bit_register!`](https://docs.rs/bit-register) definitions from register
+//! description files, so register layouts can be taken from vendor files instead of being
+//! transcribed by hand.
+//!
+//! ## Overview
+//!
+//! Descriptions are read into a common model of a [`Device`] made up of [`Peripheral`]s,
+//! [`Register`]s and [`Field`]s, which is then turned into Rust source by [`generate_rust`].
+//! Supported inputs are:
+//!
+//! - CMSIS-SVD files, read by [`svd::parse`]
+//! - SystemRDL files, read by [`rdl::parse`], for a practical subset of the language
+//! - TOML or JSON files in a simple schema of our own, read by [`description::parse_toml`] and
+//!   [`description::parse_json`], for devices without a vendor description
+//!
+//! Devices can also be described from `bit_register!` types with [`Register::of`], and written
+//! out by [`svd::write()`] and [`ipxact::write()`] for debuggers and hardware tools, or as a C
+//! header by [`generate_c_header`] so C code shares the same layouts. [`generate_markdown`] and
+//! [`generate_html`] render any device as register documentation.
+//!
+//! ## Generated Code
+//!
+//! Each peripheral becomes a module with its base address and a `bit_register!` struct for
+//! every register. Descriptions become doc comments, and access modes are noted in the docs
+//! since the macro does not enforce them. Fields with named values become enums, single bit
+//! fields become `bool` and other fields become the smallest unsigned integer which fits them.
+//! Every register struct has `OFFSET` and `RESET_VALUE` constants, and `ADDRESS_MAP` lists the
+//! absolute address of every register.
+//!
+//! ## Build Scripts
+//!
+//! [`Build`] generates definitions into `OUT_DIR` from a build script:
+//!
+//! ```no_run
+//! // In build.rs
+//! bit_register_codegen::Build::svd("device.svd")
+//!     .peripheral("TIMER0")
+//!     .output("registers.rs")
+//!     .generate()
+//!     .unwrap_or_else(|error| panic!("{}", error));
+//! ```
+//!
+//! The generated file is then included in the crate, which must depend on `bit-register`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/registers.rs"));
+//! ```
+//!
+//! ## Command Line
+//!
+//! The `svd2bitreg` binary generates definitions from an SVD file:
+//!
+//! ```text
+//! svd2bitreg device.svd --peripheral TIMER0 --output src/registers.rs
+//! ```
+
+mod build;
+mod c;
+pub mod description;
+mod docs;
+mod error;
+pub mod ipxact;
+mod model;
+pub mod rdl;
+mod rust;
+pub mod svd;
+mod xml;
+pub use build::*;
+pub use c::*;
+pub use docs::*;
+pub use error::*;
+pub use model::*;
+pub use rust::*;
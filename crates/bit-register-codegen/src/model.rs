@@ -0,0 +1,691 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use bit_register::{FieldInfo, FieldKind, RegisterInfo, RegisterLayout};
+
+use crate::Error;
+
+/// A device made up of peripherals
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Device {
+    /// Name of the device
+    pub name: String,
+    /// Description of the device
+    pub description: Option<String>,
+    /// Peripherals of the device, in declaration order
+    pub peripherals: Vec<Peripheral>,
+}
+
+impl Device {
+    /// Find a peripheral by name
+    pub fn peripheral(&self, name: &str) -> Option<&Peripheral> {
+        self.peripherals
+            .iter()
+            .find(|peripheral| peripheral.name == name)
+    }
+
+    /// Keep only the named peripherals, in the order they are declared
+    ///
+    /// Fails if a name does not match any peripheral. An empty list keeps every peripheral.
+    pub fn select<S: AsRef<str>>(&mut self, names: &[S]) -> Result<(), Error> {
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        for name in names {
+            if self.peripheral(name.as_ref()).is_none() {
+                return Err(Error::new(format!(
+                    "device {} has no peripheral named {}",
+                    self.name,
+                    name.as_ref()
+                )));
+            }
+        }
+        self.peripherals
+            .retain(|peripheral| names.iter().any(|name| name.as_ref() == peripheral.name));
+        Ok(())
+    }
+
+    /// Check that every register of the device can be represented with `bit_register!`
+    pub fn validate(&self) -> Result<(), Error> {
+        for peripheral in &self.peripherals {
+            for register in &peripheral.registers {
+                register.validate().map_err(|error| {
+                    Error::new(format!("{}.{}", peripheral.name, error.message()))
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A block of registers at a base address
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Peripheral {
+    /// Name of the peripheral
+    pub name: String,
+    /// Description of the peripheral
+    pub description: Option<String>,
+    /// Address of the peripheral
+    pub base_address: u64,
+    /// Registers of the peripheral, in declaration order
+    pub registers: Vec<Register>,
+}
+
+/// A register made up of bit fields
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Register {
+    /// Name of the register
+    pub name: String,
+    /// Description of the register
+    pub description: Option<String>,
+    /// Offset of the register from its peripheral's base address
+    pub offset: u64,
+    /// Number of bits in the register
+    pub size: u32,
+    /// How software may access the register
+    pub access: Option<Access>,
+    /// Value of the register after reset
+    pub reset_value: Option<u64>,
+    /// Fields of the register, in declaration order
+    pub fields: Vec<Field>,
+}
+
+impl Register {
+    /// Describe a register defined with `bit_register!`, at an offset from its peripheral
+    ///
+    /// Descriptions are taken from doc comments. The layout does not record access or reset
+    /// values, so those are left for the caller to fill in.
+    pub fn of<R: RegisterLayout>(offset: u64) -> Self {
+        Self::from_info(&R::LAYOUT, offset)
+    }
+
+    /// Describe a register from its layout, at an offset from its peripheral
+    pub fn from_info(info: &RegisterInfo, offset: u64) -> Self {
+        Self {
+            name: info.name.to_string(),
+            description: doc_text(info.doc),
+            offset,
+            size: info.bits,
+            fields: info.fields.iter().map(Field::from_info).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Check that the register can be represented with `bit_register!`
+    ///
+    /// The register must be 8, 16, 32 or 64 bits wide, and its fields must fit in the register
+    /// without overlapping. Named and valid values must fit in their fields, and scaled fields
+    /// need a scale other than zero, physical values which fit in 64 bits and no named values.
+    /// The raw value of an optional field which reads as no value must fit in the field, and
+    /// scaled fields cannot be optional. Lookup tables must have an entry for at most every raw
+    /// value, with values which fit in 64 bits, and fields with them cannot also have named
+    /// values, a scale or a none value.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !matches!(self.size, 8 | 16 | 32 | 64) {
+            return Err(Error::new(format!(
+                "{}: registers must be 8, 16, 32 or 64 bits wide, not {}",
+                self.name, self.size
+            )));
+        }
+
+        for (index, field) in self.fields.iter().enumerate() {
+            if field.lsb > field.msb || field.msb >= self.size {
+                return Err(Error::new(format!(
+                    "{}.{}: bits [{}:{}] do not fit in a {} bit register",
+                    self.name, field.name, field.lsb, field.msb, self.size
+                )));
+            }
+
+            if let Some(other) = self.fields[..index]
+                .iter()
+                .find(|other| other.lsb <= field.msb && field.lsb <= other.msb)
+            {
+                return Err(Error::new(format!(
+                    "{}.{}: bits [{}:{}] overlap field {}",
+                    self.name, field.name, field.lsb, field.msb, other.name
+                )));
+            }
+
+            if let Some(range) = field
+                .valid
+                .iter()
+                .find(|range| range.is_empty() || *range.end() > field.max_value())
+            {
+                return Err(Error::new(format!(
+                    "{}.{}: valid values {}..={} do not fit in {} bits",
+                    self.name,
+                    field.name,
+                    range.start(),
+                    range.end(),
+                    field.width()
+                )));
+            }
+
+            if field.scale.is_some() && field.enumeration.is_some() {
+                return Err(Error::new(format!(
+                    "{}.{}: scaled fields cannot have named values",
+                    self.name, field.name
+                )));
+            }
+            if field.scale.is_some() && field.none.is_some() {
+                return Err(Error::new(format!(
+                    "{}.{}: scaled fields cannot have a none value",
+                    self.name, field.name
+                )));
+            }
+            if let Some(none) = field.none.filter(|none| *none > field.max_value()) {
+                return Err(Error::new(format!(
+                    "{}.{}: none value {} does not fit in {} bits",
+                    self.name,
+                    field.name,
+                    none,
+                    field.width()
+                )));
+            }
+            if field.scale.as_ref().is_some_and(|scale| scale.scale == 0) {
+                return Err(Error::new(format!(
+                    "{}.{}: the scale must not be zero",
+                    self.name, field.name
+                )));
+            }
+            if let Some(range) = field
+                .physical_range()
+                .filter(|range| !fits_in_64_bits(range))
+            {
+                return Err(Error::new(format!(
+                    "{}.{}: physical values {}..={} do not fit in 64 bits",
+                    self.name,
+                    field.name,
+                    range.start(),
+                    range.end()
+                )));
+            }
+
+            if !field.table.is_empty()
+                && (field.enumeration.is_some() || field.scale.is_some() || field.none.is_some())
+            {
+                return Err(Error::new(format!(
+                    "{}.{}: fields with a lookup table cannot have named values, a scale or a none value",
+                    self.name, field.name
+                )));
+            }
+            if field.table.len() as u128 > field.max_value() as u128 + 1 {
+                return Err(Error::new(format!(
+                    "{}.{}: lookup table of {} values does not fit in {} bits",
+                    self.name,
+                    field.name,
+                    field.table.len(),
+                    field.width()
+                )));
+            }
+            if let Some(range) = field.table_range().filter(|range| !fits_in_64_bits(range)) {
+                return Err(Error::new(format!(
+                    "{}.{}: table values {}..={} do not fit in 64 bits",
+                    self.name,
+                    field.name,
+                    range.start(),
+                    range.end()
+                )));
+            }
+
+            if let Some(enumeration) = &field.enumeration {
+                for (index, variant) in enumeration.variants.iter().enumerate() {
+                    if let Some(other) = enumeration.variants[..index]
+                        .iter()
+                        .find(|other| other.value == variant.value)
+                    {
+                        return Err(Error::new(format!(
+                            "{}.{}: {} has the same value as {}",
+                            self.name, field.name, variant.name, other.name
+                        )));
+                    }
+                }
+
+                if let Some(variant) = enumeration
+                    .variants
+                    .iter()
+                    .find(|variant| variant.value > field.max_value())
+                {
+                    return Err(Error::new(format!(
+                        "{}.{}: value {} of {} does not fit in {} bits",
+                        self.name,
+                        field.name,
+                        variant.value,
+                        variant.name,
+                        field.width()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A range of bits within a register
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Field {
+    /// Name of the field
+    pub name: String,
+    /// Description of the field
+    pub description: Option<String>,
+    /// Lowest bit of the field
+    pub lsb: u32,
+    /// Highest bit of the field
+    pub msb: u32,
+    /// How software may access the field, if different from its register
+    pub access: Option<Access>,
+    /// How hardware may access the field, if the description says and hardware can access it
+    pub hardware_access: Option<Access>,
+    /// Named values of the field
+    pub enumeration: Option<Enumeration>,
+    /// Raw values the field accepts, or empty if it accepts any value which fits
+    pub valid: Vec<RangeInclusive<u64>>,
+    /// Conversion of the raw value to a physical value, if the field is scaled
+    pub scale: Option<Scale>,
+    /// Raw value which reads as no value, if the field is optional
+    pub none: Option<u64>,
+    /// Values of the field by raw value, or empty if it has no lookup table
+    pub table: Vec<i128>,
+}
+
+impl Field {
+    /// Describe a field from its layout
+    pub fn from_info(info: &FieldInfo) -> Self {
+        let enumeration = match info.kind {
+            FieldKind::Enum(variants) => Some(Enumeration {
+                name: None,
+                variants: variants
+                    .iter()
+                    .map(|variant| Variant {
+                        name: variant.name.to_string(),
+                        description: doc_text(variant.doc),
+                        value: variant.value,
+                    })
+                    .collect(),
+            }),
+            FieldKind::Bool
+            | FieldKind::Unsigned
+            | FieldKind::Fixed { .. }
+            | FieldKind::Bcd
+            | FieldKind::Gray => None,
+        };
+        Self {
+            name: info.name.to_string(),
+            description: doc_text(info.doc),
+            lsb: info.lsb,
+            msb: info.msb,
+            enumeration,
+            valid: info
+                .valid
+                .iter()
+                .map(|range| range.min..=range.max)
+                .collect(),
+            scale: info.scale.map(|scale| Scale {
+                scale: scale.scale,
+                offset: scale.offset,
+                unit: scale.unit.to_string(),
+            }),
+            none: info.none,
+            table: info.table.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Number of bits in the field
+    pub fn width(&self) -> u32 {
+        self.msb - self.lsb + 1
+    }
+
+    /// Largest value which fits in the field
+    pub fn max_value(&self) -> u64 {
+        if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.width()) - 1
+        }
+    }
+
+    /// Lowest and highest physical values of a scaled field
+    pub fn physical_range(&self) -> Option<RangeInclusive<i128>> {
+        self.scale.as_ref().map(|scale| {
+            let first = scale.physical(0);
+            let last = scale.physical(self.max_value());
+            first.min(last)..=first.max(last)
+        })
+    }
+
+    /// Lowest and highest values of a field with a lookup table
+    pub fn table_range(&self) -> Option<RangeInclusive<i128>> {
+        let min = self.table.iter().min()?;
+        let max = self.table.iter().max()?;
+        Some(*min..=*max)
+    }
+
+    /// Named values for the raw values of a field with a lookup table, for formats which have
+    /// no lookup tables
+    ///
+    /// Each raw value is named after its value in the table, such as `VALUE_16` or
+    /// `VALUE_MINUS_4`.
+    pub fn table_enumeration(&self) -> Option<Enumeration> {
+        (!self.table.is_empty()).then(|| Enumeration {
+            name: None,
+            variants: self
+                .table
+                .iter()
+                .enumerate()
+                .map(|(raw, value)| Variant {
+                    name: if *value < 0 {
+                        format!("VALUE_MINUS_{}", value.unsigned_abs())
+                    } else {
+                        format!("VALUE_{}", value)
+                    },
+                    description: None,
+                    value: raw as u64,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Whether every value of a range fits in an `i64` or every value fits in a `u64`
+fn fits_in_64_bits(range: &RangeInclusive<i128>) -> bool {
+    let signed = *range.start() >= i64::MIN as i128 && *range.end() <= i64::MAX as i128;
+    let unsigned = *range.start() >= 0 && *range.end() <= u64::MAX as i128;
+    signed || unsigned
+}
+
+/// Join the lines of doc comments into a description, or `None` if there are none
+fn doc_text(lines: &[&str]) -> Option<String> {
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+        .collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Named values of a field
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Enumeration {
+    /// Name of the enumeration, if it has one of its own
+    pub name: Option<String>,
+    /// The named values, in declaration order
+    pub variants: Vec<Variant>,
+}
+
+/// A single named value of a field
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Variant {
+    /// Name of the value
+    pub name: String,
+    /// Description of the value
+    pub description: Option<String>,
+    /// The raw value
+    pub value: u64,
+}
+
+/// Linear conversion of the raw value of a field to a physical value, `raw * scale + offset`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scale {
+    /// Physical value of one step of the raw value
+    pub scale: i64,
+    /// Physical value of a raw value of zero
+    pub offset: i64,
+    /// Unit of the physical value, or empty if it has none
+    pub unit: String,
+}
+
+impl Scale {
+    /// Physical value of a raw value
+    pub fn physical(&self, raw: u64) -> i128 {
+        raw as i128 * self.scale as i128 + self.offset as i128
+    }
+}
+
+/// How software may access a register or field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Can only be read
+    ReadOnly,
+    /// Can only be written
+    WriteOnly,
+    /// Can be read and written
+    ReadWrite,
+    /// Can only be written once after reset
+    WriteOnce,
+    /// Can be read, and written once after reset
+    ReadWriteOnce,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReadOnly => "read-only",
+            Self::WriteOnly => "write-only",
+            Self::ReadWrite => "read-write",
+            Self::WriteOnce => "write-once",
+            Self::ReadWriteOnce => "read-write-once",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bit_register::bit_register;
+
+    use super::*;
+
+    fn register(fields: Vec<Field>) -> Register {
+        Register {
+            name: "CTRL".into(),
+            size: 8,
+            fields,
+            ..Default::default()
+        }
+    }
+
+    fn field(name: &str, lsb: u32, msb: u32) -> Field {
+        Field {
+            name: name.into(),
+            lsb,
+            msb,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(register(vec![field("EN", 0, 0), field("MODE", 1, 7)])
+            .validate()
+            .is_ok());
+
+        let error = register(vec![field("MODE", 4, 8)]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.MODE: bits [4:8] do not fit in a 8 bit register"
+        );
+
+        let error = register(vec![field("EN", 0, 2), field("MODE", 2, 3)])
+            .validate()
+            .unwrap_err();
+        assert_eq!(error.message(), "CTRL.MODE: bits [2:3] overlap field EN");
+
+        let mut invalid_size = register(vec![]);
+        invalid_size.size = 24;
+        assert!(invalid_size.validate().is_err());
+
+        let mut mode = field("MODE", 0, 1);
+        mode.enumeration = Some(Enumeration {
+            name: None,
+            variants: vec![Variant {
+                name: "FAST".into(),
+                description: None,
+                value: 4,
+            }],
+        });
+        let error = register(vec![mode]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.MODE: value 4 of FAST does not fit in 2 bits"
+        );
+
+        let mut level = field("LEVEL", 0, 7);
+        level.scale = Some(Scale {
+            scale: -16,
+            offset: 0,
+            unit: "mV".into(),
+        });
+        assert_eq!(level.physical_range(), Some(-4080..=0));
+        assert!(register(vec![level.clone()]).validate().is_ok());
+        level.scale.as_mut().unwrap().scale = 0;
+        let error = register(vec![level.clone()]).validate().unwrap_err();
+        assert_eq!(error.message(), "CTRL.LEVEL: the scale must not be zero");
+        level.scale.as_mut().unwrap().scale = i64::MAX;
+        level.scale.as_mut().unwrap().offset = i64::MIN;
+        let error = register(vec![level.clone()]).validate().unwrap_err();
+        assert!(error
+            .message()
+            .starts_with("CTRL.LEVEL: physical values -9223372036854775808..="));
+        level.scale.as_mut().unwrap().offset = 0;
+        level.none = Some(0xff);
+        let error = register(vec![level]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.LEVEL: scaled fields cannot have a none value"
+        );
+
+        let mut timeout = field("TIMEOUT", 0, 1);
+        timeout.table = vec![1, 4, 16, 64];
+        assert_eq!(timeout.table_range(), Some(1..=64));
+        assert!(register(vec![timeout.clone()]).validate().is_ok());
+        timeout.table.push(256);
+        let error = register(vec![timeout.clone()]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.TIMEOUT: lookup table of 5 values does not fit in 2 bits"
+        );
+        timeout.table = vec![-1, u64::MAX.into()];
+        let error = register(vec![timeout.clone()]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.TIMEOUT: table values -1..=18446744073709551615 do not fit in 64 bits"
+        );
+        timeout.table = vec![1];
+        timeout.none = Some(3);
+        let error = register(vec![timeout]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.TIMEOUT: fields with a lookup table cannot have named values, a scale or a none value"
+        );
+
+        let mut channel = field("CHANNEL", 0, 3);
+        channel.none = Some(0xf);
+        assert!(register(vec![channel.clone()]).validate().is_ok());
+        channel.none = Some(0x10);
+        let error = register(vec![channel]).validate().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "CTRL.CHANNEL: none value 16 does not fit in 4 bits"
+        );
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Mode: u8 {
+            /// Stop after one period
+            OneShot = 0,
+            Periodic = 2,
+        }
+    }
+
+    bit_register! {
+        /// Timer control
+        ///
+        /// Changes apply at the next period
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct Ctrl: u16 {
+            /// Start the timer
+            enable: bool => [0],
+            mode: Mode => [1:2],
+            prescale: u8 => [8:15],
+            channel: Option<u8> => [4:7] none 0xf,
+            delay: i16 => [3] table [-4, 16],
+        }
+    }
+
+    #[test]
+    fn test_of() {
+        let ctrl = Register::of::<Ctrl>(0x10);
+        assert_eq!(ctrl.name, "Ctrl");
+        assert_eq!((ctrl.offset, ctrl.size), (0x10, 16));
+        assert_eq!(
+            ctrl.description.as_deref(),
+            Some("Timer control\n\nChanges apply at the next period")
+        );
+        assert_eq!(
+            ctrl.fields[0].description.as_deref(),
+            Some("Start the timer")
+        );
+        assert_eq!(ctrl.fields[2].description, None);
+        let fields: Vec<_> = ctrl
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.lsb, field.msb))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("enable", 0, 0),
+                ("mode", 1, 2),
+                ("prescale", 8, 15),
+                ("channel", 4, 7),
+                ("delay", 3, 3)
+            ]
+        );
+        assert_eq!(ctrl.fields[2].none, None);
+        assert_eq!(ctrl.fields[3].none, Some(0xf));
+        assert_eq!(ctrl.fields[4].table, [-4, 16]);
+        let delay = ctrl.fields[4].table_enumeration().unwrap();
+        let variants: Vec<_> = delay
+            .variants
+            .iter()
+            .map(|variant| (variant.name.as_str(), variant.value))
+            .collect();
+        assert_eq!(variants, [("VALUE_MINUS_4", 0), ("VALUE_16", 1)]);
+        assert_eq!(ctrl.fields[0].table_enumeration(), None);
+
+        let mode = ctrl.fields[1].enumeration.as_ref().unwrap();
+        let variants: Vec<_> = mode
+            .variants
+            .iter()
+            .map(|variant| (variant.name.as_str(), variant.value))
+            .collect();
+        assert_eq!(variants, [("OneShot", 0), ("Periodic", 2)]);
+        assert_eq!(
+            mode.variants[0].description.as_deref(),
+            Some("Stop after one period")
+        );
+        assert!(ctrl.validate().is_ok());
+    }
+
+    #[test]
+    fn test_select() {
+        let mut device = Device {
+            name: "MCU".into(),
+            peripherals: ["TIMER0", "UART0", "TIMER1"]
+                .into_iter()
+                .map(|name| Peripheral {
+                    name: name.into(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert!(device.select(&["SPI0"]).is_err());
+        device.select(&["TIMER1", "TIMER0"]).unwrap();
+        let names: Vec<_> = device.peripherals.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["TIMER0", "TIMER1"]);
+    }
+}
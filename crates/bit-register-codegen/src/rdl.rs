@@ -0,0 +1,1458 @@
+//! Reading register descriptions from SystemRDL files
+//!
+//! A practical subset of SystemRDL 2.0 is supported: `addrmap`, `regfile`, `reg` and `field`
+//! components, defined by name or anonymously, their instances and arrays, `enum` definitions,
+//! `default` property assignments and the properties which describe the software view of a
+//! register. Constructs outside the subset, such as `mem`, `signal`, parameters, dynamic
+//! property assignments and preprocessor directives, are reported as errors at their location.
+//!
+//! The root is the last `addrmap` defined at the top level which is not instantiated by another
+//! component. Each `addrmap` instantiated in the root becomes a [`Peripheral`] at its address,
+//! and registers instantiated directly in the root are collected in a first peripheral named
+//! after the root. Register files and nested address maps are flattened, with their registers
+//! named `<instance>_<register>`. Arrays become one element per index, named `<instance><index>`.
+//! Instances without an address are placed according to the `addressing` mode of their
+//! address map.
+//!
+//! Fields take their software access from `sw`, which defaults to `rw`, and their hardware
+//! access from `hw` if it is given. Fields which software cannot access (`sw = na`) are left out.
+//! Registers take the access their fields share, and their reset value from the `reset` values
+//! of their fields. Properties which only describe hardware behaviour, such as `swmod` or
+//! `woclr`, are accepted and ignored.
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{Access, Device, Enumeration, Error, Field, Location, Peripheral, Register, Variant};
+
+/// Read a device from the contents of a SystemRDL file
+pub fn parse(source: &str) -> Result<Device, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        scopes: vec![Vec::new()],
+        defaults: vec![Vec::new()],
+        instantiated: Vec::new(),
+    };
+    let body = parser.body(None)?;
+
+    let root = body
+        .definitions
+        .iter()
+        .rev()
+        .find(|definition| {
+            !parser
+                .instantiated
+                .iter()
+                .any(|instantiated| Rc::ptr_eq(instantiated, definition))
+        })
+        .ok_or_else(|| Error::new("no top-level addrmap definition to generate"))?;
+    device(root)
+}
+
+// Tokens
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u64),
+    String(String),
+    Punct(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(ident) => write!(f, "`{}`", ident),
+            Self::Number(number) => write!(f, "`{}`", number),
+            Self::String(_) => f.write_str("a string"),
+            Self::Punct(punct) => write!(f, "`{}`", punct),
+            Self::End => f.write_str("the end of the file"),
+        }
+    }
+}
+
+/// Punctuation, longest first so `+=` is not read as `+`
+const PUNCTUATION: &[&str] = &[
+    "+=", "%=", "->", "{", "}", "[", "]", "(", ")", ";", "=", ",", "@", ":", ".", "#",
+];
+
+struct Lexer {
+    chars: Vec<char>,
+    index: usize,
+    line: u32,
+    column: u32,
+}
+
+impl Lexer {
+    fn location(&self) -> Location {
+        Location {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek(0).filter(|c| predicate(*c)) {
+            taken.push(c);
+            self.next();
+        }
+        taken
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), Error> {
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.next();
+                }
+                (Some('/'), Some('/')) => {
+                    self.take_while(|c| c != '\n');
+                }
+                (Some('/'), Some('*')) => {
+                    let location = self.location();
+                    self.next();
+                    self.next();
+                    while (self.peek(0), self.peek(1)) != (Some('*'), Some('/')) {
+                        if self.next().is_none() {
+                            return Err(Error::at(location, "unterminated comment"));
+                        }
+                    }
+                    self.next();
+                    self.next();
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Read a decimal or `0x` hex number, or a Verilog style number such as `4'hf`
+    fn number(&mut self, location: Location) -> Result<u64, Error> {
+        let digits = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let value = parse_integer(&digits)
+            .ok_or_else(|| Error::at(location, format!("invalid number `{}`", digits)))?;
+        if self.peek(0) != Some('\'') {
+            return Ok(value);
+        }
+
+        self.next();
+        let width = u32::try_from(value)
+            .ok()
+            .filter(|width| (1..=64).contains(width))
+            .ok_or_else(|| Error::at(location, format!("invalid number width {}", value)))?;
+        let radix = match self.next().map(|c| c.to_ascii_lowercase()) {
+            Some('b') => 2,
+            Some('o') => 8,
+            Some('d') => 10,
+            Some('h') => 16,
+            _ => {
+                return Err(Error::at(
+                    location,
+                    "expected `b`, `o`, `d` or `h` after the width of a number",
+                ))
+            }
+        };
+        let digits = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let value = u64::from_str_radix(&digits.replace('_', ""), radix)
+            .map_err(|_| Error::at(location, format!("invalid number `{}'{}`", width, digits)))?;
+        if width < 64 && value >> width != 0 {
+            return Err(Error::at(
+                location,
+                format!("{} does not fit in {} bits", value, width),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn string(&mut self, location: Location) -> Result<String, Error> {
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.next() {
+                    Some(c) => string.push(c),
+                    None => break,
+                },
+                Some(c) => string.push(c),
+                None => break,
+            }
+        }
+        Err(Error::at(location, "unterminated string"))
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Location)>, Error> {
+    let mut lexer = Lexer {
+        chars: source.chars().collect(),
+        index: 0,
+        line: 1,
+        column: 1,
+    };
+
+    let mut tokens = Vec::new();
+    loop {
+        lexer.skip_whitespace()?;
+        let location = lexer.location();
+        let Some(c) = lexer.peek(0) else {
+            tokens.push((Token::End, location));
+            return Ok(tokens);
+        };
+
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            Token::Ident(lexer.take_while(|c| c.is_ascii_alphanumeric() || c == '_'))
+        } else if c.is_ascii_digit() {
+            Token::Number(lexer.number(location)?)
+        } else if c == '"' {
+            Token::String(lexer.string(location)?)
+        } else if c == '`' {
+            return Err(Error::at(
+                location,
+                "preprocessor directives are not supported",
+            ));
+        } else if c == '<' && lexer.peek(1) == Some('%') {
+            return Err(Error::at(location, "embedded Perl is not supported"));
+        } else {
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| {
+                    punct
+                        .chars()
+                        .enumerate()
+                        .all(|(offset, c)| lexer.peek(offset) == Some(c))
+                })
+                .ok_or_else(|| Error::at(location, format!("unexpected character `{}`", c)))?;
+            for _ in punct.chars() {
+                lexer.next();
+            }
+            Token::Punct(punct)
+        };
+        tokens.push((token, location));
+    }
+}
+
+/// Parse a plain number, which may be decimal or `0x` hex
+fn parse_integer(raw: &str) -> Option<u64> {
+    match raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => raw.parse().ok(),
+    }
+}
+
+// Syntax
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Addrmap,
+    Regfile,
+    Reg,
+    Field,
+}
+
+impl Kind {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "addrmap" => Some(Self::Addrmap),
+            "regfile" => Some(Self::Regfile),
+            "reg" => Some(Self::Reg),
+            "field" => Some(Self::Field),
+            _ => None,
+        }
+    }
+
+    fn can_contain(self, child: Kind) -> bool {
+        match self {
+            Self::Addrmap => matches!(child, Self::Addrmap | Self::Regfile | Self::Reg),
+            Self::Regfile => matches!(child, Self::Regfile | Self::Reg),
+            Self::Reg => child == Self::Field,
+            Self::Field => false,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Addrmap => "addrmap",
+            Self::Regfile => "regfile",
+            Self::Reg => "reg",
+            Self::Field => "field",
+        })
+    }
+}
+
+/// Properties which describe the software view, and the components they apply to
+const PROPERTIES: &[(&str, &[Kind])] = &[
+    (
+        "name",
+        &[Kind::Addrmap, Kind::Regfile, Kind::Reg, Kind::Field],
+    ),
+    (
+        "desc",
+        &[Kind::Addrmap, Kind::Regfile, Kind::Reg, Kind::Field],
+    ),
+    (
+        "ispresent",
+        &[Kind::Addrmap, Kind::Regfile, Kind::Reg, Kind::Field],
+    ),
+    ("sw", &[Kind::Field]),
+    ("hw", &[Kind::Field]),
+    ("reset", &[Kind::Field]),
+    ("fieldwidth", &[Kind::Field]),
+    ("encode", &[Kind::Field]),
+    ("regwidth", &[Kind::Reg]),
+    ("accesswidth", &[Kind::Reg]),
+    ("addressing", &[Kind::Addrmap]),
+    ("alignment", &[Kind::Addrmap, Kind::Regfile]),
+    ("lsb0", &[Kind::Addrmap]),
+];
+
+/// Properties which only describe hardware behaviour, and are ignored
+const IGNORED_PROPERTIES: &[&str] = &[
+    "anded",
+    "bigendian",
+    "bridge",
+    "counter",
+    "dontcompare",
+    "donttest",
+    "enable",
+    "errextbus",
+    "haltenable",
+    "haltmask",
+    "hdl_path",
+    "hdl_path_gate",
+    "hwclr",
+    "hwenable",
+    "hwmask",
+    "hwset",
+    "intr",
+    "littleendian",
+    "mask",
+    "onread",
+    "onwrite",
+    "ored",
+    "paritycheck",
+    "precedence",
+    "rclr",
+    "rset",
+    "shared",
+    "sharedextbus",
+    "singlepulse",
+    "stickybit",
+    "sticky",
+    "swacc",
+    "swmod",
+    "swwe",
+    "swwel",
+    "we",
+    "wel",
+    "woclr",
+    "woset",
+    "xored",
+];
+
+/// Keywords of SystemRDL constructs outside the supported subset
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "abstract",
+    "alias",
+    "constraint",
+    "mem",
+    "property",
+    "signal",
+    "struct",
+];
+
+#[derive(Debug)]
+struct Component {
+    kind: Kind,
+    name: Option<String>,
+    properties: Vec<Property>,
+    /// Default property values in effect where the component is defined
+    defaults: Vec<Property>,
+    instances: Vec<Instance>,
+}
+
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    value: Value,
+    location: Location,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(u64),
+    Bool(bool),
+    String(String),
+    Ident(String),
+    Enum(Rc<Enumeration>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Brackets {
+    Size(u64),
+    Range(u64, u64),
+}
+
+#[derive(Debug)]
+struct Instance {
+    component: Rc<Component>,
+    name: String,
+    location: Location,
+    brackets: Option<Brackets>,
+    reset: Option<(Value, Location)>,
+    address: Option<u64>,
+    stride: Option<u64>,
+    alignment: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+enum Definition {
+    Component(Rc<Component>),
+    Enum(Rc<Enumeration>),
+}
+
+/// The contents of a component, or of the file
+#[derive(Debug, Default)]
+struct Body {
+    properties: Vec<Property>,
+    instances: Vec<Instance>,
+    /// `addrmap` definitions at the top level
+    definitions: Vec<Rc<Component>>,
+}
+
+struct Parser {
+    tokens: Vec<(Token, Location)>,
+    position: usize,
+    /// Named definitions visible in each enclosing scope
+    scopes: Vec<Vec<(String, Definition)>>,
+    /// `default` assignments made in each enclosing scope
+    defaults: Vec<Vec<Property>>,
+    instantiated: Vec<Rc<Component>>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn location(&self) -> Location {
+        self.tokens[self.position].1
+    }
+
+    fn next(&mut self) -> (Token, Location) {
+        let token = self.tokens[self.position].clone();
+        if token.0 != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Token::Punct(found) if *found == punct);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", punct)))
+        }
+    }
+
+    fn expected(&self, what: &str) -> Error {
+        Error::at(
+            self.location(),
+            format!("expected {}, found {}", what, self.peek()),
+        )
+    }
+
+    fn ident(&mut self, what: &str) -> Result<(String, Location), Error> {
+        match self.next() {
+            (Token::Ident(ident), location) => Ok((ident, location)),
+            (token, location) => Err(Error::at(
+                location,
+                format!("expected {}, found {}", what, token),
+            )),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, Error> {
+        match self.next() {
+            (Token::Number(number), _) => Ok(number),
+            (token, location) => Err(Error::at(
+                location,
+                format!("expected a number, found {}", token),
+            )),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Definition> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(defined, _)| defined == name)
+            .map(|(_, definition)| definition)
+    }
+
+    fn define(&mut self, name: String, definition: Definition) {
+        self.scopes.last_mut().unwrap().push((name, definition));
+    }
+
+    /// Parse the contents of a component up to its closing brace, or of the file
+    fn body(&mut self, parent: Option<Kind>) -> Result<Body, Error> {
+        let mut body = Body::default();
+        loop {
+            match (self.peek(), parent) {
+                (Token::Punct("}"), Some(_)) | (Token::End, None) => return Ok(body),
+                (Token::End, Some(_)) => return Err(self.expected("`}`")),
+                _ => self.item(parent, &mut body)?,
+            }
+        }
+    }
+
+    fn item(&mut self, parent: Option<Kind>, body: &mut Body) -> Result<(), Error> {
+        let (keyword, location) = self.ident("a definition, instance or property")?;
+        match keyword.as_str() {
+            // Only affects how hardware implements the component
+            "external" | "internal" => self.item(parent, body),
+            "default" => {
+                let property = self.property()?;
+                self.defaults.last_mut().unwrap().push(property);
+                Ok(())
+            }
+            "enum" => {
+                let enumeration = self.enumeration()?;
+                let name = enumeration.name.clone().unwrap_or_default();
+                self.define(name, Definition::Enum(enumeration));
+                Ok(())
+            }
+            _ if UNSUPPORTED_KEYWORDS.contains(&keyword.as_str()) => Err(Error::at(
+                location,
+                format!("`{}` is not supported", keyword),
+            )),
+            _ => {
+                if let Some(kind) = Kind::from_keyword(&keyword) {
+                    let component = self.component(kind)?;
+                    if let Some(name) = &component.name {
+                        self.define(name.clone(), Definition::Component(component.clone()));
+                        if parent.is_none() && kind == Kind::Addrmap {
+                            body.definitions.push(component.clone());
+                        }
+                        if self.eat(";") {
+                            return Ok(());
+                        }
+                    }
+                    return self.instances(component, parent, body);
+                }
+
+                match self.peek() {
+                    Token::Ident(_) => match self.lookup(&keyword) {
+                        Some(Definition::Component(component)) => {
+                            let component = component.clone();
+                            self.instances(component, parent, body)
+                        }
+                        _ => Err(Error::at(
+                            location,
+                            format!("unknown component type `{}`", keyword),
+                        )),
+                    },
+                    Token::Punct("=") | Token::Punct(";") if parent.is_some() => {
+                        self.position -= 1;
+                        let property = self.property()?;
+                        body.properties.push(property);
+                        Ok(())
+                    }
+                    Token::Punct("->") | Token::Punct(".") => Err(Error::at(
+                        location,
+                        "dynamic property assignments are not supported",
+                    )),
+                    Token::Punct("#") => Err(Error::at(location, "parameters are not supported")),
+                    _ => Err(self.expected("an instance name or `=`")),
+                }
+            }
+        }
+    }
+
+    /// Parse a component definition after its keyword
+    fn component(&mut self, kind: Kind) -> Result<Rc<Component>, Error> {
+        let name = match self.peek() {
+            Token::Ident(_) => Some(self.ident("a name")?.0),
+            _ => None,
+        };
+        if matches!(self.peek(), Token::Punct("#")) {
+            return Err(Error::at(self.location(), "parameters are not supported"));
+        }
+        self.expect("{")?;
+
+        let defaults = self.defaults.concat();
+        self.scopes.push(Vec::new());
+        self.defaults.push(Vec::new());
+        let body = self.body(Some(kind));
+        self.scopes.pop();
+        self.defaults.pop();
+        let body = body?;
+        self.expect("}")?;
+
+        for property in &body.properties {
+            let applies = PROPERTIES
+                .iter()
+                .find(|(name, _)| *name == property.name)
+                .is_none_or(|(_, kinds)| kinds.contains(&kind));
+            if !applies {
+                return Err(Error::at(
+                    property.location,
+                    format!("`{}` does not apply to {} components", property.name, kind),
+                ));
+            }
+        }
+
+        Ok(Rc::new(Component {
+            kind,
+            name,
+            properties: body.properties,
+            defaults,
+            instances: body.instances,
+        }))
+    }
+
+    /// Parse a property assignment, `name = value;` or `name;` for `true`
+    fn property(&mut self) -> Result<Property, Error> {
+        let (name, location) = self.ident("a property name")?;
+        if name == "msb0" {
+            return Err(Error::at(location, "msb0 bit ordering is not supported"));
+        }
+        if !PROPERTIES.iter().any(|(known, _)| *known == name)
+            && !IGNORED_PROPERTIES.contains(&name.as_str())
+        {
+            return Err(Error::at(
+                location,
+                format!("unsupported property `{}`", name),
+            ));
+        }
+
+        let value = if self.eat("=") {
+            self.value(&name)?
+        } else {
+            Value::Bool(true)
+        };
+        self.expect(";")?;
+        Ok(Property {
+            name,
+            value,
+            location,
+        })
+    }
+
+    fn value(&mut self, property: &str) -> Result<Value, Error> {
+        match self.next() {
+            (Token::Number(number), _) => Ok(Value::Number(number)),
+            (Token::String(string), _) => Ok(Value::String(string)),
+            (Token::Ident(ident), _) if ident == "true" => Ok(Value::Bool(true)),
+            (Token::Ident(ident), _) if ident == "false" => Ok(Value::Bool(false)),
+            (Token::Ident(ident), location) if property == "encode" => match self.lookup(&ident) {
+                Some(Definition::Enum(enumeration)) => Ok(Value::Enum(enumeration.clone())),
+                _ => Err(Error::at(location, format!("unknown enum `{}`", ident))),
+            },
+            (Token::Ident(ident), _) => Ok(Value::Ident(ident)),
+            (token, location) => Err(Error::at(
+                location,
+                format!("expected a value, found {}", token),
+            )),
+        }
+    }
+
+    /// Parse an enum definition after its keyword
+    fn enumeration(&mut self) -> Result<Rc<Enumeration>, Error> {
+        let (name, _) = self.ident("an enum name")?;
+        self.expect("{")?;
+
+        let mut variants: Vec<Variant> = Vec::new();
+        let mut next_value = 0;
+        while !self.eat("}") {
+            let (variant, _) = self.ident("an enum entry")?;
+            let value = if self.eat("=") {
+                self.number()?
+            } else {
+                next_value
+            };
+
+            let mut description = None;
+            let mut display_name = None;
+            if self.eat("{") {
+                while !self.eat("}") {
+                    let property = self.property()?;
+                    let target = match property.name.as_str() {
+                        "desc" => &mut description,
+                        "name" => &mut display_name,
+                        _ => {
+                            return Err(Error::at(
+                                property.location,
+                                "enum entries only support `name` and `desc`",
+                            ))
+                        }
+                    };
+                    *target = Some(string(&property)?);
+                }
+            }
+            self.expect(";")?;
+
+            variants.push(Variant {
+                name: variant,
+                description: description.or(display_name),
+                value,
+            });
+            next_value = value.wrapping_add(1);
+        }
+        self.expect(";")?;
+
+        Ok(Rc::new(Enumeration {
+            name: Some(name),
+            variants,
+        }))
+    }
+
+    /// Parse the instances following a component definition or type name
+    fn instances(
+        &mut self,
+        component: Rc<Component>,
+        parent: Option<Kind>,
+        body: &mut Body,
+    ) -> Result<(), Error> {
+        let location = self.location();
+        match parent {
+            Some(parent) if parent.can_contain(component.kind) => {}
+            Some(parent) => {
+                return Err(Error::at(
+                    location,
+                    format!(
+                        "{} components cannot be instantiated in a {}",
+                        component.kind, parent
+                    ),
+                ))
+            }
+            None => {
+                return Err(Error::at(
+                    location,
+                    "instances must be inside an addrmap, regfile or reg",
+                ))
+            }
+        }
+        self.instantiated.push(component.clone());
+
+        loop {
+            let (name, location) = self.ident("an instance name")?;
+            let mut instance = Instance {
+                component: component.clone(),
+                name,
+                location,
+                brackets: None,
+                reset: None,
+                address: None,
+                stride: None,
+                alignment: None,
+            };
+
+            if self.eat("[") {
+                let first = self.number()?;
+                instance.brackets = Some(if self.eat(":") {
+                    Brackets::Range(first, self.number()?)
+                } else {
+                    Brackets::Size(first)
+                });
+                self.expect("]")?;
+                if matches!(self.peek(), Token::Punct("[")) {
+                    return Err(Error::at(
+                        self.location(),
+                        "multi-dimensional arrays are not supported",
+                    ));
+                }
+            }
+            if self.eat("=") {
+                let location = self.location();
+                instance.reset = Some((self.value("reset")?, location));
+            }
+            if self.eat("@") {
+                instance.address = Some(self.number()?);
+            }
+            if self.eat("+=") {
+                instance.stride = Some(self.number()?);
+            }
+            if self.eat("%=") {
+                instance.alignment = Some(self.number()?);
+            }
+
+            body.instances.push(instance);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+}
+
+// Elaboration
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .rev()
+            .chain(self.defaults.iter().rev())
+            .find(|property| property.name == name)
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u64>, Error> {
+        match self.property(name) {
+            Some(Property {
+                value: Value::Number(number),
+                ..
+            }) => Ok(Some(*number)),
+            Some(property) => Err(Error::at(
+                property.location,
+                format!("`{}` must be a number", name),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn ident(&self, name: &str) -> Option<(&str, Location)> {
+        match self.property(name) {
+            Some(Property {
+                value: Value::Ident(ident),
+                location,
+                ..
+            }) => Some((ident, *location)),
+            _ => None,
+        }
+    }
+
+    fn is_present(&self) -> Result<bool, Error> {
+        match self.property("ispresent") {
+            Some(Property {
+                value: Value::Bool(present),
+                ..
+            }) => Ok(*present),
+            Some(property) => Err(Error::at(
+                property.location,
+                "`ispresent` must be a boolean",
+            )),
+            None => Ok(true),
+        }
+    }
+
+    /// The component's `desc`, or its `name` if it has no description
+    fn description(&self) -> Result<Option<String>, Error> {
+        match self.property("desc").or_else(|| self.property("name")) {
+            Some(property) => string(property).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+fn string(property: &Property) -> Result<String, Error> {
+    match &property.value {
+        Value::String(string) => Ok(string.clone()),
+        _ => Err(Error::at(
+            property.location,
+            format!("`{}` must be a string", property.name),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Addressing {
+    Compact,
+    Regalign,
+    Fullalign,
+}
+
+fn device(root: &Component) -> Result<Device, Error> {
+    let name = root.name.clone().unwrap_or_default();
+    let mut peripherals = Vec::new();
+    let (registers, _) = block(root, addressing(root)?, Some(&mut peripherals))?;
+    if !registers.is_empty() {
+        peripherals.insert(
+            0,
+            Peripheral {
+                name: name.clone(),
+                description: None,
+                base_address: 0,
+                registers,
+            },
+        );
+    }
+
+    Ok(Device {
+        name,
+        description: root.description()?,
+        peripherals,
+    })
+}
+
+fn addressing(addrmap: &Component) -> Result<Addressing, Error> {
+    match addrmap.ident("addressing") {
+        None | Some(("regalign", _)) => Ok(Addressing::Regalign),
+        Some(("compact", _)) => Ok(Addressing::Compact),
+        Some(("fullalign", _)) => Ok(Addressing::Fullalign),
+        Some((other, location)) => Err(Error::at(
+            location,
+            format!(
+                "invalid addressing `{}`, expected compact, regalign or fullalign",
+                other
+            ),
+        )),
+    }
+}
+
+/// Place the instances of an address map or register file, returning its registers with
+/// offsets relative to it and its size in bytes
+///
+/// Address maps become peripherals instead of being flattened when `peripherals` is given.
+fn block(
+    component: &Component,
+    addressing: Addressing,
+    mut peripherals: Option<&mut Vec<Peripheral>>,
+) -> Result<(Vec<Register>, u64), Error> {
+    let mut registers = Vec::new();
+    let mut placed: Vec<(u64, u64, String)> = Vec::new();
+    let mut next = 0u64;
+
+    for instance in &component.instances {
+        let child = &instance.component;
+        if !child.is_present()? {
+            continue;
+        }
+
+        let count = match instance.brackets {
+            None => None,
+            Some(Brackets::Size(0)) => {
+                return Err(Error::at(instance.location, "arrays must not be empty"))
+            }
+            Some(Brackets::Size(count)) => Some(count),
+            Some(Brackets::Range(..)) => {
+                return Err(Error::at(
+                    instance.location,
+                    "bit ranges are only allowed on field instances",
+                ))
+            }
+        };
+        if instance.reset.is_some() {
+            return Err(Error::at(
+                instance.location,
+                "only field instances can have a reset value",
+            ));
+        }
+
+        // Registers of a single element, its size and its natural alignment
+        let (element, size, mut alignment) = if child.kind == Kind::Reg {
+            let register = register(child, &instance.name, instance.location)?;
+            let bytes = u64::from(register.size / 8);
+            let alignment = match addressing {
+                Addressing::Compact => child.number("accesswidth")?.unwrap_or(bytes * 8) / 8,
+                _ => bytes,
+            };
+            (vec![register], bytes, alignment)
+        } else {
+            let child_addressing = match child.kind {
+                Kind::Addrmap => self::addressing(child)?,
+                _ => addressing,
+            };
+            let (element, size) = block(child, child_addressing, None)?;
+            let alignment = match child.number("alignment")? {
+                Some(alignment) => alignment,
+                None if addressing == Addressing::Compact => 1,
+                None => element
+                    .iter()
+                    .map(|register| u64::from(register.size / 8))
+                    .max()
+                    .unwrap_or(1),
+            };
+            (element, size, alignment)
+        };
+        if addressing == Addressing::Fullalign {
+            alignment = alignment.max(size.next_power_of_two());
+        }
+
+        let stride = match instance.stride {
+            Some(stride) if stride < size => {
+                return Err(Error::at(
+                    instance.location,
+                    format!(
+                        "stride {:#x} is smaller than the {:#x} bytes of {}",
+                        stride, size, instance.name
+                    ),
+                ))
+            }
+            Some(stride) => stride,
+            None => size.next_multiple_of(alignment.max(1)),
+        };
+        if let Some(count) = count {
+            if addressing == Addressing::Fullalign {
+                alignment = alignment.max(stride.saturating_mul(count).next_power_of_two());
+            }
+        }
+        let alignment = instance.alignment.unwrap_or(alignment).max(1);
+        let address = match instance.address {
+            Some(address) => address,
+            None => next.next_multiple_of(alignment),
+        };
+
+        for index in 0..count.unwrap_or(1) {
+            let name = match count {
+                Some(_) => format!("{}{}", instance.name, index),
+                None => instance.name.clone(),
+            };
+            let overflow = || Error::at(instance.location, "address does not fit in 64 bits");
+            let start = index
+                .checked_mul(stride)
+                .and_then(|offset| offset.checked_add(address))
+                .ok_or_else(overflow)?;
+            let end = start.checked_add(size).ok_or_else(overflow)?;
+            if let Some((_, _, other)) = placed
+                .iter()
+                .find(|(other_start, other_end, _)| start < *other_end && *other_start < end)
+            {
+                return Err(Error::at(
+                    instance.location,
+                    format!("{} at {:#x} overlaps {}", name, start, other),
+                ));
+            }
+
+            match peripherals.as_deref_mut() {
+                Some(peripherals) if child.kind == Kind::Addrmap => peripherals.push(Peripheral {
+                    name: name.clone(),
+                    description: child.description()?,
+                    base_address: start,
+                    registers: element.clone(),
+                }),
+                _ => {
+                    for register in &element {
+                        let mut register = register.clone();
+                        if child.kind == Kind::Reg {
+                            register.name = name.clone();
+                        } else {
+                            register.name = format!("{}_{}", name, register.name);
+                        }
+                        register.offset += start;
+                        registers.push(register);
+                    }
+                }
+            }
+            placed.push((start, end, name));
+            next = end;
+        }
+    }
+
+    let size = placed.iter().map(|(_, end, _)| *end).max().unwrap_or(0);
+    Ok((registers, size))
+}
+
+fn register(component: &Component, name: &str, location: Location) -> Result<Register, Error> {
+    let size = component.number("regwidth")?.unwrap_or(32);
+    let size = u32::try_from(size)
+        .ok()
+        .filter(|size| matches!(size, 8 | 16 | 32 | 64))
+        .ok_or_else(|| {
+            let location = component
+                .property("regwidth")
+                .map_or(location, |property| property.location);
+            Error::at(
+                location,
+                format!("regwidth must be 8, 16, 32 or 64, not {}", size),
+            )
+        })?;
+
+    let mut register = Register {
+        name: name.to_string(),
+        description: component.description()?,
+        offset: 0,
+        size,
+        access: None,
+        reset_value: None,
+        fields: Vec::new(),
+    };
+
+    let mut next_bit = 0;
+    for instance in &component.instances {
+        let field = &instance.component;
+        if !field.is_present()? {
+            continue;
+        }
+
+        let field_width = field.number("fieldwidth")?;
+        let (lsb, width) = match instance.brackets {
+            Some(Brackets::Range(msb, lsb)) if msb < lsb => {
+                return Err(Error::at(
+                    instance.location,
+                    format!(
+                        "bits [{}:{}] are in msb0 order, which is not supported",
+                        msb, lsb
+                    ),
+                ))
+            }
+            Some(Brackets::Range(msb, lsb)) => (lsb, msb - lsb + 1),
+            Some(Brackets::Size(width)) => (next_bit, width),
+            None => (next_bit, field_width.unwrap_or(1)),
+        };
+        if width == 0 {
+            return Err(Error::at(
+                instance.location,
+                "fields must be at least 1 bit wide",
+            ));
+        }
+        if let Some(field_width) = field_width.filter(|field_width| *field_width != width) {
+            return Err(Error::at(
+                instance.location,
+                format!(
+                    "{} is {} bits wide, but its fieldwidth is {}",
+                    instance.name, width, field_width
+                ),
+            ));
+        }
+        let out_of_range = || Error::at(instance.location, "bit position out of range");
+        let lsb = u32::try_from(lsb).map_err(|_| out_of_range())?;
+        let msb = u32::try_from(width - 1)
+            .ok()
+            .and_then(|width| lsb.checked_add(width))
+            .ok_or_else(out_of_range)?;
+        next_bit = u64::from(msb) + 1;
+
+        let software_access = match field.property("sw") {
+            Some(property) => access(property)?,
+            None => Some(Access::ReadWrite),
+        };
+        // Software cannot see the field at all
+        let Some(software_access) = software_access else {
+            continue;
+        };
+        let hardware_access = match field.property("hw") {
+            Some(property) => access(property)?,
+            None => None,
+        };
+
+        let enumeration = match field.property("encode") {
+            Some(Property {
+                value: Value::Enum(enumeration),
+                ..
+            }) => Some(Enumeration::clone(enumeration)),
+            Some(property) => {
+                return Err(Error::at(property.location, "`encode` must name an enum"))
+            }
+            None => None,
+        };
+
+        let field_model = Field {
+            name: instance.name.clone(),
+            description: field.description()?,
+            lsb,
+            msb,
+            access: Some(software_access),
+            hardware_access,
+            enumeration,
+            valid: Vec::new(),
+            scale: None,
+            none: None,
+            table: Vec::new(),
+        };
+
+        let reset = match &instance.reset {
+            Some((value, location)) => Some((value, *location)),
+            None => field
+                .property("reset")
+                .map(|property| (&property.value, property.location)),
+        };
+        if let Some((value, location)) = reset {
+            let value = match value {
+                Value::Number(number) => *number,
+                _ => {
+                    return Err(Error::at(
+                        location,
+                        "reset values must be numbers, resets from signals are not supported",
+                    ))
+                }
+            };
+            if value > field_model.max_value() {
+                return Err(Error::at(
+                    location,
+                    format!(
+                        "reset value {:#x} does not fit in {} bits",
+                        value,
+                        field_model.width()
+                    ),
+                ));
+            }
+            let reset_value = register.reset_value.unwrap_or(0);
+            register.reset_value = Some(reset_value | value << lsb);
+        }
+
+        register.fields.push(field_model);
+        // Check each field as it is added, so errors point at the offending field
+        register
+            .validate()
+            .map_err(|error| Error::at(instance.location, error.message()))?;
+    }
+
+    // Fields which share their access leave it to the register
+    let first = register.fields.first().and_then(|field| field.access);
+    if register.fields.iter().all(|field| field.access == first) {
+        register.access = first;
+        for field in &mut register.fields {
+            field.access = None;
+        }
+    }
+    Ok(register)
+}
+
+fn access(property: &Property) -> Result<Option<Access>, Error> {
+    match &property.value {
+        Value::Ident(ident) => match ident.as_str() {
+            "r" => Ok(Some(Access::ReadOnly)),
+            "w" => Ok(Some(Access::WriteOnly)),
+            "rw" | "wr" => Ok(Some(Access::ReadWrite)),
+            "w1" => Ok(Some(Access::WriteOnce)),
+            "rw1" => Ok(Some(Access::ReadWriteOnce)),
+            "na" => Ok(None),
+            _ => Err(Error::at(
+                property.location,
+                format!(
+                    "invalid access `{}`, expected r, w, rw, w1, rw1 or na",
+                    ident
+                ),
+            )),
+        },
+        _ => Err(Error::at(
+            property.location,
+            format!("`{}` must be an access type such as rw", property.name),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn offsets(device: &Device) -> Vec<(&str, u64)> {
+        device.peripherals[0]
+            .registers
+            .iter()
+            .map(|register| (register.name.as_str(), register.offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let device = parse(
+            r#"
+            /* Block comments
+               span lines */
+            enum mode { OFF; ON; TURBO = 4'h4; };
+
+            addrmap top {
+                addrmap {
+                    default sw = r;
+                    reg ctrl_t {
+                        regwidth = 8;
+                        field { sw = rw; encode = mode; } MODE[3] = 1;
+                        field {} READY[7:7];
+                    };
+                    ctrl_t CTRL, CTRL_ALT;
+                    external reg { field { fieldwidth = 4; } COUNT = 0xa; } COUNT @ 0x8;
+                } TIMER @ 0x1000;
+            };
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(device.name, "top");
+        let timer = &device.peripherals[0];
+        assert_eq!(timer.name, "TIMER");
+        assert_eq!(timer.base_address, 0x1000);
+        assert_eq!(
+            offsets(&device),
+            [("CTRL", 0), ("CTRL_ALT", 1), ("COUNT", 8)]
+        );
+
+        let ctrl = &timer.registers[0];
+        assert_eq!(ctrl.size, 8);
+        assert_eq!(ctrl.reset_value, Some(1));
+        assert_eq!(ctrl.access, None);
+        assert_eq!(ctrl.fields[0].access, Some(Access::ReadWrite));
+        assert_eq!((ctrl.fields[1].lsb, ctrl.fields[1].msb), (7, 7));
+        // The default from the enclosing addrmap applies to fields defined in it
+        assert_eq!(ctrl.fields[1].access, Some(Access::ReadOnly));
+
+        let mode = ctrl.fields[0].enumeration.as_ref().unwrap();
+        assert_eq!(mode.name.as_deref(), Some("mode"));
+        let values: Vec<_> = mode.variants.iter().map(|v| v.value).collect();
+        assert_eq!(values, [0, 1, 4]);
+
+        let count = &timer.registers[2];
+        assert_eq!(count.size, 32);
+        assert_eq!(count.access, Some(Access::ReadOnly));
+        assert_eq!((count.fields[0].lsb, count.fields[0].msb), (0, 3));
+        assert_eq!(count.reset_value, Some(0xa));
+    }
+
+    #[test]
+    fn test_addressing() {
+        let device = |addressing: &str| {
+            parse(&format!(
+                "addrmap top {{
+                    addressing = {addressing};
+                    reg {{ regwidth = 8; field {{}} A; }} A;
+                    reg {{ field {{}} B; }} B[2];
+                    regfile {{ reg {{ regwidth = 16; field {{}} C; }} C; }} RF[2];
+                    reg {{ regwidth = 8; field {{}} D; }} D @ 0x40, E %= 4;
+                }};"
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            offsets(&device("regalign")),
+            [
+                ("A", 0x0),
+                ("B0", 0x4),
+                ("B1", 0x8),
+                ("RF0_C", 0xc),
+                ("RF1_C", 0xe),
+                ("D", 0x40),
+                ("E", 0x44)
+            ]
+        );
+        assert_eq!(
+            offsets(&device("fullalign")),
+            [
+                ("A", 0x0),
+                ("B0", 0x8),
+                ("B1", 0xc),
+                ("RF0_C", 0x10),
+                ("RF1_C", 0x12),
+                ("D", 0x40),
+                ("E", 0x44)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_registers() {
+        let device = parse(
+            "addrmap inner { reg { field {} X; } X; };
+             addrmap soc {
+                 reg { field {} ID[31:0] = 7; } ID @ 0x100;
+                 inner BLOCK[2] @ 0x1000 += 0x1000;
+             };",
+        )
+        .unwrap();
+
+        // The root is the addrmap which is not instantiated, wherever it is defined
+        assert_eq!(device.name, "soc");
+        let peripherals: Vec<_> = device
+            .peripherals
+            .iter()
+            .map(|peripheral| (peripheral.name.as_str(), peripheral.base_address))
+            .collect();
+        assert_eq!(
+            peripherals,
+            [("soc", 0), ("BLOCK0", 0x1000), ("BLOCK1", 0x2000)]
+        );
+        assert_eq!(offsets(&device), [("ID", 0x100)]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| {
+            let error = parse(source).unwrap_err();
+            format!("{}: {}", error.location().unwrap(), error.message())
+        };
+
+        assert_eq!(
+            error("addrmap top {\n  mem { } m;\n};"),
+            "2:3: `mem` is not supported"
+        );
+        assert_eq!(
+            error("`include \"other.rdl\"\n"),
+            "1:1: preprocessor directives are not supported"
+        );
+        assert_eq!(
+            error("addrmap top {\n  reg { field {} A; } r;\n  r.A->reset = 1;\n};"),
+            "3:3: dynamic property assignments are not supported"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field { rclr; bogus = 1; } A; } r; };"),
+            "1:35: unsupported property `bogus`"
+        );
+        assert_eq!(
+            error("addrmap top { reg { sw = rw; field {} A; } r; };"),
+            "1:21: `sw` does not apply to reg components"
+        );
+        assert_eq!(
+            error("addrmap top {\n  reg {\n    field {} A[3:0];\n    field {} B[2:1];\n  } r;\n};"),
+            "4:14: r.B: bits [1:2] overlap field A"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field {} A[0:3]; } r; };"),
+            "1:30: bits [0:3] are in msb0 order, which is not supported"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field {} A[2] = 4; } r; };"),
+            "1:37: reset value 0x4 does not fit in 2 bits"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field { sw = rx; } A; } r; };"),
+            "1:29: invalid access `rx`, expected r, w, rw, w1, rw1 or na"
+        );
+        assert_eq!(
+            error("addrmap top { unknown_t r; };"),
+            "1:15: unknown component type `unknown_t`"
+        );
+        assert_eq!(
+            error("addrmap top { field {} A; };"),
+            "1:24: field components cannot be instantiated in a addrmap"
+        );
+        assert_eq!(
+            error("addrmap top {\n  reg { field {} A; } r @ 0x0, s @ 0x2;\n};"),
+            "2:32: s at 0x2 overlaps r"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field { reset = 4'h1f; } A; } r; };"),
+            "1:37: 31 does not fit in 4 bits"
+        );
+        assert_eq!(
+            error("addrmap top { reg { field { desc = \"A; } A; } r; };"),
+            "1:36: unterminated string"
+        );
+        assert_eq!(
+            error("addrmap top {"),
+            "1:14: expected `}`, found the end of the file"
+        );
+        assert_eq!(
+            parse("").unwrap_err().to_string(),
+            "no top-level addrmap definition to generate"
+        );
+    }
+}
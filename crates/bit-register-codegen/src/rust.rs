@@ -0,0 +1,722 @@
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::{Device, Enumeration, Error, Field, Peripheral, Register};
+
+/// Generate Rust source with a module of `bit_register!` definitions for each peripheral
+///
+/// Each module contains the peripheral's base address, a register struct for every register
+/// with its offset and reset value, and an enum for every field with named values. Fields of a
+/// single bit are `bool`, other fields without named values are the smallest unsigned integer
+/// which fits them. Scaled fields and fields with a lookup table are the smallest integer which
+/// holds their values, and optional fields are an `Option` of their type. The modules are followed by `ADDRESS_MAP`, a table of every register's name
+/// and absolute address. The device is validated first.
+pub fn generate_rust(device: &Device) -> Result<String, Error> {
+    device.validate()?;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by bit-register-codegen from {}. Do not edit.",
+        device.name
+    )
+    .unwrap();
+
+    let mut modules = Vec::new();
+    for peripheral in &device.peripherals {
+        let module = snake_case(&peripheral.name);
+        if modules.contains(&module) {
+            return Err(Error::new(format!(
+                "{}: module name {} is used by another peripheral",
+                peripheral.name, module
+            )));
+        }
+        out.push('\n');
+        write_peripheral(&mut out, peripheral, &module)?;
+        modules.push(module);
+    }
+
+    out.push('\n');
+    write_address_map(&mut out, device)?;
+    Ok(out)
+}
+
+/// Write a table of the absolute address of every register, named `PERIPHERAL.REGISTER`
+fn write_address_map(out: &mut String, device: &Device) -> Result<(), Error> {
+    writeln!(
+        out,
+        "/// Absolute address of every register, in declaration order"
+    )
+    .unwrap();
+    writeln!(out, "pub const ADDRESS_MAP: &[(&str, u64)] = &[").unwrap();
+    for peripheral in &device.peripherals {
+        for register in &peripheral.registers {
+            let address = peripheral
+                .base_address
+                .checked_add(register.offset)
+                .ok_or_else(|| {
+                    Error::new(format!(
+                        "{}.{}: address does not fit in 64 bits",
+                        peripheral.name, register.name
+                    ))
+                })?;
+            writeln!(
+                out,
+                "    (\"{}.{}\", {}),",
+                peripheral.name,
+                register.name,
+                hex(address, 32.max(bits_needed(address)))
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "];").unwrap();
+    Ok(())
+}
+
+fn write_peripheral(out: &mut String, peripheral: &Peripheral, module: &str) -> Result<(), Error> {
+    write_docs(out, 0, peripheral.description.as_deref(), &[]);
+    writeln!(out, "pub mod {} {{", module).unwrap();
+    writeln!(out, "    use bit_register::bit_register;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    /// Address of the peripheral").unwrap();
+    writeln!(
+        out,
+        "    pub const BASE_ADDRESS: u64 = {};",
+        hex(
+            peripheral.base_address,
+            32.max(bits_needed(peripheral.base_address))
+        )
+    )
+    .unwrap();
+
+    let mut types: Vec<(String, Option<&Enumeration>)> = Vec::new();
+    for register in &peripheral.registers {
+        let name = pascal_case(&register.name);
+        if types.iter().any(|(existing, _)| *existing == name) {
+            return Err(Error::new(format!(
+                "{}.{}: type name {} is used by another type",
+                peripheral.name, register.name, name
+            )));
+        }
+        types.push((name.clone(), None));
+
+        let mut enums = Vec::new();
+        for field in &register.fields {
+            if let Some(enumeration) = &field.enumeration {
+                let enum_name = enum_name(register, field, enumeration, &types);
+                let existing = types.iter().find(|(name, _)| *name == enum_name);
+                match existing {
+                    // Fields sharing a named enumeration share the enum
+                    Some((_, Some(other))) if *other == enumeration => {}
+                    Some(_) => {
+                        return Err(Error::new(format!(
+                            "{}.{}.{}: type name {} is used by another type",
+                            peripheral.name, register.name, field.name, enum_name
+                        )));
+                    }
+                    None => {
+                        types.push((enum_name.clone(), Some(enumeration)));
+                        enums.push((enum_name.clone(), field, enumeration));
+                    }
+                }
+            }
+        }
+
+        writeln!(out).unwrap();
+        write_register(out, register, &name, &types)
+            .map_err(|error| Error::new(format!("{}.{}", peripheral.name, error.message())))?;
+        for (enum_name, field, enumeration) in enums {
+            writeln!(out).unwrap();
+            write_enum(out, register, field, enumeration, &enum_name)
+                .map_err(|error| Error::new(format!("{}.{}", peripheral.name, error.message())))?;
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    Ok(())
+}
+
+fn write_register(
+    out: &mut String,
+    register: &Register,
+    name: &str,
+    types: &[(String, Option<&Enumeration>)],
+) -> Result<(), Error> {
+    let access = register.access.map(|access| format!("Access: {}", access));
+    writeln!(out, "    bit_register! {{").unwrap();
+    write_docs(
+        out,
+        8,
+        register.description.as_deref(),
+        access.as_deref().into_iter().collect::<Vec<_>>().as_slice(),
+    );
+    writeln!(out, "        #[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+    writeln!(out, "        pub struct {}: u{} {{", name, register.size).unwrap();
+
+    let mut field_names = Vec::new();
+    for field in &register.fields {
+        let field_name = snake_case(&field.name);
+        if field_names.contains(&field_name) {
+            return Err(Error::new(format!(
+                "{}.{}: field name {} is used by another field",
+                register.name, field.name, field_name
+            )));
+        }
+
+        let range = field.physical_range().or_else(|| field.table_range());
+        let field_type = match (&field.enumeration, range) {
+            (Some(enumeration), _) => enum_name(register, field, enumeration, types),
+            (None, Some(range)) => physical_type(range).into(),
+            (None, None) if field.width() == 1 => "bool".into(),
+            (None, None) => uint_type(field.width()).into(),
+        };
+        let field_type = match field.none {
+            Some(_) => format!("Option<{}>", field_type),
+            None => field_type,
+        };
+        let mut bits = if field.lsb == field.msb {
+            format!("[{}]", field.lsb)
+        } else {
+            format!("[{}:{}]", field.lsb, field.msb)
+        };
+        if let Some(none) = field.none {
+            write!(bits, " none {}", hex(none, field.width())).unwrap();
+        }
+        if !field.valid.is_empty() {
+            let valid: Vec<String> = field
+                .valid
+                .iter()
+                .map(|range| {
+                    if range.start() == range.end() {
+                        range.start().to_string()
+                    } else {
+                        format!("{}..={}", range.start(), range.end())
+                    }
+                })
+                .collect();
+            write!(bits, " in {}", valid.join(" | ")).unwrap();
+        }
+        if !field.table.is_empty() {
+            let table: Vec<String> = field.table.iter().map(i128::to_string).collect();
+            write!(bits, " table [{}]", table.join(", ")).unwrap();
+        }
+        if let Some(scale) = &field.scale {
+            write!(bits, " scale {}", scale.scale).unwrap();
+            if scale.offset != 0 {
+                write!(bits, " offset {}", scale.offset).unwrap();
+            }
+            if !scale.unit.is_empty() {
+                write!(bits, " unit {:?}", scale.unit).unwrap();
+            }
+        }
+
+        let access = [
+            field.access.map(|access| format!("Access: {}", access)),
+            field
+                .hardware_access
+                .map(|access| format!("Hardware access: {}", access)),
+        ];
+        write_docs(
+            out,
+            12,
+            field.description.as_deref(),
+            access
+                .iter()
+                .flatten()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+        writeln!(
+            out,
+            "            pub {}: {} => {},",
+            field_name, field_type, bits
+        )
+        .unwrap();
+        field_names.push(field_name);
+    }
+
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    impl {} {{", name).unwrap();
+    writeln!(
+        out,
+        "        /// Offset of the register from the peripheral's base address"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "        pub const OFFSET: u64 = {};",
+        hex(register.offset, bits_needed(register.offset))
+    )
+    .unwrap();
+    if let Some(reset_value) = register.reset_value {
+        writeln!(out, "        /// Value of the register after reset").unwrap();
+        writeln!(
+            out,
+            "        pub const RESET_VALUE: u{} = {};",
+            register.size,
+            hex(reset_value, register.size)
+        )
+        .unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+    Ok(())
+}
+
+fn write_enum(
+    out: &mut String,
+    register: &Register,
+    field: &Field,
+    enumeration: &Enumeration,
+    name: &str,
+) -> Result<(), Error> {
+    let description = format!("Values of the {} field of {}", field.name, register.name);
+    writeln!(out, "    bit_register! {{").unwrap();
+    write_docs(out, 8, Some(&description), &[]);
+    writeln!(out, "        #[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+    writeln!(
+        out,
+        "        pub enum {}: {} {{",
+        name,
+        uint_type(field.width())
+    )
+    .unwrap();
+
+    let mut variant_names = Vec::new();
+    for variant in &enumeration.variants {
+        let variant_name = pascal_case(&variant.name);
+        if variant_names.contains(&variant_name) {
+            return Err(Error::new(format!(
+                "{}.{}: variant name {} is used by another value",
+                register.name, field.name, variant_name
+            )));
+        }
+        write_docs(out, 12, variant.description.as_deref(), &[]);
+        writeln!(out, "            {} = {},", variant_name, variant.value).unwrap();
+        variant_names.push(variant_name);
+    }
+
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    Ok(())
+}
+
+/// Name of the enum for a field, which is the enumeration's own name if it has one
+fn enum_name(
+    register: &Register,
+    field: &Field,
+    enumeration: &Enumeration,
+    types: &[(String, Option<&Enumeration>)],
+) -> String {
+    let fallback = || pascal_case(&format!("{}_{}", register.name, field.name));
+    match &enumeration.name {
+        Some(name) => {
+            let name = pascal_case(name);
+            // Only share a name with an identical enumeration, never with a register
+            match types.iter().find(|(existing, _)| *existing == name) {
+                Some((_, Some(other))) if *other == enumeration => name,
+                Some(_) => fallback(),
+                None => name,
+            }
+        }
+        None => fallback(),
+    }
+}
+
+/// Write doc comments from a description and extra paragraphs, one sentence per line as given
+fn write_docs(out: &mut String, indent: usize, description: Option<&str>, extra: &[&str]) {
+    let mut paragraphs: Vec<String> = Vec::new();
+    if let Some(description) = description {
+        let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !description.is_empty() {
+            paragraphs.push(description);
+        }
+    }
+    paragraphs.extend(extra.iter().map(|line| line.to_string()));
+
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        if index > 0 {
+            writeln!(out, "{:indent$}///", "", indent = indent).unwrap();
+        }
+        writeln!(out, "{:indent$}/// {}", "", paragraph, indent = indent).unwrap();
+    }
+}
+
+/// Smallest unsigned integer type with at least `bits` bits
+fn uint_type(bits: u32) -> &'static str {
+    match bits {
+        0..=8 => "u8",
+        9..=16 => "u16",
+        17..=32 => "u32",
+        _ => "u64",
+    }
+}
+
+/// The smallest integer type which holds every physical value of a scaled field, or every value
+/// of a lookup table
+fn physical_type(range: RangeInclusive<i128>) -> &'static str {
+    let types: [(&str, i128, i128); 8] = [
+        ("u8", 0, u8::MAX.into()),
+        ("u16", 0, u16::MAX.into()),
+        ("u32", 0, u32::MAX.into()),
+        ("u64", 0, u64::MAX.into()),
+        ("i8", i8::MIN.into(), i8::MAX.into()),
+        ("i16", i16::MIN.into(), i16::MAX.into()),
+        ("i32", i32::MIN.into(), i32::MAX.into()),
+        ("i64", i64::MIN.into(), i64::MAX.into()),
+    ];
+    types
+        .iter()
+        .find(|(_, min, max)| min <= range.start() && range.end() <= max)
+        .map_or("i64", |(name, _, _)| name)
+}
+
+fn bits_needed(value: u64) -> u32 {
+    (64 - value.leading_zeros()).max(8)
+}
+
+/// Format a value in hex with enough digits for `bits`, grouped by four digits
+pub(crate) fn hex(value: u64, bits: u32) -> String {
+    let digits = format!("{:0width$x}", value, width = bits.div_ceil(4) as usize);
+    let mut grouped = String::from("0x");
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 4 == 0 {
+            grouped.push('_');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Split a name into words at separators and lowercase to uppercase transitions
+pub(crate) fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous = None;
+            continue;
+        }
+        if let Some(previous) = previous {
+            if previous.is_ascii_lowercase() && c.is_ascii_uppercase() && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+        previous = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Convert a name to a `snake_case` identifier
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut ident = words(name)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if is_keyword(&ident) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// Convert a name to a `PascalCase` identifier
+pub(crate) fn pascal_case(name: &str) -> String {
+    let mut ident: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'V');
+    }
+    if is_keyword(&ident) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn is_keyword(ident: &str) -> bool {
+    matches!(
+        ident,
+        "as" | "async"
+            | "await"
+            | "box"
+            | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "do"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "final"
+            | "fn"
+            | "for"
+            | "gen"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "macro"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "override"
+            | "priv"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "Self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "try"
+            | "type"
+            | "typeof"
+            | "unsafe"
+            | "unsized"
+            | "use"
+            | "virtual"
+            | "where"
+            | "while"
+            | "yield"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Access, Scale, Variant};
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(snake_case("CR1"), "cr1");
+        assert_eq!(snake_case("TIMER_CTRL"), "timer_ctrl");
+        assert_eq!(snake_case("modeSelect"), "mode_select");
+        assert_eq!(snake_case("TYPE"), "type_");
+        assert_eq!(snake_case("3V3_EN"), "_3v3_en");
+        assert_eq!(pascal_case("TIMER_CTRL"), "TimerCtrl");
+        assert_eq!(pascal_case("modeSelect"), "ModeSelect");
+        assert_eq!(pascal_case("0"), "V0");
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex(0x1f, 8), "0x1f");
+        assert_eq!(hex(0x1f, 16), "0x001f");
+        assert_eq!(hex(0x4000_1000, 32), "0x4000_1000");
+        assert_eq!(hex(0x1_0000, 20), "0x1_0000");
+    }
+
+    #[test]
+    fn test_generate() {
+        let device = Device {
+            name: "MCU".into(),
+            description: None,
+            peripherals: vec![Peripheral {
+                name: "TIMER0".into(),
+                description: Some("Basic\n   timer".into()),
+                base_address: 0x4000_0000,
+                registers: vec![Register {
+                    name: "CTRL".into(),
+                    description: Some("Control register".into()),
+                    offset: 4,
+                    size: 16,
+                    access: Some(Access::ReadWrite),
+                    reset_value: Some(0x10),
+                    fields: vec![
+                        Field {
+                            name: "EN".into(),
+                            description: Some("Enable the timer".into()),
+                            lsb: 0,
+                            msb: 0,
+                            access: None,
+                            hardware_access: None,
+                            enumeration: None,
+                            valid: Vec::new(),
+                            scale: None,
+                            none: None,
+                            table: Vec::new(),
+                        },
+                        Field {
+                            name: "MODE".into(),
+                            description: None,
+                            lsb: 1,
+                            msb: 2,
+                            access: Some(Access::WriteOnly),
+                            hardware_access: Some(Access::ReadOnly),
+                            enumeration: Some(Enumeration {
+                                name: None,
+                                variants: vec![
+                                    Variant {
+                                        name: "ONE_SHOT".into(),
+                                        description: Some("Stop after one period".into()),
+                                        value: 0,
+                                    },
+                                    Variant {
+                                        name: "PERIODIC".into(),
+                                        description: None,
+                                        value: 1,
+                                    },
+                                ],
+                            }),
+                            valid: Vec::new(),
+                            scale: None,
+                            none: Some(3),
+                            table: Vec::new(),
+                        },
+                        Field {
+                            name: "PRESCALE".into(),
+                            description: None,
+                            lsb: 4,
+                            msb: 12,
+                            access: None,
+                            hardware_access: None,
+                            enumeration: None,
+                            valid: vec![1..=256, 511..=511],
+                            scale: None,
+                            none: None,
+                            table: Vec::new(),
+                        },
+                        Field {
+                            name: "LIMIT".into(),
+                            description: None,
+                            lsb: 13,
+                            msb: 15,
+                            access: None,
+                            hardware_access: None,
+                            enumeration: None,
+                            valid: Vec::new(),
+                            scale: Some(Scale {
+                                scale: 50,
+                                offset: -100,
+                                unit: "mA".into(),
+                            }),
+                            none: None,
+                            table: Vec::new(),
+                        },
+                        Field {
+                            name: "DELAY".into(),
+                            description: None,
+                            lsb: 3,
+                            msb: 3,
+                            access: None,
+                            hardware_access: None,
+                            enumeration: None,
+                            valid: Vec::new(),
+                            scale: None,
+                            none: None,
+                            table: vec![-4, 16],
+                        },
+                    ],
+                }],
+            }],
+        };
+
+        assert_eq!(
+            generate_rust(&device).unwrap(),
+            "\
+// Generated by bit-register-codegen from MCU. Do not edit.
+
+/// Basic timer
+pub mod timer0 {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4000_0000;
+
+    bit_register! {
+        /// Control register
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ctrl: u16 {
+            /// Enable the timer
+            pub en: bool => [0],
+            /// Access: write-only
+            ///
+            /// Hardware access: read-only
+            pub mode: Option<CtrlMode> => [1:2] none 0x3,
+            pub prescale: u16 => [4:12] in 1..=256 | 511,
+            pub limit: i16 => [13:15] scale 50 offset -100 unit \"mA\",
+            pub delay: i8 => [3] table [-4, 16],
+        }
+    }
+
+    impl Ctrl {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x04;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0010;
+    }
+
+    bit_register! {
+        /// Values of the MODE field of CTRL
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum CtrlMode: u8 {
+            /// Stop after one period
+            OneShot = 0,
+            Periodic = 1,
+        }
+    }
+}
+
+/// Absolute address of every register, in declaration order
+pub const ADDRESS_MAP: &[(&str, u64)] = &[
+    (\"TIMER0.CTRL\", 0x4000_0004),
+];
+"
+        );
+    }
+
+    #[test]
+    fn test_name_collisions() {
+        let register = |name: &str| Register {
+            name: name.into(),
+            size: 8,
+            ..Default::default()
+        };
+        let device = Device {
+            name: "MCU".into(),
+            description: None,
+            peripherals: vec![Peripheral {
+                name: "GPIO".into(),
+                registers: vec![register("OUT_SET"), register("OutSet")],
+                ..Default::default()
+            }],
+        };
+        assert_eq!(
+            generate_rust(&device).unwrap_err().message(),
+            "GPIO.OutSet: type name OutSet is used by another type"
+        );
+    }
+}
@@ -0,0 +1,821 @@
+//! Reading register descriptions from CMSIS-SVD files
+//!
+//! Peripherals, clusters and registers are read along with their `dim` arrays, which are
+//! expanded into one register per element. Register properties (`size`, `access` and
+//! `resetValue`) are inherited from the device, peripheral and cluster, and peripherals which
+//! are `derivedFrom` another peripheral reuse its registers. Cluster registers are named
+//! `<cluster>_<register>` with their offsets relative to the peripheral.
+//!
+//! Enumerated values become [`Enumeration`]s. When a field has separate values for reading and
+//! writing, the values for reading are used. `isDefault` values have no single raw value and
+//! are skipped. A `<writeConstraint>` range becomes the valid values of its field.
+//!
+//! [`write()`] goes the other way, so a device described from `bit_register!` types with
+//! [`Register::of`] can be handed to debuggers and other tools which read SVD.
+
+use roxmltree::{Document, Node};
+
+use crate::xml::{access_name, XmlWriter};
+use crate::{Access, Device, Enumeration, Error, Field, Location, Peripheral, Register, Variant};
+
+/// Read a device from the contents of a CMSIS-SVD file
+pub fn parse(xml: &str) -> Result<Device, Error> {
+    let document = Document::parse(xml).map_err(|error| {
+        let position = error.pos();
+        Error::at(
+            Location {
+                line: position.row,
+                column: position.col,
+            },
+            error.to_string(),
+        )
+    })?;
+    Parser {
+        document: &document,
+    }
+    .device()
+}
+
+/// Write a device as a CMSIS-SVD file
+///
+/// The device is validated first. SVD requires a device version, which the model does not
+/// have, so `1.0` is written. Every register is written with its own size, and every peripheral
+/// gets a single address block covering its registers. Fields with a single range of valid
+/// values get a `<writeConstraint>`, and fields with a lookup table get enumerated values named
+/// after the values in the table, such as `VALUE_16`.
+pub fn write(device: &Device) -> Result<String, Error> {
+    device.validate()?;
+
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "device schemaVersion=\"1.3\" \
+         xmlns:xs=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xs:noNamespaceSchemaLocation=\"CMSIS-SVD.xsd\"",
+    );
+    xml.element("name", &device.name);
+    xml.element("version", "1.0");
+    xml.element(
+        "description",
+        device.description.as_deref().unwrap_or(&device.name),
+    );
+    xml.element("addressUnitBits", 8);
+    xml.element("width", 32);
+    xml.open("peripherals");
+    for peripheral in &device.peripherals {
+        write_peripheral(&mut xml, peripheral);
+    }
+    xml.close("peripherals");
+    xml.close("device");
+    Ok(xml.finish())
+}
+
+fn write_peripheral(xml: &mut XmlWriter, peripheral: &Peripheral) {
+    xml.open("peripheral");
+    xml.element("name", &peripheral.name);
+    if let Some(description) = &peripheral.description {
+        xml.element("description", description);
+    }
+    xml.element("baseAddress", format!("0x{:X}", peripheral.base_address));
+    if !peripheral.registers.is_empty() {
+        let end = peripheral
+            .registers
+            .iter()
+            .map(|register| register.offset + u64::from(register.size / 8))
+            .max()
+            .unwrap_or(0);
+        xml.open("addressBlock");
+        xml.element("offset", "0x0");
+        xml.element("size", format!("0x{:X}", end));
+        xml.element("usage", "registers");
+        xml.close("addressBlock");
+
+        xml.open("registers");
+        for register in &peripheral.registers {
+            write_register(xml, register);
+        }
+        xml.close("registers");
+    }
+    xml.close("peripheral");
+}
+
+fn write_register(xml: &mut XmlWriter, register: &Register) {
+    xml.open("register");
+    xml.element("name", &register.name);
+    if let Some(description) = &register.description {
+        xml.element("description", description);
+    }
+    xml.element("addressOffset", format!("0x{:X}", register.offset));
+    xml.element("size", register.size);
+    if let Some(access) = register.access {
+        xml.element("access", access_name(access));
+    }
+    if let Some(reset_value) = register.reset_value {
+        xml.element("resetValue", format!("0x{:X}", reset_value));
+    }
+    if !register.fields.is_empty() {
+        xml.open("fields");
+        for field in &register.fields {
+            write_field(xml, field);
+        }
+        xml.close("fields");
+    }
+    xml.close("register");
+}
+
+fn write_field(xml: &mut XmlWriter, field: &Field) {
+    xml.open("field");
+    xml.element("name", &field.name);
+    if let Some(description) = &field.description {
+        xml.element("description", description);
+    }
+    xml.element("bitOffset", field.lsb);
+    xml.element("bitWidth", field.width());
+    if let Some(access) = field.access {
+        xml.element("access", access_name(access));
+    }
+    // SVD can only constrain writes to a single range
+    if let [range] = field.valid.as_slice() {
+        xml.open("writeConstraint");
+        xml.open("range");
+        xml.element("minimum", range.start());
+        xml.element("maximum", range.end());
+        xml.close("range");
+        xml.close("writeConstraint");
+    }
+    let enumeration = field
+        .enumeration
+        .clone()
+        .or_else(|| field.table_enumeration());
+    if let Some(enumeration) = &enumeration {
+        xml.open("enumeratedValues");
+        if let Some(name) = &enumeration.name {
+            xml.element("name", name);
+        }
+        for variant in &enumeration.variants {
+            xml.open("enumeratedValue");
+            xml.element("name", &variant.name);
+            if let Some(description) = &variant.description {
+                xml.element("description", description);
+            }
+            xml.element("value", variant.value);
+            xml.close("enumeratedValue");
+        }
+        xml.close("enumeratedValues");
+    }
+    xml.close("field");
+}
+
+/// Register properties which are inherited by the elements below them
+#[derive(Debug, Clone, Copy, Default)]
+struct Properties {
+    size: Option<u32>,
+    access: Option<Access>,
+    reset_value: Option<u64>,
+}
+
+/// A single element of a register or cluster, which may be part of a `dim` array
+struct Instance {
+    name: String,
+    offset: u64,
+    index: Option<String>,
+}
+
+struct Parser<'a, 'input> {
+    document: &'a Document<'input>,
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+    fn device(&self) -> Result<Device, Error> {
+        let device = self.document.root_element();
+        if !device.has_tag_name("device") {
+            return Err(self.error(device, "expected a <device> element"));
+        }
+
+        let properties = self.properties(device, Properties::default())?;
+        let peripheral_nodes: Vec<Node> = match child(device, "peripherals") {
+            Some(peripherals) => children(peripherals, "peripheral").collect(),
+            None => Vec::new(),
+        };
+
+        let mut peripherals = Vec::new();
+        for node in &peripheral_nodes {
+            peripherals.push(self.peripheral(*node, &peripheral_nodes, properties)?);
+        }
+
+        Ok(Device {
+            name: self.required_text(device, "name")?.to_string(),
+            description: text(device, "description").map(str::to_string),
+            peripherals,
+        })
+    }
+
+    fn peripheral(
+        &self,
+        node: Node,
+        all: &[Node],
+        properties: Properties,
+    ) -> Result<Peripheral, Error> {
+        if child(node, "dim").is_some() {
+            return Err(self.error(node, "peripheral arrays are not supported"));
+        }
+
+        let name = self.required_text(node, "name")?;
+        let mut properties = self.properties(node, properties)?;
+
+        // A derived peripheral reuses the registers of its base unless it has its own
+        let mut registers_node = child(node, "registers");
+        let mut description = text(node, "description");
+        if let Some(base_name) = node.attribute("derivedFrom") {
+            let base = all
+                .iter()
+                .find(|other| text(**other, "name") == Some(base_name))
+                .ok_or_else(|| {
+                    self.error(
+                        node,
+                        format!("{} is derived from unknown peripheral {}", name, base_name),
+                    )
+                })?;
+            if base.attribute("derivedFrom").is_some() {
+                return Err(self.error(
+                    node,
+                    format!("{} is derived from derived peripheral {}", name, base_name),
+                ));
+            }
+            if registers_node.is_none() {
+                registers_node = child(*base, "registers");
+                properties = self.properties(*base, properties)?;
+            }
+            description = description.or_else(|| text(*base, "description"));
+        }
+
+        let mut registers = Vec::new();
+        if let Some(registers_node) = registers_node {
+            self.registers(registers_node, properties, "", 0, &mut registers)?;
+        }
+
+        Ok(Peripheral {
+            name: name.to_string(),
+            description: description.map(str::to_string),
+            base_address: self.required_number(node, "baseAddress")?,
+            registers,
+        })
+    }
+
+    /// Collect the registers and clusters below `node`, flattening clusters
+    fn registers(
+        &self,
+        node: Node,
+        properties: Properties,
+        prefix: &str,
+        base_offset: u64,
+        registers: &mut Vec<Register>,
+    ) -> Result<(), Error> {
+        for element in node.children().filter(Node::is_element) {
+            match element.tag_name().name() {
+                "register" => {
+                    for instance in self.instances(element)? {
+                        let mut register = self.register(
+                            element,
+                            properties,
+                            format!("{}{}", prefix, instance.name),
+                            base_offset + instance.offset,
+                        )?;
+                        if let Some(index) = &instance.index {
+                            register.description = register
+                                .description
+                                .map(|description| description.replace("%s", index));
+                        }
+                        registers.push(register);
+                    }
+                }
+                "cluster" => {
+                    let cluster_properties = self.properties(element, properties)?;
+                    for instance in self.instances(element)? {
+                        self.registers(
+                            element,
+                            cluster_properties,
+                            &format!("{}{}_", prefix, instance.name),
+                            base_offset + instance.offset,
+                            registers,
+                        )?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Names and offsets of the instances of a register or cluster, expanding `dim` arrays
+    fn instances(&self, node: Node) -> Result<Vec<Instance>, Error> {
+        let name = self.required_text(node, "name")?;
+        let offset = self.required_number(node, "addressOffset")?;
+
+        let Some(dim) = self.number(node, "dim")? else {
+            return Ok(vec![Instance {
+                name: name.to_string(),
+                offset,
+                index: None,
+            }]);
+        };
+        let increment = self.required_number(node, "dimIncrement")?;
+        let indices = match text(node, "dimIndex") {
+            Some(indices) => self.dim_indices(child(node, "dimIndex").unwrap_or(node), indices)?,
+            None => (0..dim).map(|index| index.to_string()).collect(),
+        };
+        if indices.len() as u64 != dim {
+            return Err(self.error(
+                node,
+                format!(
+                    "{} has {} indices for {} elements",
+                    name,
+                    indices.len(),
+                    dim
+                ),
+            ));
+        }
+
+        Ok(indices
+            .iter()
+            .enumerate()
+            .map(|(position, index)| Instance {
+                name: name.replace("[%s]", index).replace("%s", index),
+                offset: offset + position as u64 * increment,
+                index: Some(index.clone()),
+            })
+            .collect())
+    }
+
+    /// Parse a `dimIndex` list like `0-3`, `A-D` or `low,high`
+    fn dim_indices(&self, node: Node, indices: &str) -> Result<Vec<String>, Error> {
+        if let Some((start, end)) = indices.split_once('-') {
+            if let (Ok(start), Ok(end)) = (start.trim().parse::<u64>(), end.trim().parse::<u64>()) {
+                return Ok((start..=end).map(|index| index.to_string()).collect());
+            }
+            let (start, end) = (start.trim(), end.trim());
+            if let ([start], [end]) = (start.as_bytes(), end.as_bytes()) {
+                if start.is_ascii_uppercase() && end.is_ascii_uppercase() {
+                    return Ok((*start..=*end).map(|c| (c as char).to_string()).collect());
+                }
+            }
+            return Err(self.error(node, format!("invalid dimIndex {}", indices)));
+        }
+        Ok(indices
+            .split(',')
+            .map(|index| index.trim().to_string())
+            .collect())
+    }
+
+    fn register(
+        &self,
+        node: Node,
+        properties: Properties,
+        name: String,
+        offset: u64,
+    ) -> Result<Register, Error> {
+        let properties = self.properties(node, properties)?;
+        let size = properties
+            .size
+            .ok_or_else(|| self.error(node, format!("{} has no size", name)))?;
+
+        let mut fields = Vec::new();
+        if let Some(fields_node) = child(node, "fields") {
+            for field in children(fields_node, "field") {
+                if child(field, "dim").is_some() {
+                    return Err(self.error(field, "field arrays are not supported"));
+                }
+                fields.push(self.field(field, properties.access)?);
+            }
+        }
+
+        let register = Register {
+            name,
+            description: text(node, "description").map(str::to_string),
+            offset,
+            size,
+            access: properties.access,
+            reset_value: properties.reset_value,
+            fields,
+        };
+        register
+            .validate()
+            .map_err(|error| self.error(node, error.message()))?;
+        Ok(register)
+    }
+
+    fn field(&self, node: Node, register_access: Option<Access>) -> Result<Field, Error> {
+        let name = self.required_text(node, "name")?;
+        let (lsb, msb) = if let Some(offset) = self.number(node, "bitOffset")? {
+            let width = self.number(node, "bitWidth")?.unwrap_or(1);
+            (offset, offset + width.max(1) - 1)
+        } else if let Some(lsb) = self.number(node, "lsb")? {
+            (lsb, self.required_number(node, "msb")?)
+        } else if let Some(range) = text(node, "bitRange") {
+            let range_node = child(node, "bitRange").unwrap_or(node);
+            range
+                .strip_prefix('[')
+                .and_then(|range| range.strip_suffix(']'))
+                .and_then(|range| range.split_once(':'))
+                .and_then(|(msb, lsb)| Some((lsb.trim().parse().ok()?, msb.trim().parse().ok()?)))
+                .ok_or_else(|| self.error(range_node, format!("invalid bitRange {}", range)))?
+        } else {
+            return Err(self.error(node, format!("{} has no bit range", name)));
+        };
+        let (lsb, msb) = (
+            u32::try_from(lsb).map_err(|_| self.error(node, "bit position out of range"))?,
+            u32::try_from(msb).map_err(|_| self.error(node, "bit position out of range"))?,
+        );
+
+        let access = self
+            .access(node)?
+            .filter(|access| Some(*access) != register_access);
+
+        let enumeration = match self.enumerated_values(node)? {
+            Some(values) => self.enumeration(values)?,
+            None => None,
+        };
+
+        let valid = match child(node, "writeConstraint")
+            .and_then(|constraint| child(constraint, "range"))
+        {
+            Some(range) => vec![
+                self.required_number(range, "minimum")?..=self.required_number(range, "maximum")?,
+            ],
+            None => Vec::new(),
+        };
+
+        Ok(Field {
+            name: name.to_string(),
+            description: text(node, "description").map(str::to_string),
+            lsb,
+            msb,
+            access,
+            hardware_access: None,
+            enumeration,
+            valid,
+            scale: None,
+            none: None,
+            table: Vec::new(),
+        })
+    }
+
+    /// The `<enumeratedValues>` of a field, preferring the values for reading
+    fn enumerated_values(
+        &self,
+        field: Node<'a, 'input>,
+    ) -> Result<Option<Node<'a, 'input>>, Error> {
+        let all: Vec<Node> = children(field, "enumeratedValues").collect();
+        let chosen = all
+            .iter()
+            .find(|values| {
+                matches!(
+                    text(**values, "usage"),
+                    None | Some("read") | Some("read-write")
+                )
+            })
+            .or(all.first())
+            .copied();
+
+        match chosen {
+            Some(values) => match values.attribute("derivedFrom") {
+                Some(path) => {
+                    // Only the last element of a dotted path is used to find the values
+                    let base_name = path.rsplit('.').next().unwrap_or(path);
+                    let base = self
+                        .document
+                        .descendants()
+                        .filter(|node| node.has_tag_name("enumeratedValues"))
+                        .find(|node| text(*node, "name") == Some(base_name))
+                        .ok_or_else(|| {
+                            self.error(values, format!("enumeratedValues {} not found", path))
+                        })?;
+                    Ok(Some(base))
+                }
+                None => Ok(Some(values)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn enumeration(&self, node: Node) -> Result<Option<Enumeration>, Error> {
+        let mut variants = Vec::new();
+        for value in children(node, "enumeratedValue") {
+            if text(value, "isDefault") == Some("true") {
+                continue;
+            }
+            let value_node = child(value, "value")
+                .ok_or_else(|| self.error(value, "enumeratedValue has no value"))?;
+            let raw = value_node.text().unwrap_or("").trim();
+            if raw.starts_with('#') && raw.contains(['x', 'X']) {
+                return Err(self.error(
+                    value_node,
+                    format!(
+                        "don't care bits in enumerated value {} are not supported",
+                        raw
+                    ),
+                ));
+            }
+            variants.push(Variant {
+                name: self.required_text(value, "name")?.to_string(),
+                description: text(value, "description").map(str::to_string),
+                value: parse_number(raw)
+                    .ok_or_else(|| self.error(value_node, format!("invalid number {}", raw)))?,
+            });
+        }
+
+        if variants.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Enumeration {
+            name: text(node, "name").map(str::to_string),
+            variants,
+        }))
+    }
+
+    fn properties(&self, node: Node, inherited: Properties) -> Result<Properties, Error> {
+        let size = match self.number(node, "size")? {
+            Some(size) => Some(
+                u32::try_from(size)
+                    .map_err(|_| self.error(node, format!("invalid size {}", size)))?,
+            ),
+            None => inherited.size,
+        };
+        Ok(Properties {
+            size,
+            access: self.access(node)?.or(inherited.access),
+            reset_value: self.number(node, "resetValue")?.or(inherited.reset_value),
+        })
+    }
+
+    fn access(&self, node: Node) -> Result<Option<Access>, Error> {
+        let Some(access_node) = child(node, "access") else {
+            return Ok(None);
+        };
+        let access = match access_node.text().unwrap_or("").trim() {
+            "read-only" => Access::ReadOnly,
+            "write-only" => Access::WriteOnly,
+            "read-write" => Access::ReadWrite,
+            "writeOnce" => Access::WriteOnce,
+            "read-writeOnce" => Access::ReadWriteOnce,
+            other => return Err(self.error(access_node, format!("invalid access {}", other))),
+        };
+        Ok(Some(access))
+    }
+
+    fn number(&self, node: Node, name: &str) -> Result<Option<u64>, Error> {
+        match child(node, name) {
+            Some(number_node) => {
+                let raw = number_node.text().unwrap_or("").trim();
+                parse_number(raw)
+                    .map(Some)
+                    .ok_or_else(|| self.error(number_node, format!("invalid number {}", raw)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn required_number(&self, node: Node, name: &str) -> Result<u64, Error> {
+        self.number(node, name)?
+            .ok_or_else(|| self.error(node, format!("missing <{}>", name)))
+    }
+
+    fn required_text(&self, node: Node<'a, 'input>, name: &str) -> Result<&'a str, Error> {
+        text(node, name).ok_or_else(|| self.error(node, format!("missing <{}>", name)))
+    }
+
+    fn error(&self, node: Node, message: impl Into<String>) -> Error {
+        let position = self.document.text_pos_at(node.range().start);
+        Error::at(
+            Location {
+                line: position.row,
+                column: position.col,
+            },
+            message,
+        )
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+fn text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Parse an SVD number, which may be decimal, `0x` hex, `0b` or `#` binary
+fn parse_number(raw: &str) -> Option<u64> {
+    if let Some(hex) = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = raw
+        .strip_prefix("0b")
+        .or_else(|| raw.strip_prefix("0B"))
+        .or_else(|| raw.strip_prefix('#'))
+    {
+        u64::from_str_radix(binary, 2).ok()
+    } else {
+        raw.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device schemaVersion="1.3">
+  <name>MCU</name>
+  <size>32</size>
+  <access>read-write</access>
+  <resetValue>0</resetValue>
+  <peripherals>
+    <peripheral>
+      <name>TIMER0</name>
+      <description>Basic timer</description>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CTRL</name>
+          <description>Control register</description>
+          <addressOffset>0x0</addressOffset>
+          <size>16</size>
+          <resetValue>0x0010</resetValue>
+          <fields>
+            <field>
+              <name>EN</name>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+            </field>
+            <field>
+              <name>MODE</name>
+              <bitRange>[2:1]</bitRange>
+              <enumeratedValues>
+                <usage>write</usage>
+                <enumeratedValue><name>W</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+              <enumeratedValues>
+                <name>Mode</name>
+                <usage>read</usage>
+                <enumeratedValue><name>ONE_SHOT</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>PERIODIC</name><value>#01</value></enumeratedValue>
+                <enumeratedValue><name>OTHER</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <name>STATUS</name>
+              <lsb>8</lsb>
+              <msb>15</msb>
+              <access>read-only</access>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <name>CC[%s]</name>
+          <addressOffset>0x10</addressOffset>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <name>CH%s</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x4</addressOffset>
+            <size>8</size>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="TIMER0">
+      <name>TIMER1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+    #[test]
+    fn test_parse() {
+        let device = parse(SVD).unwrap();
+        assert_eq!(device.name, "MCU");
+        assert_eq!(device.peripherals.len(), 2);
+
+        let timer = &device.peripherals[0];
+        assert_eq!(timer.base_address, 0x4000_0000);
+        assert_eq!(timer.description.as_deref(), Some("Basic timer"));
+
+        let names: Vec<_> = timer
+            .registers
+            .iter()
+            .map(|register| (register.name.as_str(), register.offset, register.size))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("CTRL", 0x0, 16),
+                ("CC0", 0x10, 32),
+                ("CC1", 0x14, 32),
+                ("CHA_CFG", 0x24, 8),
+                ("CHB_CFG", 0x34, 8),
+            ]
+        );
+
+        let ctrl = &timer.registers[0];
+        assert_eq!(ctrl.access, Some(Access::ReadWrite));
+        assert_eq!(ctrl.reset_value, Some(0x10));
+        let fields: Vec<_> = ctrl
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.lsb, field.msb, field.access))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("EN", 0, 0, None),
+                ("MODE", 1, 2, None),
+                ("STATUS", 8, 15, Some(Access::ReadOnly)),
+            ]
+        );
+
+        // The values for reading are used, skipping the default
+        let mode = ctrl.fields[1].enumeration.as_ref().unwrap();
+        assert_eq!(mode.name.as_deref(), Some("Mode"));
+        let variants: Vec<_> = mode
+            .variants
+            .iter()
+            .map(|variant| (variant.name.as_str(), variant.value))
+            .collect();
+        assert_eq!(variants, [("ONE_SHOT", 0), ("PERIODIC", 1)]);
+
+        // Derived peripherals share the registers of their base
+        let derived = &device.peripherals[1];
+        assert_eq!(derived.base_address, 0x4000_1000);
+        assert_eq!(derived.description.as_deref(), Some("Basic timer"));
+        assert_eq!(derived.registers, timer.registers);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse("<device><name>MCU</name>").unwrap_err();
+        assert_eq!(error.location().map(|l| l.line), Some(1));
+
+        let svd = SVD.replace("<bitRange>[2:1]</bitRange>", "<bitRange>[2:x]</bitRange>");
+        let error = parse(&svd).unwrap_err();
+        assert_eq!(error.to_string(), "27:15: invalid bitRange [2:x]");
+
+        let svd = SVD
+            .replace("<lsb>8</lsb>", "<lsb>12</lsb>")
+            .replace("<msb>15</msb>", "<msb>16</msb>");
+        let error = parse(&svd).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "13:9: CTRL.STATUS: bits [12:16] do not fit in a 16 bit register"
+        );
+
+        let svd = SVD.replace("<value>#01</value>", "<value>#x1</value>");
+        let error = parse(&svd).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "36:55: don't care bits in enumerated value #x1 are not supported"
+        );
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        // SVD requires a device description, so one is made up from the name when missing
+        let mut device = parse(SVD).unwrap();
+        let written = write(&device).unwrap();
+        assert_eq!(parse(&written).unwrap().description.as_deref(), Some("MCU"));
+
+        device.description = Some("Example microcontroller".into());
+        let written = write(&device).unwrap();
+        assert_eq!(parse(&written).unwrap(), device);
+
+        let mut invalid = device;
+        invalid.peripherals[0].registers[0].size = 24;
+        assert!(write(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("0x2A"), Some(42));
+        assert_eq!(parse_number("0b101010"), Some(42));
+        assert_eq!(parse_number("#101010"), Some(42));
+        assert_eq!(parse_number("forty two"), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Write;
+
+use crate::Access;
+
+/// Writes indented XML, one element per line
+pub(crate) struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    pub(crate) fn new() -> Self {
+        Self {
+            out: String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n"),
+            depth: 0,
+        }
+    }
+
+    /// Open an element, `tag` may include attributes
+    pub(crate) fn open(&mut self, tag: &str) {
+        writeln!(self.out, "{:indent$}<{}>", "", tag, indent = self.depth * 2).unwrap();
+        self.depth += 1;
+    }
+
+    pub(crate) fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        writeln!(
+            self.out,
+            "{:indent$}</{}>",
+            "",
+            tag,
+            indent = self.depth * 2
+        )
+        .unwrap();
+    }
+
+    /// Write an element containing only text
+    pub(crate) fn element(&mut self, tag: &str, text: impl std::fmt::Display) {
+        writeln!(
+            self.out,
+            "{:indent$}<{tag}>{}</{tag}>",
+            "",
+            escape(&text.to_string()),
+            indent = self.depth * 2,
+            tag = tag
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Name of an access mode, which SVD and IP-XACT spell the same way
+pub(crate) fn access_name(access: Access) -> &'static str {
+    match access {
+        Access::ReadOnly => "read-only",
+        Access::WriteOnly => "write-only",
+        Access::ReadWrite => "read-write",
+        Access::WriteOnce => "writeOnce",
+        Access::ReadWriteOnce => "read-writeOnce",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_writer() {
+        let mut xml = XmlWriter::new();
+        xml.open("device schemaVersion=\"1.3\"");
+        xml.element("description", "Timers & <counters>");
+        xml.close("device");
+        assert_eq!(
+            xml.finish(),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <device schemaVersion=\"1.3\">\n  \
+             <description>Timers &amp; &lt;counters&gt;</description>\n\
+             </device>\n"
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use std::path::Path;
+
+/// Compare generated code with a golden file, rewriting it when `UPDATE_GOLDEN` is set
+pub fn assert_golden(generated: &str, golden: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(golden);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, generated).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        generated == expected,
+        "generated code differs from {}, rerun with UPDATE_GOLDEN=1 to update it:\n{}",
+        path.display(),
+        generated
+    );
+}
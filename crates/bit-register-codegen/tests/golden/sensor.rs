@@ -0,0 +1,88 @@
+// Generated by bit-register-codegen from TEMP_SENSOR. Do not edit.
+
+/// Register interface
+pub mod sensor {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x0000_0000;
+
+    bit_register! {
+        /// Configuration register
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Config: u8 {
+            /// Stop conversions to save power
+            pub shutdown: bool => [0],
+            pub rate: ConversionRate => [1:2],
+            pub alert_polarity: ConfigAlertPolarity => [3],
+        }
+    }
+
+    impl Config {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x01;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u8 = 0x02;
+    }
+
+    bit_register! {
+        /// Values of the RATE field of CONFIG
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ConversionRate: u8 {
+            /// One conversion per second
+            Hz1 = 0,
+            Hz4 = 1,
+            Hz16 = 2,
+        }
+    }
+
+    bit_register! {
+        /// Values of the ALERT_POLARITY field of CONFIG
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ConfigAlertPolarity: u8 {
+            ActiveLow = 0,
+            ActiveHigh = 1,
+        }
+    }
+
+    bit_register! {
+        /// Last converted temperature, in 1/16 °C
+        ///
+        /// Access: read-only
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Temperature: u16 {
+            pub value: u16 => [4:15],
+            /// A conversion has completed since the last read
+            pub ready: bool => [0],
+        }
+    }
+
+    impl Temperature {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x02;
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Limit: u16 {
+            /// Access: read-write
+            pub high: u8 => [8:15],
+            /// Access: write-only
+            pub low: u8 => [0:7],
+        }
+    }
+
+    impl Limit {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x04;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x5000;
+    }
+}
+
+/// Absolute address of every register, in declaration order
+pub const ADDRESS_MAP: &[(&str, u64)] = &[
+    ("SENSOR.CONFIG", 0x0000_0001),
+    ("SENSOR.TEMPERATURE", 0x0000_0002),
+    ("SENSOR.LIMIT", 0x0000_0004),
+];
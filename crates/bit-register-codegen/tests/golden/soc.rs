@@ -0,0 +1,444 @@
+// Generated by bit-register-codegen from example_soc. Do not edit.
+
+pub mod example_soc {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x0000_0000;
+
+    bit_register! {
+        /// Chip identification
+        ///
+        /// Access: read-only
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ChipId: u32 {
+            pub id: u32 => [0:31],
+        }
+    }
+
+    impl ChipId {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x4000_3000;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x1234_5678;
+    }
+}
+
+/// DMA controller
+pub mod dma0 {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4000_0000;
+
+    bit_register! {
+        /// Interrupt status
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Status: u32 {
+            /// Channel done flags
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: u8 => [0:1],
+            /// Bus error, write 1 to clear
+            ///
+            /// Access: read-write
+            ///
+            /// Hardware access: write-only
+            pub err: bool => [8],
+        }
+    }
+
+    impl Status {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x00;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Channel configuration
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Config: u16 {
+            /// Enable the channel
+            ///
+            /// Access: read-write
+            pub en: bool => [0],
+            /// Source transfer size
+            ///
+            /// Access: read-write
+            pub src_size: XferSize => [1:2],
+            /// Destination transfer size
+            ///
+            /// Access: read-write
+            pub dst_size: XferSize => [3:4],
+            /// Transfer complete
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: bool => [8],
+        }
+    }
+
+    impl Ch0Config {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x10;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0014;
+    }
+
+    bit_register! {
+        /// Values of the SRC_SIZE field of CH0_CONFIG
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum XferSize: u8 {
+            /// 8-bit transfers
+            Byte = 0,
+            /// 16-bit transfers
+            Half = 1,
+            /// 32-bit transfers
+            Word = 2,
+        }
+    }
+
+    bit_register! {
+        /// Source address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Src: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch0Src {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x14;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Destination address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Dst: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch0Dst {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x18;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Channel configuration
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Config: u16 {
+            /// Enable the channel
+            ///
+            /// Access: read-write
+            pub en: bool => [0],
+            /// Source transfer size
+            ///
+            /// Access: read-write
+            pub src_size: XferSize => [1:2],
+            /// Destination transfer size
+            ///
+            /// Access: read-write
+            pub dst_size: XferSize => [3:4],
+            /// Transfer complete
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: bool => [8],
+        }
+    }
+
+    impl Ch1Config {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x20;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0014;
+    }
+
+    bit_register! {
+        /// Source address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Src: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch1Src {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x24;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Destination address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Dst: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch1Dst {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x28;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+}
+
+/// DMA controller
+pub mod dma1 {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4000_1000;
+
+    bit_register! {
+        /// Interrupt status
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Status: u32 {
+            /// Channel done flags
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: u8 => [0:1],
+            /// Bus error, write 1 to clear
+            ///
+            /// Access: read-write
+            ///
+            /// Hardware access: write-only
+            pub err: bool => [8],
+        }
+    }
+
+    impl Status {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x00;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Channel configuration
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Config: u16 {
+            /// Enable the channel
+            ///
+            /// Access: read-write
+            pub en: bool => [0],
+            /// Source transfer size
+            ///
+            /// Access: read-write
+            pub src_size: XferSize => [1:2],
+            /// Destination transfer size
+            ///
+            /// Access: read-write
+            pub dst_size: XferSize => [3:4],
+            /// Transfer complete
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: bool => [8],
+        }
+    }
+
+    impl Ch0Config {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x10;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0014;
+    }
+
+    bit_register! {
+        /// Values of the SRC_SIZE field of CH0_CONFIG
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum XferSize: u8 {
+            /// 8-bit transfers
+            Byte = 0,
+            /// 16-bit transfers
+            Half = 1,
+            /// 32-bit transfers
+            Word = 2,
+        }
+    }
+
+    bit_register! {
+        /// Source address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Src: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch0Src {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x14;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Destination address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch0Dst: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch0Dst {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x18;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Channel configuration
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Config: u16 {
+            /// Enable the channel
+            ///
+            /// Access: read-write
+            pub en: bool => [0],
+            /// Source transfer size
+            ///
+            /// Access: read-write
+            pub src_size: XferSize => [1:2],
+            /// Destination transfer size
+            ///
+            /// Access: read-write
+            pub dst_size: XferSize => [3:4],
+            /// Transfer complete
+            ///
+            /// Access: read-only
+            ///
+            /// Hardware access: write-only
+            pub done: bool => [8],
+        }
+    }
+
+    impl Ch1Config {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x20;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0014;
+    }
+
+    bit_register! {
+        /// Source address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Src: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch1Src {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x24;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Destination address
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ch1Dst: u32 {
+            pub addr: u32 => [0:31],
+        }
+    }
+
+    impl Ch1Dst {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x28;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+}
+
+/// Watchdog
+pub mod wdt {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4000_2000;
+
+    bit_register! {
+        /// Write 0x5a5a to restart the countdown
+        ///
+        /// Access: write-only
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Kick: u16 {
+            /// Hardware access: read-only
+            pub key: u16 => [0:15],
+        }
+    }
+
+    impl Kick {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x00;
+    }
+
+    bit_register! {
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Cfg: u16 {
+            /// Timeout in milliseconds
+            pub timeout: u16 => [0:11],
+        }
+    }
+
+    impl Cfg {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x02;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0fff;
+    }
+}
+
+/// Absolute address of every register, in declaration order
+pub const ADDRESS_MAP: &[(&str, u64)] = &[
+    ("example_soc.CHIP_ID", 0x4000_3000),
+    ("DMA0.STATUS", 0x4000_0000),
+    ("DMA0.CH0_CONFIG", 0x4000_0010),
+    ("DMA0.CH0_SRC", 0x4000_0014),
+    ("DMA0.CH0_DST", 0x4000_0018),
+    ("DMA0.CH1_CONFIG", 0x4000_0020),
+    ("DMA0.CH1_SRC", 0x4000_0024),
+    ("DMA0.CH1_DST", 0x4000_0028),
+    ("DMA1.STATUS", 0x4000_1000),
+    ("DMA1.CH0_CONFIG", 0x4000_1010),
+    ("DMA1.CH0_SRC", 0x4000_1014),
+    ("DMA1.CH0_DST", 0x4000_1018),
+    ("DMA1.CH1_CONFIG", 0x4000_1020),
+    ("DMA1.CH1_SRC", 0x4000_1024),
+    ("DMA1.CH1_DST", 0x4000_1028),
+    ("WDT.KICK", 0x4000_2000),
+    ("WDT.CFG", 0x4000_2002),
+];
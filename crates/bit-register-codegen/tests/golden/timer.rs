@@ -0,0 +1,127 @@
+// Generated by bit-register-codegen from EXAMPLE_MCU. Do not edit.
+
+/// General purpose timer
+pub mod timer0 {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4001_0000;
+
+    bit_register! {
+        /// Control register
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Ctrl: u16 {
+            /// Start the timer
+            pub en: bool => [0],
+            /// Counting mode
+            pub mode: CountMode => [1:2],
+            /// Divide the input clock by 2^PRESCALER
+            pub prescaler: u8 => [4:7],
+        }
+    }
+
+    impl Ctrl {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x00;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u16 = 0x0002;
+    }
+
+    bit_register! {
+        /// Values of the MODE field of CTRL
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum CountMode: u8 {
+            /// Stop at the end of the period
+            OneShot = 0,
+            /// Restart at the end of the period
+            Periodic = 1,
+            Pwm = 2,
+        }
+    }
+
+    bit_register! {
+        /// Status register
+        ///
+        /// Access: read-only
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Status: u32 {
+            pub running: bool => [0],
+            /// Current counter value
+            pub count: u16 => [16:31],
+        }
+    }
+
+    impl Status {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x04;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Compare value 0
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Cc0: u32 {
+            pub value: u16 => [0:15],
+        }
+    }
+
+    impl Cc0 {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x10;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+
+    bit_register! {
+        /// Compare value 1
+        ///
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Cc1: u32 {
+            pub value: u16 => [0:15],
+        }
+    }
+
+    impl Cc1 {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x14;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u32 = 0x0000_0000;
+    }
+}
+
+/// GPIO port A
+pub mod gpioa {
+    use bit_register::bit_register;
+
+    /// Address of the peripheral
+    pub const BASE_ADDRESS: u64 = 0x4002_0000;
+
+    bit_register! {
+        /// Access: read-write
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Out: u8 {
+        }
+    }
+
+    impl Out {
+        /// Offset of the register from the peripheral's base address
+        pub const OFFSET: u64 = 0x00;
+        /// Value of the register after reset
+        pub const RESET_VALUE: u8 = 0x00;
+    }
+}
+
+/// Absolute address of every register, in declaration order
+pub const ADDRESS_MAP: &[(&str, u64)] = &[
+    ("TIMER0.CTRL", 0x4001_0000),
+    ("TIMER0.STATUS", 0x4001_0004),
+    ("TIMER0.CC0", 0x4001_0010),
+    ("TIMER0.CC1", 0x4001_0014),
+    ("GPIOA.OUT", 0x4002_0000),
+];
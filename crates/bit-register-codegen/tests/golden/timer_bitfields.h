@@ -0,0 +1,70 @@
+/* Generated by bit-register-codegen from MCU. Do not edit. */
+
+#ifndef MCU_REGISTERS_H
+#define MCU_REGISTERS_H
+
+#include <stdint.h>
+
+/* Basic timer */
+#define TIMER0_BASE_ADDRESS 0x40000000u
+
+/* Timer control and reload */
+#define TIMER0_CONTROL_OFFSET 0x00u
+#define TIMER0_CONTROL_ADDRESS (TIMER0_BASE_ADDRESS + TIMER0_CONTROL_OFFSET)
+#define TIMER0_CONTROL_RESET_VALUE 0xFFFF0000u
+
+#define TIMER0_CONTROL_ENABLE_SHIFT 0
+#define TIMER0_CONTROL_ENABLE_MASK 0x00000001u
+
+#define TIMER0_CONTROL_PRESCALER_SHIFT 1
+#define TIMER0_CONTROL_PRESCALER_MASK 0x00000006u
+#define TIMER0_CONTROL_PRESCALER_DIV1 0x0u
+#define TIMER0_CONTROL_PRESCALER_DIV8 0x1u
+#define TIMER0_CONTROL_PRESCALER_DIV64 0x2u
+
+#define TIMER0_CONTROL_TIMEOUT_SHIFT 4
+#define TIMER0_CONTROL_TIMEOUT_MASK 0x00000030u
+#define TIMER0_CONTROL_TIMEOUT_VALUE_1 0x0u
+#define TIMER0_CONTROL_TIMEOUT_VALUE_4 0x1u
+#define TIMER0_CONTROL_TIMEOUT_VALUE_16 0x2u
+#define TIMER0_CONTROL_TIMEOUT_VALUE_64 0x3u
+
+#define TIMER0_CONTROL_RELOAD_SHIFT 16
+#define TIMER0_CONTROL_RELOAD_MASK 0xFFFF0000u
+
+typedef union {
+    struct {
+        uint32_t enable : 1;
+        uint32_t prescaler : 2;
+        uint32_t : 1;
+        uint32_t timeout : 2;
+        uint32_t : 10;
+        uint32_t reload : 16;
+    } bits;
+    uint32_t raw;
+} timer0_control_t;
+
+/* Access: read-only */
+#define TIMER0_STATUS_OFFSET 0x04u
+#define TIMER0_STATUS_ADDRESS (TIMER0_BASE_ADDRESS + TIMER0_STATUS_OFFSET)
+
+#define TIMER0_STATUS_OVERFLOW_SHIFT 0
+#define TIMER0_STATUS_OVERFLOW_MASK 0x01u
+
+#define TIMER0_STATUS_CHANNEL_SHIFT 1
+#define TIMER0_STATUS_CHANNEL_MASK 0x0Eu
+#define TIMER0_STATUS_CHANNEL_NONE 0x7u
+
+#define TIMER0_STATUS_COUNT_SHIFT 4
+#define TIMER0_STATUS_COUNT_MASK 0xF0u
+
+typedef union {
+    struct {
+        uint8_t overflow : 1;
+        uint8_t channel : 3;
+        uint8_t count : 4;
+    } bits;
+    uint8_t raw;
+} timer0_status_t;
+
+#endif /* MCU_REGISTERS_H */
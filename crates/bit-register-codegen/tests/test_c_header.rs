@@ -0,0 +1,69 @@
+#![allow(missing_docs)]
+
+use bit_register::bit_register;
+use bit_register_codegen::{
+    generate_c_header, Access, CHeaderOptions, Device, Peripheral, Register,
+};
+
+mod common;
+use common::assert_golden;
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Prescaler: u8 {
+        Div1 = 0,
+        Div8 = 1,
+        Div64 = 2,
+    }
+}
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Control: u32 {
+        pub enable: bool => [0],
+        pub prescaler: Prescaler => [1:2],
+        pub timeout: u16 => [4:5] table [1, 4, 16, 64],
+        pub reload: u16 => [16:31],
+    }
+}
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Status: u8 {
+        pub overflow: bool => [0],
+        pub channel: Option<u8> => [1:3] none 0x7,
+        pub count: u8 => [4:7],
+    }
+}
+
+fn device() -> Device {
+    let mut control = Register::of::<Control>(0x00);
+    control.description = Some("Timer control and reload".into());
+    control.reset_value = Some(0xFFFF_0000);
+    let mut status = Register::of::<Status>(0x04);
+    status.access = Some(Access::ReadOnly);
+
+    Device {
+        name: "MCU".into(),
+        description: None,
+        peripherals: vec![Peripheral {
+            name: "TIMER0".into(),
+            description: Some("Basic timer".into()),
+            base_address: 0x4000_0000,
+            registers: vec![control, status],
+        }],
+    }
+}
+
+#[test]
+fn test_c_header_golden() {
+    let header = generate_c_header(&device(), &CHeaderOptions::default()).unwrap();
+    assert_golden(&header, "timer.h");
+}
+
+#[test]
+fn test_c_header_bitfields_golden() {
+    let options = CHeaderOptions { bitfields: true };
+    let header = generate_c_header(&device(), &options).unwrap();
+    assert_golden(&header, "timer_bitfields.h");
+}
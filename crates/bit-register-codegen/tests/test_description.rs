@@ -0,0 +1,47 @@
+#![allow(missing_docs)]
+
+use bit_register_codegen::Build;
+
+mod common;
+use common::assert_golden;
+
+const TOML: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sensor.toml");
+const JSON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sensor.json");
+
+// The golden file is compiled here, so the generated code is checked against the macro too
+#[allow(dead_code)]
+mod generated {
+    include!("golden/sensor.rs");
+}
+
+#[test]
+fn test_toml_golden() {
+    let generated = Build::toml(TOML).generate_string().unwrap();
+    assert_golden(&generated, "sensor.rs");
+}
+
+#[test]
+fn test_json_matches_toml() {
+    assert_eq!(
+        Build::json(JSON).device().unwrap(),
+        Build::toml(TOML).device().unwrap()
+    );
+}
+
+#[test]
+fn test_generated_registers() {
+    use generated::sensor::{Config, ConfigAlertPolarity, ConversionRate, Limit};
+
+    assert_eq!(Config::OFFSET, 0x01);
+    assert_eq!(
+        Config::try_from(Config::RESET_VALUE).unwrap(),
+        Config {
+            shutdown: false,
+            rate: ConversionRate::Hz4,
+            alert_polarity: ConfigAlertPolarity::ActiveLow,
+        }
+    );
+
+    let limit = Limit::try_from(Limit::RESET_VALUE).unwrap();
+    assert_eq!((limit.high, limit.low), (0x50, 0));
+}
@@ -0,0 +1,80 @@
+#![allow(missing_docs)]
+
+use bit_register::bit_register;
+use bit_register_codegen::{
+    generate_html, generate_markdown, Access, Device, Peripheral, Register,
+};
+
+mod common;
+use common::assert_golden;
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Prescaler: u8 {
+        /// Count every clock cycle
+        Div1 = 0,
+        /// Count every 8 clock cycles
+        Div8 = 1,
+        /// Count every 64 clock cycles
+        Div64 = 2,
+    }
+}
+
+bit_register! {
+    /// Timer control and reload
+    ///
+    /// The reload value is loaded into the counter when it <wraps>.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Control: u32 {
+        /// Start the timer
+        pub enable: bool => [0],
+        /// Clock divider
+        pub prescaler: Prescaler => [1:2],
+        /// Cycles before the watchdog fires
+        pub timeout: u16 => [4:5] table [1, 4, 16, 64],
+        /// Time between counter ticks
+        pub tick: u16 => [8:11] scale 250 offset 250 unit "us",
+        /// Value the counter restarts from
+        pub reload: u16 => [16:31],
+    }
+}
+
+bit_register! {
+    /// Timer status
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Status: u8 {
+        /// The counter wrapped since the last read
+        pub overflow: bool => [0],
+        /// Channel which overflowed
+        pub channel: Option<u8> => [1:3] none 0x7,
+        pub count: u8 => [4:7] in 1..=12,
+    }
+}
+
+fn device() -> Device {
+    let mut control = Register::of::<Control>(0x00);
+    control.reset_value = Some(0xFFFF_0002);
+    let mut status = Register::of::<Status>(0x04);
+    status.access = Some(Access::ReadOnly);
+
+    Device {
+        name: "MCU".into(),
+        description: Some("Example microcontroller".into()),
+        peripherals: vec![Peripheral {
+            name: "TIMER0".into(),
+            description: Some("Basic timer".into()),
+            base_address: 0x4000_0000,
+            registers: vec![control, status],
+        }],
+    }
+}
+
+#[test]
+fn test_markdown_golden() {
+    assert_golden(&generate_markdown(&device()).unwrap(), "timer.md");
+}
+
+#[test]
+fn test_html_golden() {
+    assert_golden(&generate_html(&device()).unwrap(), "timer.html");
+}
@@ -0,0 +1,80 @@
+#![allow(missing_docs)]
+
+use bit_register::bit_register;
+use bit_register_codegen::ipxact::{self, Vlnv};
+use bit_register_codegen::{svd, Access, Device, Peripheral, Register};
+
+mod common;
+use common::assert_golden;
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Prescaler: u8 {
+        Div1 = 0,
+        Div8 = 1,
+        Div64 = 2,
+    }
+}
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Control: u32 {
+        pub enable: bool => [0],
+        pub prescaler: Prescaler => [1:2],
+        pub timeout: u16 => [4:5] table [1, 4, 16, 64],
+        pub reload: u16 => [16:31],
+    }
+}
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Status: u8 {
+        pub overflow: bool => [0],
+        pub count: u8 => [4:7] in 1..=12,
+    }
+}
+
+fn device() -> Device {
+    let mut control = Register::of::<Control>(0x00);
+    control.description = Some("Timer control & reload".into());
+    control.reset_value = Some(0xFFFF_0000);
+    let mut status = Register::of::<Status>(0x04);
+    status.access = Some(Access::ReadOnly);
+
+    Device {
+        name: "MCU".into(),
+        description: Some("Example microcontroller".into()),
+        peripherals: vec![Peripheral {
+            name: "TIMER0".into(),
+            description: Some("Basic timer".into()),
+            base_address: 0x4000_0000,
+            registers: vec![control, status],
+        }],
+    }
+}
+
+#[test]
+fn test_svd_golden() {
+    assert_golden(&svd::write(&device()).unwrap(), "timer_export.svd");
+}
+
+#[test]
+fn test_svd_round_trip() {
+    // Lookup tables are read back as the named values they are written as
+    let mut device = device();
+    let timeout = &mut device.peripherals[0].registers[0].fields[2];
+    assert_eq!(timeout.name, "timeout");
+    timeout.enumeration = timeout.table_enumeration();
+    timeout.table.clear();
+    assert_eq!(svd::parse(&svd::write(&device).unwrap()).unwrap(), device);
+}
+
+#[test]
+fn test_ipxact_golden() {
+    let vlnv = Vlnv {
+        vendor: "example.com",
+        library: "timers",
+        version: "1.0",
+    };
+    assert_golden(&ipxact::write(&device(), vlnv).unwrap(), "timer_export.xml");
+}
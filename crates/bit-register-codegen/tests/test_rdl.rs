@@ -0,0 +1,64 @@
+#![allow(missing_docs)]
+
+use bit_register_codegen::Build;
+
+mod common;
+use common::assert_golden;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/soc.rdl");
+
+// The golden file is compiled here, so the generated code is checked against the macro too
+#[allow(dead_code)]
+mod generated {
+    include!("golden/soc.rs");
+}
+
+#[test]
+fn test_rdl_golden() {
+    let generated = Build::rdl(FIXTURE).generate_string().unwrap();
+    assert_golden(&generated, "soc.rs");
+}
+
+#[test]
+fn test_generated_registers() {
+    use generated::dma0::{Ch1Config, Status, XferSize, BASE_ADDRESS};
+
+    assert_eq!(BASE_ADDRESS, 0x4000_0000);
+    assert_eq!(Ch1Config::OFFSET, 0x20);
+    assert_eq!(
+        Ch1Config::try_from(Ch1Config::RESET_VALUE).unwrap(),
+        Ch1Config {
+            en: false,
+            src_size: XferSize::Word,
+            dst_size: XferSize::Word,
+            done: false,
+        }
+    );
+
+    let raw: u32 = Status { done: 3, err: true }.try_into().unwrap();
+    assert_eq!(raw, 0x103);
+}
+
+#[test]
+fn test_address_map() {
+    use generated::ADDRESS_MAP;
+
+    let address = |name| {
+        ADDRESS_MAP
+            .iter()
+            .find(|(register, _)| *register == name)
+            .map(|(_, address)| *address)
+    };
+    assert_eq!(address("DMA1.CH1_DST"), Some(0x4000_1028));
+    assert_eq!(address("WDT.CFG"), Some(0x4000_2002));
+    assert_eq!(address("example_soc.CHIP_ID"), Some(0x4000_3000));
+    assert_eq!(ADDRESS_MAP.len(), 17);
+}
+
+#[test]
+fn test_select_peripherals() {
+    let device = Build::rdl(FIXTURE).peripheral("WDT").device().unwrap();
+    assert_eq!(device.name, "example_soc");
+    assert_eq!(device.peripherals.len(), 1);
+    assert_eq!(device.peripherals[0].base_address, 0x4000_2000);
+}
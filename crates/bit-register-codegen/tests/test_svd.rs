@@ -0,0 +1,73 @@
+#![allow(missing_docs)]
+
+use std::path::Path;
+
+use bit_register_codegen::Build;
+
+mod common;
+use common::assert_golden;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/timer.svd");
+
+// The golden file is compiled here, so the generated code is checked against the macro too
+#[allow(dead_code)]
+mod generated {
+    include!("golden/timer.rs");
+}
+
+#[test]
+fn test_svd_golden() {
+    let generated = Build::svd(FIXTURE).generate_string().unwrap();
+    assert_golden(&generated, "timer.rs");
+}
+
+#[test]
+fn test_select_peripherals() {
+    let device = Build::svd(FIXTURE).peripheral("GPIOA").device().unwrap();
+    assert_eq!(device.peripherals.len(), 1);
+    assert_eq!(device.peripherals[0].name, "GPIOA");
+
+    let error = Build::svd(FIXTURE)
+        .peripheral("UART0")
+        .device()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "device EXAMPLE_MCU has no peripheral named UART0"
+    );
+}
+
+#[test]
+fn test_generated_registers() {
+    use generated::timer0::{CountMode, Ctrl, Status, BASE_ADDRESS};
+
+    assert_eq!(BASE_ADDRESS, 0x4001_0000);
+    assert_eq!(Status::OFFSET, 0x04);
+
+    let reset = Ctrl::try_from(Ctrl::RESET_VALUE).unwrap();
+    assert_eq!(
+        reset,
+        Ctrl {
+            en: false,
+            mode: CountMode::Periodic,
+            prescaler: 0,
+        }
+    );
+
+    let ctrl = Ctrl {
+        en: true,
+        mode: CountMode::Pwm,
+        prescaler: 3,
+    };
+    let raw: u16 = ctrl.try_into().unwrap();
+    assert_eq!(raw, 0b0011_0101);
+
+    // Values without a name are rejected like any other enum
+    assert!(Ctrl::try_from(0b0110u16).is_err());
+}
+
+#[test]
+fn test_missing_file() {
+    let error = Build::svd("missing.svd").generate_string().unwrap_err();
+    assert_eq!(error.path(), Some(Path::new("missing.svd")));
+}
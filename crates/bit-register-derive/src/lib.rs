@@ -0,0 +1,1456 @@
+//! # Bit Register Derive
+//!
+//! A derive macro alternative to the `bit_register!` macro from the `bit-register` crate.
+//!
+//! ## Overview
+//!
+//! `#[derive(BitRegister)]` generates the same `TryFrom`, `TryInto`, `NumBytes`, `BitRegister`
+//! and `RegisterLayout` implementations for a struct as `bit_register!`, and the same
+//! `TryFromBits`, `TryIntoBits`, `NumBytes` and `FieldType` implementations for an enum. As a
+//! derive it works with ordinary Rust syntax, so field types can be any type, structs can be
+//! generic, and mistakes are reported at the offending field.
+//!
+//! The underlying type is given by `#[bit_register(u8)]`, `#[bit_register(u16)]`,
+//! `#[bit_register(u32)]` or `#[bit_register(u64)]`, and every field of a struct names its bits
+//! with `#[bits(3)]`, `#[bits(1..=3)]` or `#[bits(1..4)]`. Enum variants need explicit
+//! discriminants. A field can be restricted to some raw values with `#[valid(1..=10 | 12)]`, and
+//! an integer field can hold a physical value of `raw * 16 + 2000` with
+//! `#[scale(16, offset = 2000, unit = "mV")]`. An `Option<T>` field with `#[none(0xff)]` decodes
+//! the raw value `0xff` to `None`, and encodes `None` back to it. An integer field with
+//! `#[table(1, 4, 16, 64)]` decodes raw bits of 2 to 16, and encodes 16 back to 2.
+//!
+//! Bits are numbered from the least significant end unless the struct has
+//! `#[bit_register(u16, bit_order = msb0)]`, in which case bit 0 is the most significant bit.
+//! A struct with `#[bit_register(u16, validate = path::to_fn)]` is also checked as a whole by
+//! `fn(&Self) -> Result<(), &'static str>` after decoding and before encoding, the same
+//! function `bit_register!` takes. The derive has no `const` conversions, so it need not be a
+//! `const fn`.
+//!
+//! ## Usage
+//!
+//! The derive is re-exported by `bit-register` with its `derive` feature:
+//!
+//! ```rust
+//! use bit_register::BitRegister;
+//!
+//! #[derive(BitRegister, Debug, Clone, Copy, PartialEq)]
+//! #[bit_register(u8)]
+//! pub enum OperationMode {
+//!     Idle = 0,
+//!     Active = 1,
+//! }
+//!
+//! #[derive(BitRegister, Debug, PartialEq)]
+//! #[bit_register(u16)]
+//! pub struct StatusRegister {
+//!     #[bits(0)]
+//!     pub enabled: bool,
+//!     #[bits(1..=2)]
+//!     pub mode: OperationMode,
+//!     #[bits(8..=15)]
+//!     pub count: u8,
+//! }
+//!
+//! let status = StatusRegister::try_from(0x2a03u16).unwrap();
+//! assert_eq!(status.mode, OperationMode::Active);
+//! let bits: u16 = status.try_into().unwrap();
+//! assert_eq!(bits, 0x2a03);
+//! ```
+//!
+//! Fields must fit in the underlying type and must not overlap, which is checked when the
+//! derive expands:
+//!
+//! ```text
+//! error: bits 12..=16 do not fit in u16
+//!   |
+//!   |     #[bits(12..=16)]
+//!   |            ^^^^^^^
+//! ```
+
+use std::any::type_name;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Error, Expr, ExprLit,
+    ExprUnary, Fields, GenericArgument, Ident, Lit, LitInt, Meta, Pat, PatLit, PatRange, Path,
+    PathArguments, RangeLimits, Token, Type, UnOp,
+};
+
+/// Derive the bit register implementations of `bit_register!` for a struct or enum
+///
+/// See the crate documentation for the attributes it reads.
+#[proc_macro_derive(BitRegister, attributes(bit_register, bits, valid, scale, none, table))]
+pub fn derive_bit_register(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let repr = Repr::from_attrs(input)?;
+    match &input.data {
+        Data::Struct(data) => expand_struct(input, data, &repr),
+        Data::Enum(data) => expand_enum(input, data, &repr),
+        Data::Union(data) => Err(Error::new(
+            data.union_token.span,
+            "BitRegister cannot be derived for unions",
+        )),
+    }
+}
+
+/// The underlying unsigned integer type and register options
+struct Repr {
+    ident: Ident,
+    bits: u32,
+    msb0: bool,
+    /// Function checking a whole register, from `validate = path`
+    validate: Option<Path>,
+}
+
+impl Repr {
+    fn from_attrs(input: &DeriveInput) -> syn::Result<Self> {
+        let mut repr: Option<Self> = None;
+        for attr in &input.attrs {
+            if !attr.path().is_ident("bit_register") {
+                continue;
+            }
+            if repr.is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "duplicate #[bit_register(...)] attribute",
+                ));
+            }
+
+            let mut ty = None;
+            let mut msb0 = None;
+            let mut validate = None;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("validate") {
+                    if validate.is_some() {
+                        return Err(meta.error("duplicate validate"));
+                    }
+                    validate = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("bit_order") {
+                    if msb0.is_some() {
+                        return Err(meta.error("duplicate bit_order"));
+                    }
+                    let order: Ident = meta.value()?.parse()?;
+                    msb0 = match order.to_string().as_str() {
+                        "msb0" => Some(true),
+                        "lsb0" => Some(false),
+                        _ => {
+                            return Err(Error::new_spanned(
+                                &order,
+                                "bit_order must be msb0 or lsb0",
+                            ))
+                        }
+                    };
+                    return Ok(());
+                }
+
+                let ident = meta.path.require_ident()?;
+                let bits = match ident.to_string().as_str() {
+                    "u8" => 8,
+                    "u16" => 16,
+                    "u32" => 32,
+                    "u64" => 64,
+                    _ => return Err(meta.error("the underlying type must be u8, u16, u32 or u64")),
+                };
+                if ty.is_some() {
+                    return Err(meta.error("duplicate underlying type"));
+                }
+                ty = Some((ident.clone(), bits));
+                Ok(())
+            })?;
+
+            let Some((ident, bits)) = ty else {
+                return Err(Error::new_spanned(
+                    attr,
+                    "missing the underlying type, such as #[bit_register(u16)]",
+                ));
+            };
+            repr = Some(Self {
+                ident,
+                bits,
+                msb0: msb0.unwrap_or(false),
+                validate,
+            });
+        }
+
+        repr.ok_or_else(|| {
+            Error::new(
+                input.ident.span(),
+                "missing #[bit_register(...)] attribute naming the underlying type, such as #[bit_register(u16)]",
+            )
+        })
+    }
+
+    /// The `(lsb, msb)` of bits as written, counted from the least significant end
+    fn lsb0(&self, first: u32, last: u32) -> (u32, u32) {
+        if self.msb0 {
+            (self.bits - 1 - last, self.bits - 1 - first)
+        } else {
+            (first, last)
+        }
+    }
+}
+
+/// A struct field and the bits it occupies
+struct BitField<'a> {
+    ident: &'a Ident,
+    /// Type of the field's values, which is `T` for an `Option<T>` field
+    ty: &'a Type,
+    lsb: u32,
+    msb: u32,
+    valid: Vec<(u64, u64)>,
+    scale: Option<Scale>,
+    /// Raw value of `None`, if the field is an `Option`
+    none: Option<u64>,
+    /// Values of the raw bits as indexes, or empty if the field has no lookup table
+    table: Vec<i128>,
+    docs: Vec<&'a Expr>,
+}
+
+/// The linear conversion of a scaled field's raw bits to a physical value
+struct Scale {
+    scale: i64,
+    offset: i64,
+    unit: String,
+}
+
+impl Scale {
+    fn info(&self) -> TokenStream2 {
+        let Self {
+            scale,
+            offset,
+            unit,
+        } = self;
+        quote! {
+            ::bit_register::FieldScale {
+                scale: #scale,
+                offset: #offset,
+                unit: #unit,
+            }
+        }
+    }
+}
+
+impl BitField<'_> {
+    fn width(&self) -> u32 {
+        self.msb - self.lsb + 1
+    }
+
+    fn value_mask(&self) -> u64 {
+        if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.width()) - 1
+        }
+    }
+
+    /// Return an error if the raw bits in `raw` are not among the field's valid values
+    fn check_valid(&self, raw: TokenStream2) -> TokenStream2 {
+        if self.valid.is_empty() {
+            return quote!();
+        }
+        let patterns = self.valid.iter().map(|&(min, max)| {
+            let (min_lit, max_lit) = (Literal::u64_unsuffixed(min), Literal::u64_unsuffixed(max));
+            if min == max {
+                quote!(#min_lit)
+            } else {
+                quote!(#min_lit..=#max_lit)
+            }
+        });
+        let text: Vec<String> = self
+            .valid
+            .iter()
+            .map(|&(min, max)| {
+                if min == max {
+                    min.to_string()
+                } else {
+                    format!("{}..={}", min, max)
+                }
+            })
+            .collect();
+        let invalid = format!("{} is not in {}", self.ident, text.join(" | "));
+        quote! {
+            if !::core::matches!(#raw, #(#patterns)|*) {
+                return ::core::result::Result::Err(#invalid);
+            }
+        }
+    }
+}
+
+fn expand_struct(input: &DeriveInput, data: &DataStruct, repr: &Repr) -> syn::Result<TokenStream2> {
+    let Fields::Named(named) = &data.fields else {
+        return Err(Error::new(
+            input.ident.span(),
+            "BitRegister can only be derived for structs with named fields",
+        ));
+    };
+
+    // Collect every field error so they are all reported at once
+    let mut fields: Vec<BitField> = Vec::new();
+    let mut errors: Option<Error> = None;
+    for field in &named.named {
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let bits = field_bits(ident, &field.attrs, repr, &fields).and_then(|(lsb, msb)| {
+            let valid = field_valid(&field.attrs, msb - lsb + 1)?;
+            let scale = field_scale(&field.attrs)?;
+            let (ty, none) = field_none(&field.ty, &field.attrs, msb - lsb + 1)?;
+            let table = field_table(&field.attrs, msb - lsb + 1)?;
+            if none.is_some() && scale.is_some() {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`Option` fields cannot be scaled",
+                ));
+            }
+            if none.is_some() && !table.is_empty() {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`Option` fields cannot have a lookup table",
+                ));
+            }
+            if scale.is_some() && !table.is_empty() {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "fields with a lookup table cannot be scaled",
+                ));
+            }
+            Ok((ty, lsb, msb, valid, scale, none, table))
+        });
+        match bits {
+            Ok((ty, lsb, msb, valid, scale, none, table)) => fields.push(BitField {
+                ident,
+                ty,
+                lsb,
+                msb,
+                valid,
+                scale,
+                none,
+                table,
+                docs: docs(&field.attrs),
+            }),
+            Err(error) => match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    // Fields are checked as written, and only then counted from the least significant end
+    for field in &mut fields {
+        (field.lsb, field.msb) = repr.lsb0(field.lsb, field.msb);
+    }
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let repr_ident = &repr.ident;
+    let bits = Literal::u32_unsuffixed(repr.bits);
+
+    // Generic field types need the bit conversion traits, and `FieldType` so the layout can
+    // describe them. Concrete field types without `FieldType` are described as unsigned.
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_params: Vec<&Ident> = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let mut predicates: Vec<TokenStream2> = where_clause
+        .map(|clause| clause.predicates.iter().map(|p| quote!(#p)).collect())
+        .unwrap_or_default();
+    for field in &fields {
+        if mentions(field.ty, &type_params) {
+            let ty = field.ty;
+            if field.scale.is_some() {
+                predicates.push(quote_spanned! {ty.span()=>
+                    #ty: ::bit_register::ScaledType
+                });
+                continue;
+            }
+            predicates.push(quote_spanned! {ty.span()=>
+                #ty: ::bit_register::TryFromBits<#repr_ident>
+                    + ::bit_register::TryIntoBits<#repr_ident>
+                    + ::bit_register::NumBytes
+                    + ::bit_register::FieldType
+            });
+        }
+    }
+    let where_clause = if predicates.is_empty() {
+        quote!()
+    } else {
+        quote!(where #(#predicates),*)
+    };
+
+    let extracts = fields.iter().map(|field| {
+        let ident = field.ident;
+        let lsb = Literal::u32_unsuffixed(field.lsb);
+        let mask = Literal::u64_unsuffixed(field.value_mask());
+        let ty = field.ty;
+        let convert = match &field.scale {
+            Some(scale) => {
+                let scale = scale.info();
+                let overflow = format!("{} does not fit in {}", ident, quote!(#ty));
+                quote_spanned! {ty.span()=>
+                    <#ty as ::bit_register::ScaledType>::from_physical(
+                        #scale.physical(extracted_value as u64),
+                    )
+                    .ok_or(#overflow)?
+                }
+            }
+            None => quote_spanned! {ty.span()=>
+                ::bit_register::TryFromBits::try_from_bits(extracted_value)?
+            },
+        };
+        let check_valid = field.check_valid(quote!(extracted_value));
+        if !field.table.is_empty() {
+            let table = field
+                .table
+                .iter()
+                .map(|&value| Literal::i128_unsuffixed(value));
+            let missing = format!("{} is not an index of its lookup table", ident);
+            return quote! {
+                #ident: {
+                    const TABLE: &[#ty] = &[#(#table),*];
+                    let mask: #repr_ident = #mask;
+                    let extracted_value = (value >> #lsb) & mask;
+                    #check_valid
+                    match TABLE.get(extracted_value as usize) {
+                        ::core::option::Option::Some(field_value) => *field_value,
+                        ::core::option::Option::None => return Err(#missing),
+                    }
+                }
+            };
+        }
+        if let Some(none) = field.none {
+            let none = Literal::u64_unsuffixed(none);
+            return quote! {
+                #ident: {
+                    let mask: #repr_ident = #mask;
+                    let extracted_value = (value >> #lsb) & mask;
+                    if extracted_value == #none {
+                        ::core::option::Option::None
+                    } else {
+                        #check_valid
+                        ::core::option::Option::Some(#convert)
+                    }
+                }
+            };
+        }
+        quote! {
+            #ident: {
+                let mask: #repr_ident = #mask;
+                let extracted_value = (value >> #lsb) & mask;
+                #check_valid
+                #convert
+            }
+        }
+    });
+
+    let packs = fields.iter().map(|field| {
+        let ident = field.ident;
+        let ty = field.ty;
+        let lsb = Literal::u32_unsuffixed(field.lsb);
+        let width = Literal::usize_unsuffixed(field.width() as usize);
+        let mask = Literal::u64_unsuffixed(field.value_mask());
+        let overflow = format!("{} exceeds maximum value for its bit width", ident);
+        // `Some` values of an `Option` field are converted like any other field
+        let source = match field.none {
+            Some(_) => quote!(field_value),
+            None => quote!(self.#ident),
+        };
+        let convert = quote_spanned! {ty.span()=>
+            ::bit_register::TryIntoBits::try_into_bits(#source)?
+        };
+        let check_valid = field.check_valid(quote!(field_value));
+        if let Some(scale) = &field.scale {
+            let scale = scale.info();
+            let overflow = format!("{} is out of range for its scale and bit width", ident);
+            return quote! {
+                value |= {
+                    // Physical values are rounded to the nearest step
+                    let raw = #scale.raw(<#ty as ::bit_register::ScaledType>::to_physical(self.#ident));
+                    if !(0..=#mask).contains(&raw) {
+                        return Err(#overflow);
+                    }
+                    let field_value = raw as #repr_ident;
+                    #check_valid
+                    field_value << #lsb
+                };
+            };
+        }
+        if !field.table.is_empty() {
+            let table = field
+                .table
+                .iter()
+                .map(|&value| Literal::i128_unsuffixed(value));
+            let missing = format!("{} is not in its lookup table", ident);
+            return quote! {
+                value |= {
+                    const TABLE: &[#ty] = &[#(#table),*];
+                    let index = TABLE
+                        .iter()
+                        .position(|field_value| *field_value == self.#ident)
+                        .ok_or(#missing)?;
+                    let field_value = index as #repr_ident;
+                    #check_valid
+                    field_value << #lsb
+                };
+            };
+        }
+        let reserved = field.none.map(|none| {
+            let none = Literal::u64_unsuffixed(none);
+            let reserved = format!("{} is the raw value reserved for None", ident);
+            quote! {
+                if field_value == #none {
+                    return Err(#reserved);
+                }
+            }
+        });
+        let packed = quote! {
+            {
+                let bit_count: usize = #width;
+                let field_type_bits: usize = <#ty as ::bit_register::NumBytes>::NUM_BYTES * 8;
+                let field_value: #repr_ident = #convert;
+
+                // Fields whose type is no wider than their bits always fit
+                if bit_count < field_type_bits && field_value as u64 > #mask {
+                    return Err(#overflow);
+                }
+                #reserved
+                #check_valid
+
+                let field_mask: #repr_ident = if bit_count >= field_type_bits {
+                    #repr_ident::MAX
+                } else {
+                    #mask
+                };
+                (field_value & field_mask) << #lsb
+            }
+        };
+        match field.none {
+            Some(none) => {
+                let none = Literal::u64_unsuffixed(none);
+                quote! {
+                    value |= match self.#ident {
+                        ::core::option::Option::None => #none << #lsb,
+                        ::core::option::Option::Some(field_value) => #packed,
+                    };
+                }
+            }
+            None => quote! {
+                value |= #packed;
+            },
+        }
+    });
+
+    // The whole register is checked after decoding its fields, and before encoding them
+    let (validate_register, validate_self) = match &repr.validate {
+        Some(validate) => (quote!(#validate(&register)?;), quote!(#validate(&self)?;)),
+        None => (quote!(), quote!()),
+    };
+
+    let field_infos = fields.iter().map(|field| {
+        let ident_str = field.ident.to_string();
+        let ty = field.ty;
+        let lsb = Literal::u32_unsuffixed(field.lsb);
+        let msb = Literal::u32_unsuffixed(field.msb);
+        let docs = &field.docs;
+        let valid = field
+            .valid
+            .iter()
+            .map(|&(min, max)| quote!(::bit_register::ValueRange { min: #min, max: #max }));
+        let (kind, scale) = match &field.scale {
+            Some(scale) => {
+                let scale = scale.info();
+                (
+                    quote!(::bit_register::FieldKind::Unsigned),
+                    quote!(::core::option::Option::Some(#scale)),
+                )
+            }
+            None if !field.table.is_empty() => (
+                quote!(::bit_register::FieldKind::Unsigned),
+                quote!(::core::option::Option::None),
+            ),
+            None => (
+                quote!({
+                    #[allow(unused_imports)]
+                    use ::bit_register::__FieldKindFallback as _;
+                    ::bit_register::__FieldKindOf::<#ty>::KIND
+                }),
+                quote!(::core::option::Option::None),
+            ),
+        };
+        let none = match field.none {
+            Some(none) => quote!(::core::option::Option::Some(#none)),
+            None => quote!(::core::option::Option::None),
+        };
+        let table = field
+            .table
+            .iter()
+            .map(|&value| Literal::i128_unsuffixed(value));
+        quote! {
+            ::bit_register::FieldInfo {
+                name: #ident_str,
+                lsb: #lsb,
+                msb: #msb,
+                kind: #kind,
+                valid: &[#(#valid),*],
+                scale: #scale,
+                none: #none,
+                table: &[#(#table),*],
+                doc: &[#(#docs),*],
+            }
+        }
+    });
+    // Fixed-point fields must be exactly as wide as their type. Fields with generic types cannot
+    // be checked in a `const` item, and are only described by the layout.
+    let width_checks = fields
+        .iter()
+        .filter(|field| field.scale.is_none() && field.table.is_empty())
+        .filter(|field| !mentions(field.ty, &type_params))
+        .map(|field| {
+            let ty = field.ty;
+            let width = Literal::u32_unsuffixed(field.width());
+            let message = format!(
+                "{} must be exactly as wide as its fixed-point type",
+                field.ident
+            );
+            quote_spanned! {ty.span()=>
+                const _: () = ::core::assert!(
+                    {
+                        #[allow(unused_imports)]
+                        use ::bit_register::__FieldKindFallback as _;
+                        ::bit_register::__FieldKindOf::<#ty>::KIND
+                    }
+                    .fits_width(#width),
+                    #message
+                );
+            }
+        });
+    let docs = docs(&input.attrs);
+
+    Ok(quote! {
+        #(#width_checks)*
+
+        impl #impl_generics ::bit_register::NumBytes for #name #ty_generics #where_clause {
+            const NUM_BYTES: usize = <#repr_ident as ::bit_register::NumBytes>::NUM_BYTES;
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<#repr_ident> for #name #ty_generics #where_clause {
+            type Error = &'static str;
+
+            fn try_from(value: #repr_ident) -> ::core::result::Result<Self, Self::Error> {
+                // Fields are built in place, so their names cannot shadow `value`
+                let register = Self { #(#extracts),* };
+                #validate_register
+                ::core::result::Result::Ok(register)
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryInto<#repr_ident> for #name #ty_generics #where_clause {
+            type Error = &'static str;
+
+            fn try_into(self) -> ::core::result::Result<#repr_ident, Self::Error> {
+                #validate_self
+                let mut value: #repr_ident = 0;
+                #(#packs)*
+                ::core::result::Result::Ok(value)
+            }
+        }
+
+        impl #impl_generics ::bit_register::BitRegister<#repr_ident> for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::bit_register::RegisterLayout for #name #ty_generics #where_clause {
+            const LAYOUT: ::bit_register::RegisterInfo = ::bit_register::RegisterInfo {
+                name: #name_str,
+                bits: #bits,
+                fields: &[#(#field_infos),*],
+                doc: &[#(#docs),*],
+            };
+        }
+    })
+}
+
+/// Read the bits of a field from its `#[bits(...)]` attribute
+fn field_bits(
+    ident: &Ident,
+    attrs: &[Attribute],
+    repr: &Repr,
+    previous: &[BitField],
+) -> syn::Result<(u32, u32)> {
+    let mut bits_attrs = attrs.iter().filter(|attr| attr.path().is_ident("bits"));
+    let attr = bits_attrs.next().ok_or_else(|| {
+        Error::new(
+            ident.span(),
+            format!(
+                "missing #[bits(...)] attribute on field `{}`, such as #[bits(3)] or #[bits(1..=3)]",
+                ident
+            ),
+        )
+    })?;
+    if let Some(duplicate) = bits_attrs.next() {
+        return Err(Error::new_spanned(
+            duplicate,
+            "duplicate #[bits(...)] attribute",
+        ));
+    }
+
+    let expr: Expr = attr.parse_args()?;
+    let (lsb, msb) = match &expr {
+        Expr::Lit(_) => {
+            let bit = bit_index(&expr)?;
+            (bit, bit)
+        }
+        Expr::Range(range) => {
+            let (Some(start), Some(end)) = (&range.start, &range.end) else {
+                return Err(Error::new_spanned(
+                    &expr,
+                    "bit ranges need a start and an end, such as 1..=3",
+                ));
+            };
+            let lsb = bit_index(start)?;
+            let end = bit_index(end)?;
+            let msb = match range.limits {
+                RangeLimits::Closed(_) => Some(end),
+                RangeLimits::HalfOpen(_) => end.checked_sub(1),
+            };
+            match msb {
+                Some(msb) if msb >= lsb => (lsb, msb),
+                _ => return Err(Error::new_spanned(&expr, "bit range is empty")),
+            }
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                &expr,
+                "expected a bit such as 3, or a range such as 1..=3 or 1..4",
+            ))
+        }
+    };
+
+    if msb >= repr.bits {
+        return Err(Error::new_spanned(
+            &expr,
+            format!("bits {}..={} do not fit in {}", lsb, msb, repr.ident),
+        ));
+    }
+    if let Some(other) = previous
+        .iter()
+        .find(|other| other.lsb <= msb && lsb <= other.msb)
+    {
+        return Err(Error::new_spanned(
+            &expr,
+            format!(
+                "bits {}..={} overlap field `{}` at bits {}..={}",
+                lsb, msb, other.ident, other.lsb, other.msb
+            ),
+        ));
+    }
+    Ok((lsb, msb))
+}
+
+/// Read the valid raw values of a field from its `#[valid(...)]` attribute, as inclusive ranges
+fn field_valid(attrs: &[Attribute], width: u32) -> syn::Result<Vec<(u64, u64)>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("valid")) else {
+        return Ok(Vec::new());
+    };
+    let pattern = attr.parse_args_with(Pat::parse_multi)?;
+    let cases: Vec<&Pat> = match &pattern {
+        Pat::Or(or) => or.cases.iter().collect(),
+        pattern => vec![pattern],
+    };
+
+    let max_value = if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    };
+    let mut valid = Vec::new();
+    for case in cases {
+        let (min, max) = match case {
+            Pat::Lit(PatLit {
+                lit: Lit::Int(int), ..
+            }) => {
+                let value = int.base10_parse()?;
+                (value, value)
+            }
+            Pat::Range(PatRange {
+                start: Some(start),
+                end: Some(end),
+                limits,
+                ..
+            }) => {
+                let min = value(start)?;
+                let end = value(end)?;
+                let max = match limits {
+                    RangeLimits::Closed(_) => Some(end),
+                    RangeLimits::HalfOpen(_) => end.checked_sub(1),
+                };
+                match max {
+                    Some(max) if max >= min => (min, max),
+                    _ => return Err(Error::new_spanned(case, "range of valid values is empty")),
+                }
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    case,
+                    "expected values such as 3, 1..=10 or 1..=4 | 8",
+                ))
+            }
+        };
+        if max > max_value {
+            return Err(Error::new_spanned(
+                case,
+                format!("valid values do not fit in {} bits", width),
+            ));
+        }
+        valid.push((min, max));
+    }
+    Ok(valid)
+}
+
+/// Read the scale of a field from its `#[scale(16, offset = 2000, unit = "mV")]` attribute
+fn field_scale(attrs: &[Attribute]) -> syn::Result<Option<Scale>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("scale")) else {
+        return Ok(None);
+    };
+    let args = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+    let mut args = args.iter();
+    let Some(scale_expr) = args.next() else {
+        return Err(Error::new_spanned(
+            attr,
+            "expected a scale such as #[scale(16, offset = 2000, unit = \"mV\")]",
+        ));
+    };
+    let scale = signed_value(scale_expr)?;
+    if scale == 0 {
+        return Err(Error::new_spanned(
+            scale_expr,
+            "the scale of a field must not be zero",
+        ));
+    }
+
+    let mut offset = 0;
+    let mut unit = String::new();
+    for arg in args {
+        let Expr::Assign(assign) = arg else {
+            return Err(Error::new_spanned(
+                arg,
+                "expected `offset = ...` or `unit = \"...\"`",
+            ));
+        };
+        match &*assign.left {
+            Expr::Path(path) if path.path.is_ident("offset") => {
+                offset = signed_value(&assign.right)?
+            }
+            Expr::Path(path) if path.path.is_ident("unit") => match &*assign.right {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(text),
+                    ..
+                }) => unit = text.value(),
+                right => return Err(Error::new_spanned(right, "expected a string")),
+            },
+            _ => {
+                return Err(Error::new_spanned(
+                    arg,
+                    "expected `offset = ...` or `unit = \"...\"`",
+                ))
+            }
+        }
+    }
+    Ok(Some(Scale {
+        scale,
+        offset,
+        unit,
+    }))
+}
+
+/// Read the raw value of `None` from the `#[none(0xff)]` attribute of an `Option<T>` field,
+/// returning the type of its `Some` values along with it
+fn field_none<'a>(
+    ty: &'a Type,
+    attrs: &[Attribute],
+    width: u32,
+) -> syn::Result<(&'a Type, Option<u64>)> {
+    let attr = attrs.iter().find(|attr| attr.path().is_ident("none"));
+    let (inner, attr) = match (option_inner(ty), attr) {
+        (None, None) => return Ok((ty, None)),
+        (Some(inner), Some(attr)) => (inner, attr),
+        (Some(_), None) => {
+            return Err(Error::new_spanned(
+                ty,
+                "`Option` fields need a none value, such as #[none(0xff)]",
+            ))
+        }
+        (None, Some(attr)) => {
+            return Err(Error::new_spanned(
+                attr,
+                "only `Option` fields can have a none value",
+            ))
+        }
+    };
+    let value: LitInt = attr.parse_args()?;
+    let none: u64 = value.base10_parse()?;
+    if width < 64 && none >> width != 0 {
+        return Err(Error::new_spanned(
+            &value,
+            "the none value of a field must fit in its bits",
+        ));
+    }
+    Ok((inner, Some(none)))
+}
+
+/// Read the values of a field's raw bits from its `#[table(1, 4, 16, 64)]` attribute
+fn field_table(attrs: &[Attribute], width: u32) -> syn::Result<Vec<i128>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("table")) else {
+        return Ok(Vec::new());
+    };
+    let args = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+    if args.is_empty() {
+        return Err(Error::new_spanned(
+            attr,
+            "expected values such as #[table(1, 4, 16, 64)]",
+        ));
+    }
+    if width < 64 && args.len() as u64 > 1 << width {
+        return Err(Error::new_spanned(
+            attr,
+            "the lookup table of a field must fit in its bits",
+        ));
+    }
+    args.iter().map(signed_value).collect()
+}
+
+/// The `T` of an `Option<T>` type
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if path.qself.is_some() || segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first() {
+        Some(GenericArgument::Type(inner)) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+fn signed_value<T: FromStr>(expr: &Expr) -> syn::Result<T>
+where
+    T::Err: Display,
+{
+    match expr {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr: inner,
+            ..
+        }) => match &**inner {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(int), ..
+            }) => format!("-{}", int.base10_digits()).parse().map_err(|_| {
+                Error::new_spanned(expr, format!("number does not fit in {}", type_name::<T>()))
+            }),
+            _ => Err(Error::new_spanned(expr, "expected a number")),
+        },
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse(),
+        _ => Err(Error::new_spanned(expr, "expected a number")),
+    }
+}
+
+fn value(expr: &Expr) -> syn::Result<u64> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse(),
+        _ => Err(Error::new_spanned(expr, "expected a number")),
+    }
+}
+
+fn bit_index(expr: &Expr) -> syn::Result<u32> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse(),
+        _ => Err(Error::new_spanned(expr, "expected a bit number")),
+    }
+}
+
+fn expand_enum(input: &DeriveInput, data: &DataEnum, repr: &Repr) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "BitRegister cannot be derived for generic enums",
+        ));
+    }
+
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "BitRegister enums cannot have fields",
+            ));
+        }
+        let Some((_, value)) = &variant.discriminant else {
+            return Err(Error::new(
+                variant.ident.span(),
+                format!(
+                    "variant `{}` needs an explicit value, such as `{} = 0`",
+                    variant.ident, variant.ident
+                ),
+            ));
+        };
+        variants.push((&variant.ident, value, docs(&variant.attrs)));
+    }
+
+    let name = &input.ident;
+    let repr_ident = &repr.ident;
+    let invalid = format!("Invalid value for enum {}", name);
+
+    // Values are typed as the underlying type, so values which do not fit are a compile error
+    let encodes = variants.iter().map(|(variant, value, _)| {
+        quote_spanned! {value.span()=>
+            Self::#variant => #value,
+        }
+    });
+    let decodes = variants.iter().map(|(variant, value, _)| {
+        quote! {
+            if value == #value {
+                return ::core::result::Result::Ok(Self::#variant);
+            }
+        }
+    });
+    let variant_infos = variants.iter().map(|(variant, value, docs)| {
+        let variant_str = variant.to_string();
+        quote! {
+            ::bit_register::VariantInfo {
+                name: #variant_str,
+                value: (#value) as u64,
+                doc: &[#(#docs),*],
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl ::bit_register::NumBytes for #name {
+            const NUM_BYTES: usize = <#repr_ident as ::bit_register::NumBytes>::NUM_BYTES;
+        }
+
+        impl<T: Copy + ::core::convert::TryFrom<#repr_ident>> ::bit_register::TryIntoBits<T> for #name {
+            fn try_into_bits(self) -> ::core::result::Result<T, &'static str> {
+                let value: #repr_ident = match self {
+                    #(#encodes)*
+                };
+                ::bit_register::TryIntoBits::try_into_bits(value)
+            }
+        }
+
+        impl<T: Copy> ::bit_register::TryFromBits<T> for #name
+        where
+            #repr_ident: ::core::convert::TryFrom<T>,
+        {
+            fn try_from_bits(bits: T) -> ::core::result::Result<Self, &'static str> {
+                let value: #repr_ident = ::bit_register::TryFromBits::try_from_bits(bits)?;
+                #(#decodes)*
+                ::core::result::Result::Err(#invalid)
+            }
+        }
+
+        impl ::bit_register::FieldType for #name {
+            const KIND: ::bit_register::FieldKind = ::bit_register::FieldKind::Enum(&[
+                #(#variant_infos),*
+            ]);
+        }
+    })
+}
+
+/// The values of `#[doc = ...]` attributes, which doc comments become
+fn docs(attrs: &[Attribute]) -> Vec<&Expr> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => Some(&meta.value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether a type refers to any of the given type parameters
+fn mentions(ty: &Type, params: &[&Ident]) -> bool {
+    fn walk(tokens: TokenStream2, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.iter().any(|param| **param == ident),
+            TokenTree::Group(group) => walk(group.stream(), params),
+            _ => false,
+        })
+    }
+    !params.is_empty() && walk(quote!(#ty), params)
+}
+
+#[cfg(test)]
+mod test {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn error(input: DeriveInput) -> String {
+        expand(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_struct_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                struct Status {
+                    #[bits(0)]
+                    ready: bool,
+                }
+            }),
+            "missing #[bit_register(...)] attribute naming the underlying type, such as #[bit_register(u16)]"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(i16)]
+                struct Status {}
+            }),
+            "the underlying type must be u8, u16, u32 or u64"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16, bit_order = msb1)]
+                struct Status {}
+            }),
+            "bit_order must be msb0 or lsb0"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16, bit_order = msb0, bit_order = lsb0)]
+                struct Status {}
+            }),
+            "duplicate bit_order"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16, validate = check_order, validate = check_range)]
+                struct Status {}
+            }),
+            "duplicate validate"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(bit_order = msb0)]
+                struct Status {}
+            }),
+            "missing the underlying type, such as #[bit_register(u16)]"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status(bool);
+            }),
+            "BitRegister can only be derived for structs with named fields"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status {
+                    ready: bool,
+                }
+            }),
+            "missing #[bits(...)] attribute on field `ready`, such as #[bits(3)] or #[bits(1..=3)]"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status {
+                    #[bits(12..=16)]
+                    count: u8,
+                }
+            }),
+            "bits 12..=16 do not fit in u16"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status {
+                    #[bits(0..4)]
+                    count: u8,
+                    #[bits(3)]
+                    ready: bool,
+                }
+            }),
+            "bits 3..=3 overlap field `count` at bits 0..=3"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status {
+                    #[bits(4..4)]
+                    count: u8,
+                }
+            }),
+            "bit range is empty"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u16)]
+                struct Status {
+                    #[bits(..4)]
+                    count: u8,
+                }
+            }),
+            "bit ranges need a start and an end, such as 1..=3"
+        );
+    }
+
+    #[test]
+    fn test_valid_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Clock {
+                    #[bits(0..=3)]
+                    #[valid(1..=16)]
+                    divider: u8,
+                }
+            }),
+            "valid values do not fit in 4 bits"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Clock {
+                    #[bits(0..=3)]
+                    #[valid(4..4)]
+                    divider: u8,
+                }
+            }),
+            "range of valid values is empty"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Clock {
+                    #[bits(0..=3)]
+                    #[valid(MIN..=4)]
+                    divider: u8,
+                }
+            }),
+            "expected a number"
+        );
+    }
+
+    #[test]
+    fn test_scale_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Charger {
+                    #[bits(0..=7)]
+                    #[scale(0)]
+                    vbat: u16,
+                }
+            }),
+            "the scale of a field must not be zero"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Charger {
+                    #[bits(0..=7)]
+                    #[scale(16, unit = mV)]
+                    vbat: u16,
+                }
+            }),
+            "expected a string"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Charger {
+                    #[bits(0..=7)]
+                    #[scale(16, step = 2)]
+                    vbat: u16,
+                }
+            }),
+            "expected `offset = ...` or `unit = \"...\"`"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Charger {
+                    #[bits(0..=7)]
+                    #[scale(16, offset = -1.5)]
+                    vbat: u16,
+                }
+            }),
+            "expected a number"
+        );
+    }
+
+    #[test]
+    fn test_none_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Fault {
+                    #[bits(0..=3)]
+                    channel: Option<u8>,
+                }
+            }),
+            "`Option` fields need a none value, such as #[none(0xff)]"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Fault {
+                    #[bits(0..=3)]
+                    #[none(0xf)]
+                    channel: u8,
+                }
+            }),
+            "only `Option` fields can have a none value"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Fault {
+                    #[bits(0..=3)]
+                    #[none(0xff)]
+                    channel: Option<u8>,
+                }
+            }),
+            "the none value of a field must fit in its bits"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Fault {
+                    #[bits(0..=3)]
+                    #[none(0xf)]
+                    #[scale(10)]
+                    level: Option<u16>,
+                }
+            }),
+            "`Option` fields cannot be scaled"
+        );
+    }
+
+    #[test]
+    fn test_table_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Timer {
+                    #[bits(0..=1)]
+                    #[table(1, 4, 16, 64, 256)]
+                    timeout: u16,
+                }
+            }),
+            "the lookup table of a field must fit in its bits"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Timer {
+                    #[bits(0..=1)]
+                    #[table()]
+                    timeout: u16,
+                }
+            }),
+            "expected values such as #[table(1, 4, 16, 64)]"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Timer {
+                    #[bits(0..=1)]
+                    #[table(1, 4)]
+                    #[scale(10)]
+                    timeout: u16,
+                }
+            }),
+            "fields with a lookup table cannot be scaled"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                struct Timer {
+                    #[bits(0..=1)]
+                    #[none(3)]
+                    #[table(1, 4)]
+                    timeout: Option<u16>,
+                }
+            }),
+            "`Option` fields cannot have a lookup table"
+        );
+    }
+
+    #[test]
+    fn test_all_field_errors_reported() {
+        let input: DeriveInput = parse_quote! {
+            #[bit_register(u8)]
+            struct Status {
+                ready: bool,
+                #[bits(9)]
+                count: u8,
+            }
+        };
+        let messages: Vec<String> = expand(&input)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1], "bits 9..=9 do not fit in u8");
+    }
+
+    #[test]
+    fn test_enum_errors() {
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                enum Mode {
+                    Idle = 0,
+                    Active,
+                }
+            }),
+            "variant `Active` needs an explicit value, such as `Active = 0`"
+        );
+        assert_eq!(
+            error(parse_quote! {
+                #[bit_register(u8)]
+                enum Mode {
+                    Idle(u8) = 0,
+                }
+            }),
+            "BitRegister enums cannot have fields"
+        );
+    }
+
+    #[test]
+    fn test_mentions() {
+        let t: Ident = parse_quote!(T);
+        assert!(mentions(&parse_quote!(T), &[&t]));
+        assert!(mentions(&parse_quote!(Wrapper<[T; 2]>), &[&t]));
+        assert!(!mentions(&parse_quote!(Tee), &[&t]));
+        assert!(!mentions(&parse_quote!(u8), &[]));
+    }
+}
@@ -0,0 +1,828 @@
+#![allow(missing_docs)]
+
+//! The derive and `bit_register!` define the same registers, so they must behave identically
+
+use bit_register::{FieldKind, FieldType, RegisterLayout, TryFromBits, TryIntoBits};
+
+/// A field type with only the bit conversion traits, without `FieldType`
+mod custom {
+    use bit_register::{NumBytes, TryFromBits, TryIntoBits};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Celsius(pub u8);
+
+    impl NumBytes for Celsius {
+        const NUM_BYTES: usize = 1;
+    }
+
+    impl TryFromBits<u8> for Celsius {
+        fn try_from_bits(bits: u8) -> Result<Self, &'static str> {
+            Ok(Celsius(bits))
+        }
+    }
+
+    impl TryIntoBits<u8> for Celsius {
+        fn try_into_bits(self) -> Result<u8, &'static str> {
+            Ok(self.0)
+        }
+    }
+
+    /// A `NonZero` field type behind an alias
+    pub type Prescaler = core::num::NonZeroU16;
+}
+
+mod with_macro {
+    use super::custom::{Celsius, Prescaler};
+    use bit_register::bit_register;
+    use core::num::NonZeroU8;
+
+    bit_register! {
+        /// The operating mode
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Mode: u8 {
+            /// Nothing is running
+            Idle = 0,
+            Active = 1,
+            Sleep = 3,
+        }
+    }
+
+    bit_register! {
+        /// The device status
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Status: u16 {
+            /// Set when the device is ready
+            pub ready: bool => [0],
+            pub mode: Mode => [1:2],
+            pub code: u8 => [4:7],
+            pub count: u8 => [8:15],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Timer: u32 {
+            pub enable: bool => [0],
+            pub prescaler: u32 => [1:3],
+            pub reload: u16 => [16:31],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Wide: u64 {
+            pub value: u64 => [0:63],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Clock: u8 {
+            pub divider: u8 => [0:3] in 1..=10,
+            pub source: u8 => [4:5] in 0 | 2,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Charger: u16 {
+            pub vbat: u16 => [0:7] scale 16 offset 2000 unit "mV",
+            pub temperature: i8 => [8:15] scale -1 offset 100,
+        }
+    }
+
+    type Gain = bit_register::Q<2, 6>;
+    type Coefficient = bit_register::UQ<0, 8>;
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Amplifier: u16 {
+            pub gain: Gain => [0:7],
+            pub coefficient: Coefficient => [8:15],
+        }
+    }
+
+    type Digits = bit_register::Bcd<u8>;
+    type Position = bit_register::Gray<u8>;
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Dial: u16 {
+            pub minutes: Digits => [0:6],
+            pub position: Position => [8:11],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Fault: u16 {
+            pub channel: Option<u8> => [0:7] none 0xff,
+            pub code: Option<u8> => [8:11] none 0 in 1..=9,
+            pub mode: Option<Mode> => [12:13] none 2,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Debounce: u16 {
+            pub timeout: u16 => [0:1] table [1, 4, 16, 64],
+            pub prescaler: u32 => [8:10] in 0..=3 table [1, 8, 64, 256, 1024],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Pattern: u8 {
+            pub pattern: u64 => [0:1] table [0, 0x8000_0000_0000_0000, 0xffff_ffff_ffff_ffff],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Divider: u16 {
+            pub divider: NonZeroU8 => [0:7],
+            pub repeat: Option<NonZeroU8> => [8:11] none 0,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Prescaled: u32 {
+            pub divider: core::num::NonZeroU16 => [0:9],
+            pub prescaler: Option<Prescaler> => [16:19] none 0,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[bit_register(validate = checks::window)]
+        pub struct Window: u16 {
+            pub low: u8 => [0:7],
+            pub high: u8 => [8:15],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Thermometer: u8 {
+            pub temperature: Celsius => [0:6],
+            pub alert: bool => [7],
+        }
+    }
+
+    // Not a `const fn`, as `Window` has no `const` option
+    mod checks {
+        pub fn window(window: &super::Window) -> Result<(), &'static str> {
+            if window.low > window.high {
+                Err("low must not exceed high")
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    bit_register! {
+        #[bit_register(bit_order = msb0)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Header: u16 {
+            pub version: u8 => [0:3],
+            pub urgent: bool => [4],
+            pub length: u8 => [8:15],
+        }
+    }
+}
+
+mod with_derive {
+    use super::custom::{Celsius, Prescaler};
+    use bit_register::BitRegister;
+    use core::num::NonZeroU8;
+
+    /// The operating mode
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u8)]
+    pub enum Mode {
+        /// Nothing is running
+        Idle = 0,
+        Active = 1,
+        Sleep = 3,
+    }
+
+    /// The device status
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Status {
+        /// Set when the device is ready
+        #[bits(0)]
+        pub ready: bool,
+        #[bits(1..=2)]
+        pub mode: Mode,
+        #[bits(4..8)]
+        pub code: u8,
+        #[bits(8..=15)]
+        pub count: u8,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u32)]
+    pub struct Timer {
+        #[bits(0)]
+        pub enable: bool,
+        #[bits(1..=3)]
+        pub prescaler: u32,
+        #[bits(16..=31)]
+        pub reload: u16,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u64)]
+    pub struct Wide {
+        #[bits(0..64)]
+        pub value: u64,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u8)]
+    pub struct Clock {
+        #[bits(0..=3)]
+        #[valid(1..=10)]
+        pub divider: u8,
+        #[bits(4..=5)]
+        #[valid(0 | 2)]
+        pub source: u8,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Charger {
+        #[bits(0..=7)]
+        #[scale(16, offset = 2000, unit = "mV")]
+        pub vbat: u16,
+        #[bits(8..=15)]
+        #[scale(-1, offset = 100)]
+        pub temperature: i8,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Amplifier {
+        #[bits(0..=7)]
+        pub gain: bit_register::Q<2, 6>,
+        #[bits(8..=15)]
+        pub coefficient: bit_register::UQ<0, 8>,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Dial {
+        #[bits(0..=6)]
+        pub minutes: bit_register::Bcd<u8>,
+        #[bits(8..=11)]
+        pub position: bit_register::Gray<u8>,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Fault {
+        #[bits(0..=7)]
+        #[none(0xff)]
+        pub channel: Option<u8>,
+        #[bits(8..=11)]
+        #[none(0)]
+        #[valid(1..=9)]
+        pub code: Option<u8>,
+        #[bits(12..=13)]
+        #[none(2)]
+        pub mode: Option<Mode>,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Debounce {
+        #[bits(0..=1)]
+        #[table(1, 4, 16, 64)]
+        pub timeout: u16,
+        #[bits(8..=10)]
+        #[table(1, 8, 64, 256, 1024)]
+        #[valid(0..=3)]
+        pub prescaler: u32,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u8)]
+    pub struct Pattern {
+        #[bits(0..=1)]
+        #[table(0, 0x8000_0000_0000_0000, 0xffff_ffff_ffff_ffff)]
+        pub pattern: u64,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16)]
+    pub struct Divider {
+        #[bits(0..=7)]
+        pub divider: NonZeroU8,
+        #[bits(8..=11)]
+        #[none(0)]
+        pub repeat: Option<NonZeroU8>,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u32)]
+    pub struct Prescaled {
+        #[bits(0..=9)]
+        pub divider: core::num::NonZeroU16,
+        #[bits(16..=19)]
+        #[none(0)]
+        pub prescaler: Option<Prescaler>,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16, validate = checks::window)]
+    pub struct Window {
+        #[bits(0..=7)]
+        pub low: u8,
+        #[bits(8..=15)]
+        pub high: u8,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u8)]
+    pub struct Thermometer {
+        #[bits(0..=6)]
+        pub temperature: Celsius,
+        #[bits(7)]
+        pub alert: bool,
+    }
+
+    mod checks {
+        pub fn window(window: &super::Window) -> Result<(), &'static str> {
+            if window.low > window.high {
+                Err("low must not exceed high")
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u16, bit_order = msb0)]
+    pub struct Header {
+        #[bits(0..=3)]
+        pub version: u8,
+        #[bits(4)]
+        pub urgent: bool,
+        #[bits(8..16)]
+        pub length: u8,
+    }
+
+    #[derive(BitRegister, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_register(u8)]
+    pub struct Generic<T> {
+        #[bits(0..=1)]
+        pub value: T,
+        #[bits(7)]
+        pub flag: bool,
+    }
+}
+
+const MODES: [(with_macro::Mode, with_derive::Mode); 3] = [
+    (with_macro::Mode::Idle, with_derive::Mode::Idle),
+    (with_macro::Mode::Active, with_derive::Mode::Active),
+    (with_macro::Mode::Sleep, with_derive::Mode::Sleep),
+];
+
+#[test]
+fn test_enum() {
+    for raw in 0..=u8::MAX {
+        let from_macro: Result<with_macro::Mode, _> = TryFromBits::try_from_bits(raw);
+        let from_derive: Result<with_derive::Mode, _> = TryFromBits::try_from_bits(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for (from_macro, from_derive) in MODES {
+        let from_macro: u16 = from_macro.try_into_bits().unwrap();
+        let from_derive: u16 = from_derive.try_into_bits().unwrap();
+        assert_eq!(from_macro, from_derive);
+    }
+    assert_eq!(
+        <with_derive::Mode as TryFromBits<u16>>::try_from_bits(0x100),
+        Err("bit pattern too large for target type u8")
+    );
+    assert_eq!(with_macro::Mode::KIND, with_derive::Mode::KIND);
+    assert!(matches!(with_derive::Mode::KIND, FieldKind::Enum(variants) if variants.len() == 3));
+}
+
+#[test]
+fn test_try_from() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Status::try_from(raw);
+        let from_derive = with_derive::Status::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    assert_eq!(
+        with_derive::Status::try_from(0x0004),
+        Err("Invalid value for enum Mode")
+    );
+}
+
+#[test]
+fn test_try_into() {
+    for (mode_macro, mode_derive) in MODES {
+        for ready in [false, true] {
+            for code in 0..=u8::MAX {
+                for count in [0, 1, 0x80, u8::MAX] {
+                    let from_macro: Result<u16, _> = with_macro::Status {
+                        ready,
+                        mode: mode_macro,
+                        code,
+                        count,
+                    }
+                    .try_into();
+                    let from_derive: Result<u16, _> = with_derive::Status {
+                        ready,
+                        mode: mode_derive,
+                        code,
+                        count,
+                    }
+                    .try_into();
+                    assert_eq!(from_macro, from_derive);
+                }
+            }
+        }
+    }
+
+    let status = with_derive::Status {
+        ready: false,
+        mode: with_derive::Mode::Idle,
+        code: 16,
+        count: 0,
+    };
+    assert_eq!(
+        TryInto::<u16>::try_into(status),
+        Err("code exceeds maximum value for its bit width")
+    );
+}
+
+#[test]
+fn test_wide_fields() {
+    for raw in (0..=u32::MAX).step_by(0x0001_2345) {
+        let from_macro = with_macro::Timer::try_from(raw);
+        let from_derive = with_derive::Timer::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for prescaler in [0, 7, 8, u32::MAX] {
+        let from_macro: Result<u32, _> = with_macro::Timer {
+            enable: true,
+            prescaler,
+            reload: u16::MAX,
+        }
+        .try_into();
+        let from_derive: Result<u32, _> = with_derive::Timer {
+            enable: true,
+            prescaler,
+            reload: u16::MAX,
+        }
+        .try_into();
+        assert_eq!(from_macro, from_derive);
+    }
+
+    for raw in [0, 1, u64::MAX >> 1, u64::MAX] {
+        let from_macro: Result<u64, _> = with_macro::Wide::try_from(raw).unwrap().try_into();
+        let from_derive: Result<u64, _> = with_derive::Wide::try_from(raw).unwrap().try_into();
+        assert_eq!(from_macro, Ok(raw));
+        assert_eq!(from_derive, Ok(raw));
+    }
+}
+
+#[test]
+fn test_valid_values() {
+    for raw in 0..=u8::MAX {
+        let from_macro = with_macro::Clock::try_from(raw);
+        let from_derive = with_derive::Clock::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for divider in 0..=16 {
+        for source in 0..=4 {
+            let from_macro: Result<u8, _> = with_macro::Clock { divider, source }.try_into();
+            let from_derive: Result<u8, _> = with_derive::Clock { divider, source }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(
+        with_derive::Clock::try_from(0x01),
+        Ok(with_derive::Clock {
+            divider: 1,
+            source: 0
+        })
+    );
+    assert_eq!(
+        with_derive::Clock::try_from(0x10),
+        Err("divider is not in 1..=10")
+    );
+    assert_eq!(
+        with_derive::Clock::try_from(0x11),
+        Err("source is not in 0 | 2")
+    );
+    assert_eq!(with_macro::Clock::LAYOUT, with_derive::Clock::LAYOUT);
+}
+
+#[test]
+fn test_scaled_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Charger::try_from(raw);
+        let from_derive = with_derive::Charger::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for vbat in (0..=7000).step_by(7) {
+        for temperature in [i8::MIN, -100, -1, 0, 7, 100, i8::MAX] {
+            let from_macro: Result<u16, _> = with_macro::Charger { vbat, temperature }.try_into();
+            let from_derive: Result<u16, _> = with_derive::Charger { vbat, temperature }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(
+        with_derive::Charger::try_from(0xff04),
+        Err("temperature does not fit in i8")
+    );
+    assert_eq!(
+        TryInto::<u16>::try_into(with_derive::Charger {
+            vbat: 2072,
+            temperature: 100
+        }),
+        Ok(0x0005)
+    );
+    assert_eq!(with_macro::Charger::LAYOUT, with_derive::Charger::LAYOUT);
+}
+
+#[test]
+fn test_fixed_point_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Amplifier::try_from(raw);
+        let from_derive = with_derive::Amplifier::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    let amplifier = with_derive::Amplifier {
+        gain: bit_register::Q::from_f32(-0.5).unwrap(),
+        coefficient: bit_register::UQ::from_f32(0.75).unwrap(),
+    };
+    assert_eq!(TryInto::<u16>::try_into(amplifier), Ok(0xc0e0));
+    assert_eq!(
+        with_macro::Amplifier::LAYOUT,
+        with_derive::Amplifier::LAYOUT
+    );
+}
+
+#[test]
+fn test_bcd_and_gray_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Dial::try_from(raw);
+        let from_derive = with_derive::Dial::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for minutes in 0..=99 {
+        for position in [0, 2, 15, 16] {
+            let minutes = bit_register::Bcd::<u8>::new(minutes).unwrap();
+            let position = bit_register::Gray::<u8>::new(position);
+            let from_macro: Result<u16, _> = with_macro::Dial { minutes, position }.try_into();
+            let from_derive: Result<u16, _> = with_derive::Dial { minutes, position }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(with_macro::Dial::LAYOUT, with_derive::Dial::LAYOUT);
+}
+
+#[test]
+fn test_option_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Fault::try_from(raw);
+        let from_derive = with_derive::Fault::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for channel in [None, Some(0), Some(0xfe), Some(0xff)] {
+        for code in [None, Some(0), Some(3), Some(10), Some(16)] {
+            for (macro_mode, derive_mode) in [
+                (None, None),
+                (
+                    Some(with_macro::Mode::Active),
+                    Some(with_derive::Mode::Active),
+                ),
+                (
+                    Some(with_macro::Mode::Sleep),
+                    Some(with_derive::Mode::Sleep),
+                ),
+            ] {
+                let from_macro: Result<u16, _> = with_macro::Fault {
+                    channel,
+                    code,
+                    mode: macro_mode,
+                }
+                .try_into();
+                let from_derive: Result<u16, _> = with_derive::Fault {
+                    channel,
+                    code,
+                    mode: derive_mode,
+                }
+                .try_into();
+                assert_eq!(from_macro, from_derive);
+            }
+        }
+    }
+    assert_eq!(
+        TryInto::<u16>::try_into(with_derive::Fault {
+            channel: Some(0xff),
+            code: None,
+            mode: None,
+        }),
+        Err("channel is the raw value reserved for None")
+    );
+    assert_eq!(with_macro::Fault::LAYOUT, with_derive::Fault::LAYOUT);
+}
+
+#[test]
+fn test_table_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Debounce::try_from(raw);
+        let from_derive = with_derive::Debounce::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for timeout in [0, 1, 4, 5, 64] {
+        for prescaler in [1, 2, 256, 1024] {
+            let from_macro: Result<u16, _> = with_macro::Debounce { timeout, prescaler }.try_into();
+            let from_derive: Result<u16, _> =
+                with_derive::Debounce { timeout, prescaler }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(
+        TryInto::<u16>::try_into(with_derive::Debounce {
+            timeout: 16,
+            prescaler: 64
+        }),
+        Ok(0x0202)
+    );
+    assert_eq!(
+        with_macro::Debounce::try_from(0x0400u16),
+        Err("prescaler is not in 0..=3")
+    );
+    assert_eq!(with_macro::Debounce::LAYOUT, with_derive::Debounce::LAYOUT);
+
+    for raw in 0..=u8::MAX {
+        let from_macro = with_macro::Pattern::try_from(raw);
+        let from_derive = with_derive::Pattern::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    assert_eq!(
+        TryInto::<u8>::try_into(with_derive::Pattern { pattern: u64::MAX }),
+        Ok(2)
+    );
+    assert_eq!(with_macro::Pattern::LAYOUT, with_derive::Pattern::LAYOUT);
+}
+
+#[test]
+fn test_non_zero_fields() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Divider::try_from(raw);
+        let from_derive = with_derive::Divider::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for divider in [1, 0x80, 0xff] {
+        for repeat in [None, Some(1), Some(15), Some(16)] {
+            let divider = core::num::NonZeroU8::new(divider).unwrap();
+            let repeat = repeat.and_then(core::num::NonZeroU8::new);
+            let from_macro: Result<u16, _> = with_macro::Divider { divider, repeat }.try_into();
+            let from_derive: Result<u16, _> = with_derive::Divider { divider, repeat }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(
+        with_derive::Divider::try_from(0x0100),
+        Err("bit pattern is zero for target type NonZeroU8")
+    );
+    assert_eq!(with_macro::Divider::LAYOUT, with_derive::Divider::LAYOUT);
+
+    // Paths and aliases of `NonZero` types
+    for raw in [
+        0,
+        1,
+        0x3ff,
+        0x400,
+        0x0001_0000,
+        0x0001_0001,
+        0x000f_0200,
+        0x0010_0001,
+    ] {
+        let from_macro = with_macro::Prescaled::try_from(raw);
+        let from_derive = with_derive::Prescaled::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    assert_eq!(
+        with_macro::Prescaled::try_from(0x0001_0000),
+        Err("bit pattern is zero for target type NonZeroU16")
+    );
+    for divider in [1, 0x3ff, 0x400] {
+        for prescaler in [None, Some(1), Some(15), Some(16)] {
+            let divider = core::num::NonZeroU16::new(divider).unwrap();
+            let prescaler = prescaler.and_then(core::num::NonZeroU16::new);
+            let from_macro: Result<u32, _> =
+                with_macro::Prescaled { divider, prescaler }.try_into();
+            let from_derive: Result<u32, _> =
+                with_derive::Prescaled { divider, prescaler }.try_into();
+            assert_eq!(from_macro, from_derive);
+        }
+    }
+    assert_eq!(
+        with_macro::Prescaled::LAYOUT,
+        with_derive::Prescaled::LAYOUT
+    );
+}
+
+#[test]
+fn test_validate() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Window::try_from(raw);
+        let from_derive = with_derive::Window::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    for (low, high) in [(0, 0), (1, 2), (2, 1), (0xff, 0)] {
+        let from_macro: Result<u16, _> = with_macro::Window { low, high }.try_into();
+        let from_derive: Result<u16, _> = with_derive::Window { low, high }.try_into();
+        assert_eq!(from_macro, from_derive);
+    }
+    assert_eq!(
+        with_derive::Window::try_from(0x0102),
+        Err("low must not exceed high")
+    );
+    assert_eq!(with_macro::Window::LAYOUT, with_derive::Window::LAYOUT);
+}
+
+#[test]
+fn test_msb0() {
+    for raw in 0..=u16::MAX {
+        let from_macro = with_macro::Header::try_from(raw);
+        let from_derive = with_derive::Header::try_from(raw);
+        assert_eq!(format!("{:?}", from_macro), format!("{:?}", from_derive));
+    }
+    let header = with_derive::Header {
+        version: 0xa,
+        urgent: true,
+        length: 0x5c,
+    };
+    assert_eq!(TryInto::<u16>::try_into(header), Ok(0xa85c));
+    assert_eq!(with_macro::Header::LAYOUT, with_derive::Header::LAYOUT);
+}
+
+#[test]
+fn test_layout() {
+    assert_eq!(with_macro::Status::LAYOUT, with_derive::Status::LAYOUT);
+    assert_eq!(with_macro::Timer::LAYOUT, with_derive::Timer::LAYOUT);
+    assert_eq!(with_macro::Wide::LAYOUT, with_derive::Wide::LAYOUT);
+    assert_eq!(with_derive::Status::LAYOUT.doc, &[" The device status"]);
+}
+
+#[test]
+fn test_generic() {
+    type Numeric = with_derive::Generic<u8>;
+    type Moded = with_derive::Generic<with_derive::Mode>;
+
+    assert_eq!(
+        Numeric::try_from(0x83),
+        Ok(with_derive::Generic {
+            value: 3,
+            flag: true
+        })
+    );
+    assert_eq!(
+        TryInto::<u8>::try_into(with_derive::Generic {
+            value: 4u8,
+            flag: false
+        }),
+        Err("value exceeds maximum value for its bit width")
+    );
+    assert_eq!(Moded::try_from(0x02), Err("Invalid value for enum Mode"));
+    assert_eq!(
+        Moded::try_from(0x03).map(|generic| generic.value),
+        Ok(with_derive::Mode::Sleep)
+    );
+    assert_eq!(Moded::LAYOUT.fields[0].kind, with_derive::Mode::KIND);
+    assert_eq!(Numeric::LAYOUT.fields[0].kind, FieldKind::Unsigned);
+}
+
+#[test]
+fn test_custom_field_types() {
+    assert_eq!(
+        with_macro::Thermometer::try_from(0x99).map(|register| register.temperature),
+        Ok(custom::Celsius(0x19))
+    );
+    assert_eq!(
+        with_derive::Thermometer::try_from(0x99).map(|register| register.temperature),
+        Ok(custom::Celsius(0x19))
+    );
+
+    // Field types without `FieldType` are described as unsigned
+    assert_eq!(
+        with_macro::Thermometer::LAYOUT,
+        with_derive::Thermometer::LAYOUT
+    );
+    assert_eq!(
+        with_derive::Thermometer::LAYOUT.fields[0].kind,
+        FieldKind::Unsigned
+    );
+}
@@ -0,0 +1,37 @@
+//! Helpers for the defmt implementations generated by [`bit_register!`](crate::bit_register)
+//!
+//! These are not part of the public API and may change at any time. defmt's macros refer to the
+//! `defmt` crate by name, so they are only used here, where it is a direct dependency, and the
+//! generated implementations do not need one.
+
+use defmt::{write, Format, Formatter};
+
+/// Format an enum variant by name
+pub fn variant(f: Formatter<'_>, variant: &str) {
+    write!(f, "{=str}", variant);
+}
+
+/// Format a type by name and underlying value in hex
+pub fn raw<T: Format>(f: Formatter<'_>, name: &str, value: T) {
+    write!(f, "{=str}({:#x})", name, value);
+}
+
+/// Format a type by name and the error which stopped it from being encoded
+pub fn raw_error(f: Formatter<'_>, name: &str, error: &str) {
+    write!(f, "{=str}(<{=str}>)", name, error);
+}
+
+/// Format the start of a register, before its fields
+pub fn register_start(f: Formatter<'_>, name: &str) {
+    write!(f, "{=str} {{ ", name);
+}
+
+/// Format a field of a register, after the separator from the previous field
+pub fn field<T: Format + ?Sized>(f: Formatter<'_>, separator: &str, name: &str, value: &T) {
+    write!(f, "{=str}{=str}: {}", separator, name, value);
+}
+
+/// Format the end of a register, after its fields
+pub fn register_end(f: Formatter<'_>) {
+    write!(f, " }}");
+}
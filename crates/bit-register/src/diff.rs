@@ -0,0 +1,161 @@
+use core::fmt;
+
+use crate::{FieldInfo, FieldValue, RegisterInfo};
+
+/// A single field which differs between two register values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// The field which differs
+    pub field: &'static FieldInfo,
+    /// Value of the field in the original register
+    pub old: FieldValue,
+    /// Value of the field in the other register
+    pub new: FieldValue,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.lsb == self.field.msb {
+            write!(f, "{} [{}]", self.field.name, self.field.lsb)?;
+        } else {
+            write!(
+                f,
+                "{} [{}:{}]",
+                self.field.name, self.field.lsb, self.field.msb
+            )?;
+        }
+        write!(f, ": {} -> {}", self.old, self.new)
+    }
+}
+
+/// Field-level differences between two values of a register with `N` fields
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RegisterDiff<const N: usize> {
+    layout: &'static RegisterInfo,
+    old: [u64; N],
+    new: [u64; N],
+}
+
+impl<const N: usize> RegisterDiff<N> {
+    /// Create a diff from the raw bits of every field, in the order of the layout's fields
+    pub const fn new(layout: &'static RegisterInfo, old: [u64; N], new: [u64; N]) -> Self {
+        Self { layout, old, new }
+    }
+
+    /// Description of the register being compared
+    pub const fn layout(&self) -> &'static RegisterInfo {
+        self.layout
+    }
+
+    /// Whether the two register values are equal
+    pub fn is_empty(&self) -> bool {
+        self.old == self.new
+    }
+
+    /// Iterate over the fields which differ
+    pub fn iter(&self) -> impl Iterator<Item = FieldDiff> + '_ {
+        self.layout
+            .fields
+            .iter()
+            .zip(self.old.iter().zip(self.new.iter()))
+            .filter(|(_, (old, new))| old != new)
+            .map(|(field, (&old, &new))| FieldDiff {
+                field,
+                old: FieldValue { field, raw: old },
+                new: FieldValue { field, raw: new },
+            })
+    }
+}
+
+impl<const N: usize> fmt::Debug for RegisterDiff<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize> fmt::Display for RegisterDiff<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}: no differences", self.layout.name);
+        }
+
+        write!(f, "{}:", self.layout.name)?;
+        for diff in self.iter() {
+            write!(f, "\n  {}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+/// Asserts that two register values are equal, printing only the differing fields on failure
+///
+/// Both values must be of the same type generated by [`bit_register!`](crate::bit_register).
+/// An optional message can be given like with [`assert_eq!`].
+#[macro_export]
+macro_rules! assert_register_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = left.diff(right);
+                if !diff.is_empty() {
+                    panic!("assertion `left == right` failed\n{}", diff);
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = left.diff(right);
+                if !diff.is_empty() {
+                    panic!("assertion `left == right` failed: {}\n{}", format_args!($($arg)+), diff);
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::format;
+    use std::string::ToString;
+
+    use super::*;
+    use crate::fixtures::LAYOUT;
+
+    #[test]
+    fn test_diff() {
+        let diff = RegisterDiff::new(&LAYOUT, [1, 0, 7], [1, 1, 9]);
+        assert!(!diff.is_empty());
+
+        let fields: std::vec::Vec<_> = diff.iter().map(|diff| diff.field.name).collect();
+        assert_eq!(fields, ["mode", "count"]);
+
+        assert_eq!(
+            diff.to_string(),
+            "StatusRegister:\n  mode [4:5]: Idle -> Active\n  count [8:15]: 7 -> 9"
+        );
+        assert_eq!(
+            format!("{:?}", diff),
+            format!(
+                "{:?}",
+                [diff.iter().next().unwrap(), diff.iter().nth(1).unwrap()]
+            )
+        );
+
+        let same = RegisterDiff::new(&LAYOUT, [0, 1, 2], [0, 1, 2]);
+        assert!(same.is_empty());
+        assert_eq!(same.iter().count(), 0);
+        assert_eq!(same.to_string(), "StatusRegister: no differences");
+    }
+
+    #[test]
+    fn test_single_bit_display() {
+        let diff = RegisterDiff::new(&LAYOUT, [0, 0, 0], [1, 0, 0]);
+        assert_eq!(
+            diff.iter().next().unwrap().to_string(),
+            "ready [3]: false -> true"
+        );
+    }
+}
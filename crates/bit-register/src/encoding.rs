@@ -0,0 +1,337 @@
+use core::fmt;
+
+use crate::{FieldKind, FieldType, NumBytes, TryFromBits, TryIntoBits};
+
+/// A decimal number stored as packed BCD, one decimal digit per nibble
+///
+/// Real-time clocks commonly store seconds, minutes and hours this way, so 59 seconds are the
+/// bits `0x59`. A `Bcd<T>` holds as many digits as fit in the bits of `T`, so `Bcd<u8>` holds
+/// 0 to 99 and `Bcd<u16>` holds 0 to 9999. Decoding fails if a nibble is not a decimal digit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bcd<T>(T);
+
+/// An unsigned integer stored as Gray code, where consecutive values differ in a single bit
+///
+/// Rotary and absolute position encoders commonly report Gray code, so a position of 2 is the
+/// bits `0b11`. Any value of `T` can be encoded.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Gray<T>(T);
+
+/// Packed BCD digits of a value, which must have at most 16 digits
+pub(crate) const fn bcd_encode(mut value: u64) -> u64 {
+    let mut bits = 0;
+    let mut shift = 0;
+    while value != 0 {
+        bits |= (value % 10) << shift;
+        value /= 10;
+        shift += 4;
+    }
+    bits
+}
+
+/// Value of packed BCD digits, or `None` if a nibble is not a decimal digit
+pub(crate) const fn bcd_decode(mut bits: u64) -> Option<u64> {
+    let mut value = 0;
+    let mut scale = 1;
+    while bits != 0 {
+        let digit = bits & 0xf;
+        if digit > 9 {
+            return None;
+        }
+        value += digit * scale;
+        bits >>= 4;
+        // At most 16 nibbles, so the scale is at most 10^16
+        scale *= 10;
+    }
+    Some(value)
+}
+
+/// Gray code of a value
+pub(crate) const fn gray_encode(value: u64) -> u64 {
+    value ^ (value >> 1)
+}
+
+/// Value of a Gray code, where each bit is the parity of the code's bits from that bit upwards
+pub(crate) const fn gray_decode(mut bits: u64) -> u64 {
+    let mut shift = 1;
+    while shift < 64 {
+        bits ^= bits >> shift;
+        shift *= 2;
+    }
+    bits
+}
+
+macro_rules! impl_encodings {
+    ($($t:ty),*) => {
+        $(
+            impl Bcd<$t> {
+                /// The largest value, with every digit 9
+                pub const MAX: Self = Self((10u64.pow(2 * core::mem::size_of::<$t>() as u32) - 1) as $t);
+
+                /// A value, or `None` if it has more digits than fit in the bits of the type
+                pub const fn new(value: $t) -> Option<Self> {
+                    if value > Self::MAX.0 {
+                        None
+                    } else {
+                        Some(Self(value))
+                    }
+                }
+
+                /// The decimal value
+                pub const fn get(self) -> $t {
+                    self.0
+                }
+
+                /// The value as packed BCD digits
+                pub const fn encode_const(&self) -> u64 {
+                    bcd_encode(self.0 as u64)
+                }
+
+                /// The value of packed BCD digits, usable in `const` contexts
+                pub const fn try_decode_const(bits: u64) -> Result<Self, &'static str> {
+                    if core::mem::size_of::<$t>() < 8 && bits >> (core::mem::size_of::<$t>() * 8) != 0 {
+                        return Err("bit pattern too large for target type Bcd");
+                    }
+                    match bcd_decode(bits) {
+                        Some(value) => Ok(Self(value as $t)),
+                        None => Err("bit pattern is not valid BCD"),
+                    }
+                }
+            }
+
+            impl Gray<$t> {
+                /// A value
+                pub const fn new(value: $t) -> Self {
+                    Self(value)
+                }
+
+                /// The value
+                pub const fn get(self) -> $t {
+                    self.0
+                }
+
+                /// The value as Gray code
+                pub const fn encode_const(&self) -> u64 {
+                    gray_encode(self.0 as u64)
+                }
+
+                /// The value of a Gray code, usable in `const` contexts
+                pub const fn try_decode_const(bits: u64) -> Result<Self, &'static str> {
+                    if core::mem::size_of::<$t>() < 8 && bits >> (core::mem::size_of::<$t>() * 8) != 0 {
+                        return Err("bit pattern too large for target type Gray");
+                    }
+                    Ok(Self(gray_decode(bits) as $t))
+                }
+            }
+
+            impl_encodings!(@bits Bcd, $t);
+            impl_encodings!(@bits Gray, $t);
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for Bcd<$t> {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = <$t as serde::Deserialize>::deserialize(deserializer)?;
+                    Self::new(value)
+                        .ok_or_else(|| serde::de::Error::custom("value out of range for Bcd"))
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for Gray<$t> {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <$t as serde::Deserialize>::deserialize(deserializer).map(Self)
+                }
+            }
+        )*
+    };
+
+    // Conversions shared by both encodings, which go through `encode_const` and
+    // `try_decode_const` so the macro's const and trait based paths agree
+    (@bits $name:ident, $t:ty) => {
+        impl NumBytes for $name<$t> {
+            const NUM_BYTES: usize = core::mem::size_of::<$t>();
+        }
+
+        impl<T: TryFrom<u64>> TryIntoBits<T> for $name<$t> {
+            fn try_into_bits(self) -> Result<T, &'static str> {
+                T::try_from(self.encode_const())
+                    .map_err(|_| concat!(stringify!($name), " value too large for target type"))
+            }
+        }
+
+        impl<T> TryFromBits<T> for $name<$t>
+        where
+            u64: TryFrom<T>,
+        {
+            fn try_from_bits(bits: T) -> Result<Self, &'static str> {
+                let bits = u64::try_from(bits)
+                    .map_err(|_| concat!("bit pattern too large for target type ", stringify!($name)))?;
+                Self::try_decode_const(bits)
+            }
+        }
+
+        impl FieldType for $name<$t> {
+            const KIND: FieldKind = FieldKind::$name;
+        }
+    };
+}
+
+impl_encodings!(u8, u16, u32, u64);
+
+macro_rules! impl_formatting {
+    ($($name:ident),*) => {
+        $(
+            impl<T: fmt::Display> fmt::Display for $name<T> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.0.fmt(f)
+                }
+            }
+
+            impl<T: fmt::Debug> fmt::Debug for $name<T> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.0.fmt(f)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<T: serde::Serialize> serde::Serialize for $name<T> {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.0.serialize(serializer)
+                }
+            }
+
+            #[cfg(feature = "defmt")]
+            impl<T: defmt::Format> defmt::Format for $name<T> {
+                fn format(&self, f: defmt::Formatter) {
+                    self.0.format(f)
+                }
+            }
+
+            #[cfg(feature = "ufmt")]
+            impl<T: ufmt::uDebug> ufmt::uDebug for $name<T> {
+                fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ufmt::uWrite + ?Sized,
+                {
+                    self.0.fmt(f)
+                }
+            }
+
+            #[cfg(feature = "ufmt")]
+            impl<T: ufmt::uDisplay> ufmt::uDisplay for $name<T> {
+                fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ufmt::uWrite + ?Sized,
+                {
+                    self.0.fmt(f)
+                }
+            }
+        )*
+    };
+}
+
+impl_formatting!(Bcd, Gray);
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::format;
+    use std::string::ToString;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_bcd() {
+        let seconds = Bcd::<u8>::new(59).unwrap();
+        assert_eq!(seconds.get(), 59);
+        assert_eq!(seconds.encode_const(), 0x59);
+        assert_eq!(TryIntoBits::<u8>::try_into_bits(seconds), Ok(0x59));
+        assert_eq!(Bcd::<u8>::try_decode_const(0x59), Ok(seconds));
+        assert_eq!(Bcd::<u8>::new(100), None);
+        assert_eq!(Bcd::<u8>::MAX.get(), 99);
+        assert_eq!(Bcd::<u16>::MAX.encode_const(), 0x9999);
+        assert_eq!(Bcd::<u64>::MAX.encode_const(), 0x9999_9999_9999_9999);
+        assert_eq!(
+            Bcd::<u64>::try_decode_const(0x9999_9999_9999_9999),
+            Ok(Bcd::<u64>::MAX)
+        );
+
+        assert_eq!(
+            Bcd::<u8>::try_decode_const(0x5a),
+            Err("bit pattern is not valid BCD")
+        );
+        assert_eq!(
+            Bcd::<u8>::try_decode_const(0xa0),
+            Err("bit pattern is not valid BCD")
+        );
+        assert_eq!(
+            <Bcd<u8> as TryFromBits<u16>>::try_from_bits(0x100),
+            Err("bit pattern too large for target type Bcd")
+        );
+        assert_eq!(
+            TryIntoBits::<u8>::try_into_bits(Bcd::<u16>::new(100).unwrap()),
+            Err("Bcd value too large for target type")
+        );
+        assert_eq!(format!("{:?}", seconds), "59");
+    }
+
+    #[test]
+    fn test_gray() {
+        let codes = [0b000, 0b001, 0b011, 0b010, 0b110, 0b111, 0b101, 0b100];
+        for (value, code) in codes.into_iter().enumerate() {
+            let position = Gray::<u8>::new(value as u8);
+            assert_eq!(position.encode_const(), code);
+            assert_eq!(Gray::<u8>::try_decode_const(code), Ok(position));
+        }
+        assert_eq!(Gray::<u64>::new(u64::MAX).encode_const(), 1 << 63);
+        assert_eq!(
+            Gray::<u64>::try_decode_const(1 << 63).unwrap().get(),
+            u64::MAX
+        );
+        assert_eq!(
+            Gray::<u8>::try_decode_const(0x100),
+            Err("bit pattern too large for target type Gray")
+        );
+        assert_eq!(Gray::<u16>::new(300).to_string(), "300");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let hours = Bcd::<u8>::new(23).unwrap();
+        assert_eq!(serde_json::to_string(&hours).unwrap(), "23");
+        assert_eq!(serde_json::from_str::<Bcd<u8>>("23").unwrap(), hours);
+        assert_eq!(
+            serde_json::from_str::<Bcd<u8>>("123")
+                .unwrap_err()
+                .to_string(),
+            "value out of range for Bcd"
+        );
+        assert_eq!(
+            serde_json::from_str::<Gray<u8>>("5").unwrap(),
+            Gray::<u8>::new(5)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bcd_round_trip(value in 0..=9999_9999u32) {
+            let bcd = Bcd::<u32>::new(value).unwrap();
+            prop_assert_eq!(Bcd::<u32>::try_decode_const(bcd.encode_const()), Ok(bcd));
+        }
+
+        #[test]
+        fn prop_gray_round_trip(value: u64) {
+            let gray = Gray::<u64>::new(value);
+            prop_assert_eq!(Gray::<u64>::try_decode_const(gray.encode_const()), Ok(gray));
+        }
+
+        #[test]
+        fn prop_gray_adjacent_values_differ_in_one_bit(value in 0..u32::MAX) {
+            let code = Gray::<u32>::new(value).encode_const();
+            let next = Gray::<u32>::new(value + 1).encode_const();
+            prop_assert_eq!((code ^ next).count_ones(), 1);
+        }
+    }
+}
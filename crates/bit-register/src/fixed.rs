@@ -0,0 +1,401 @@
+use core::fmt;
+
+use crate::{FieldKind, FieldType, NumBytes, TryFromBits, TryIntoBits};
+
+macro_rules! impl_fixed {
+    ($(#[$attr:meta])* $name:ident, $raw:ty, $signed:literal) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name<const INT: u32, const FRAC: u32>($raw);
+
+        impl<const INT: u32, const FRAC: u32> $name<INT, FRAC> {
+            /// Number of bits in the value
+            pub const BITS: u32 = {
+                assert!(
+                    INT + FRAC >= 1 && INT + FRAC <= 64,
+                    "fixed-point values must have 1 to 64 bits"
+                );
+                assert!(!$signed || INT >= 1, "signed fixed-point values need a sign bit");
+                INT + FRAC
+            };
+
+            const MIN_RAW: i128 = if $signed { -(1 << (Self::BITS - 1)) } else { 0 };
+            const MAX_RAW: i128 = if $signed {
+                (1 << (Self::BITS - 1)) - 1
+            } else {
+                (1 << Self::BITS) - 1
+            };
+
+            /// The smallest value
+            pub const MIN: Self = Self(Self::MIN_RAW as $raw);
+            /// The largest value
+            pub const MAX: Self = Self(Self::MAX_RAW as $raw);
+            /// Zero
+            pub const ZERO: Self = Self(0);
+
+            /// The value `raw / 2^FRAC`, or `None` if it is out of range
+            pub const fn from_raw(raw: $raw) -> Option<Self> {
+                if (raw as i128) < Self::MIN_RAW || (raw as i128) > Self::MAX_RAW {
+                    None
+                } else {
+                    Some(Self(raw))
+                }
+            }
+
+            /// The value `raw / 2^FRAC`, clamped to the range of the type
+            pub const fn saturating_from_raw(raw: $raw) -> Self {
+                Self::saturating_from_i128(raw as i128)
+            }
+
+            const fn saturating_from_i128(raw: i128) -> Self {
+                if raw < Self::MIN_RAW {
+                    Self::MIN
+                } else if raw > Self::MAX_RAW {
+                    Self::MAX
+                } else {
+                    Self(raw as $raw)
+                }
+            }
+
+            /// The value in units of `2^-FRAC`
+            pub const fn raw(self) -> $raw {
+                self.0
+            }
+
+            /// The nearest value to a float, or `None` if it is out of range or not a number
+            pub fn from_f32(value: f32) -> Option<Self> {
+                Self::from_f64(value.into())
+            }
+
+            /// The nearest value to a float, clamped to the range of the type
+            ///
+            /// Not a number becomes zero.
+            pub fn saturating_from_f32(value: f32) -> Self {
+                Self::saturating_from_f64(value.into())
+            }
+
+            /// The value as a float, which may round values with more than 24 significant bits
+            pub fn to_f32(self) -> f32 {
+                self.to_f64() as f32
+            }
+
+            /// The nearest value to a float, or `None` if it is out of range or not a number
+            pub fn from_f64(value: f64) -> Option<Self> {
+                let raw = Self::round(value)?;
+                (Self::MIN_RAW..=Self::MAX_RAW)
+                    .contains(&raw)
+                    .then_some(Self(raw as $raw))
+            }
+
+            /// The nearest value to a float, clamped to the range of the type
+            ///
+            /// Not a number becomes zero.
+            pub fn saturating_from_f64(value: f64) -> Self {
+                Self::round(value).map_or(Self::ZERO, Self::saturating_from_i128)
+            }
+
+            /// The value as a float, which may round values with more than 53 significant bits
+            pub fn to_f64(self) -> f64 {
+                self.0 as f64 / Self::one()
+            }
+
+            /// The raw value nearest to a float, rounding halfway values away from zero. Casts
+            /// saturate, so values far out of range stay out of range.
+            fn round(value: f64) -> Option<i128> {
+                if value.is_nan() {
+                    return None;
+                }
+                let scaled = value * Self::one();
+                let rounded = if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 };
+                Some(rounded as i128)
+            }
+
+            /// `2^FRAC` as a float
+            fn one() -> f64 {
+                (1u128 << FRAC) as f64
+            }
+
+            /// The value as raw bits, the two's complement for negative values
+            pub const fn encode_const(&self) -> u64 {
+                let bits = self.0 as u64;
+                if Self::BITS >= 64 {
+                    bits
+                } else {
+                    bits & ((1 << Self::BITS) - 1)
+                }
+            }
+
+            /// The value of raw bits, usable in `const` contexts
+            pub const fn try_decode_const(bits: u64) -> Result<Self, &'static str> {
+                if Self::BITS < 64 && bits >> Self::BITS != 0 {
+                    return Err(concat!("bit pattern too large for target type ", stringify!($name)));
+                }
+                // Signed values are sign extended from their top bit
+                let shift = 64 - Self::BITS;
+                Ok(Self(if $signed {
+                    (((bits << shift) as i64) >> shift) as $raw
+                } else {
+                    bits as $raw
+                }))
+            }
+        }
+
+        impl<const INT: u32, const FRAC: u32> NumBytes for $name<INT, FRAC> {
+            const NUM_BYTES: usize = Self::BITS.div_ceil(8) as usize;
+        }
+
+        impl<T: TryFrom<u64>, const INT: u32, const FRAC: u32> TryIntoBits<T> for $name<INT, FRAC> {
+            fn try_into_bits(self) -> Result<T, &'static str> {
+                T::try_from(self.encode_const())
+                    .map_err(|_| concat!(stringify!($name), " value too large for target type"))
+            }
+        }
+
+        impl<T, const INT: u32, const FRAC: u32> TryFromBits<T> for $name<INT, FRAC>
+        where
+            u64: TryFrom<T>,
+        {
+            fn try_from_bits(bits: T) -> Result<Self, &'static str> {
+                let bits = u64::try_from(bits)
+                    .map_err(|_| concat!("bit pattern too large for target type ", stringify!($name)))?;
+                Self::try_decode_const(bits)
+            }
+        }
+
+        impl<const INT: u32, const FRAC: u32> FieldType for $name<INT, FRAC> {
+            const KIND: FieldKind = FieldKind::Fixed {
+                signed: $signed,
+                frac: FRAC,
+                bits: Self::BITS,
+            };
+        }
+
+        impl<const INT: u32, const FRAC: u32> fmt::Display for $name<INT, FRAC> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(Decimal::new(self.0 as i128, FRAC).as_str())
+            }
+        }
+
+        impl<const INT: u32, const FRAC: u32> fmt::Debug for $name<INT, FRAC> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<const INT: u32, const FRAC: u32> serde::Serialize for $name<INT, FRAC> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_f64(self.to_f64())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, const INT: u32, const FRAC: u32> serde::Deserialize<'de> for $name<INT, FRAC> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <f64 as serde::Deserialize>::deserialize(deserializer)?;
+                Self::from_f64(value).ok_or_else(|| {
+                    serde::de::Error::custom(concat!("value out of range for ", stringify!($name)))
+                })
+            }
+        }
+
+        #[cfg(feature = "defmt")]
+        impl<const INT: u32, const FRAC: u32> defmt::Format for $name<INT, FRAC> {
+            fn format(&self, f: defmt::Formatter) {
+                defmt::write!(f, "{=str}", Decimal::new(self.0 as i128, FRAC).as_str())
+            }
+        }
+
+        #[cfg(feature = "ufmt")]
+        impl<const INT: u32, const FRAC: u32> ufmt::uDebug for $name<INT, FRAC> {
+            fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ufmt::uWrite + ?Sized,
+            {
+                ufmt::uDisplay::fmt(self, f)
+            }
+        }
+
+        #[cfg(feature = "ufmt")]
+        impl<const INT: u32, const FRAC: u32> ufmt::uDisplay for $name<INT, FRAC> {
+            fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ufmt::uWrite + ?Sized,
+            {
+                f.write_str(Decimal::new(self.0 as i128, FRAC).as_str())
+            }
+        }
+    };
+}
+
+impl_fixed!(
+    /// A signed fixed-point value in Q format, with `INT` integer bits including the sign bit
+    /// and `FRAC` fractional bits
+    ///
+    /// `Q<1, 15>` is a 16 bit value from -1 to just under 1 in steps of `2^-15`. Its raw bits are
+    /// the two's complement of the value in units of `2^-FRAC`.
+    Q,
+    i64,
+    true
+);
+
+impl_fixed!(
+    /// An unsigned fixed-point value in Q format, with `INT` integer bits and `FRAC` fractional
+    /// bits
+    ///
+    /// `UQ<4, 4>` is an 8 bit value from 0 to 15.9375 in steps of 1/16. Its raw bits are the
+    /// value in units of `2^-FRAC`.
+    UQ,
+    u64,
+    false
+);
+
+/// The exact decimal digits of `raw / 2^frac`, formatted without floats
+pub(crate) struct Decimal {
+    buffer: [u8; 88],
+    len: usize,
+}
+
+impl Decimal {
+    pub(crate) fn new(raw: i128, frac: u32) -> Self {
+        let mut decimal = Self {
+            buffer: [0; 88],
+            len: 0,
+        };
+        let magnitude = raw.unsigned_abs();
+        let mask = (1u128 << frac) - 1;
+        if raw < 0 {
+            decimal.push(b'-');
+        }
+
+        // Integer digits, most significant first
+        let integer = magnitude >> frac;
+        let mut divisor = 1;
+        while integer / divisor >= 10 {
+            divisor *= 10;
+        }
+        while divisor > 0 {
+            decimal.push(b'0' + (integer / divisor % 10) as u8);
+            divisor /= 10;
+        }
+
+        // Every fraction of a power of two has a finite decimal expansion
+        let mut fraction = magnitude & mask;
+        if fraction != 0 {
+            decimal.push(b'.');
+        }
+        while fraction != 0 {
+            fraction *= 10;
+            decimal.push(b'0' + (fraction >> frac) as u8);
+            fraction &= mask;
+        }
+        decimal
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.buffer[self.len] = byte;
+        self.len += 1;
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[..self.len]).expect("decimal digits are ASCII")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::format;
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_signed() {
+        type Gain = Q<1, 15>;
+        assert_eq!(Gain::BITS, 16);
+        assert_eq!(<Gain as NumBytes>::NUM_BYTES, 2);
+        assert_eq!(Gain::MIN.raw(), -0x8000);
+        assert_eq!(Gain::MAX.raw(), 0x7fff);
+
+        let half = Gain::from_f32(0.5).unwrap();
+        assert_eq!(half.raw(), 0x4000);
+        assert_eq!(half.to_f32(), 0.5);
+        assert_eq!(Gain::from_f32(-1.0), Some(Gain::MIN));
+        assert_eq!(Gain::from_f32(1.0), None);
+        assert_eq!(Gain::from_f32(f32::NAN), None);
+        assert_eq!(Gain::saturating_from_f32(1.0), Gain::MAX);
+        assert_eq!(Gain::saturating_from_f32(-7.0), Gain::MIN);
+        assert_eq!(Gain::saturating_from_f32(f32::NAN), Gain::ZERO);
+        assert_eq!(Gain::from_raw(0x8000), None);
+        assert_eq!(Gain::saturating_from_raw(-0x9000), Gain::MIN);
+
+        // Negative values are their two's complement
+        let negative = Gain::from_f32(-0.25).unwrap();
+        assert_eq!(negative.encode_const(), 0xe000);
+        assert_eq!(TryIntoBits::<u16>::try_into_bits(negative), Ok(0xe000));
+        assert_eq!(Gain::try_decode_const(0xe000), Ok(negative));
+        assert_eq!(
+            <Gain as TryFromBits<u32>>::try_from_bits(0x1_0000),
+            Err("bit pattern too large for target type Q")
+        );
+        assert_eq!(
+            TryIntoBits::<u8>::try_into_bits(negative),
+            Err("Q value too large for target type")
+        );
+    }
+
+    #[test]
+    fn test_unsigned() {
+        type Ratio = UQ<4, 4>;
+        assert_eq!(Ratio::MAX.to_f64(), 15.9375);
+        assert_eq!(Ratio::from_f64(15.97), None);
+        assert_eq!(Ratio::from_f64(15.96), Some(Ratio::MAX));
+        assert_eq!(Ratio::from_f64(-0.01), Some(Ratio::ZERO));
+        assert_eq!(Ratio::saturating_from_f64(-3.0), Ratio::ZERO);
+        assert_eq!(Ratio::try_decode_const(0x18).unwrap().to_f64(), 1.5);
+
+        type Wide = UQ<32, 32>;
+        assert_eq!(Wide::MAX.encode_const(), u64::MAX);
+        assert_eq!(Wide::try_decode_const(u64::MAX), Ok(Wide::MAX));
+        assert_eq!(Q::<64, 0>::try_decode_const(u64::MAX).unwrap().raw(), -1);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Q::<1, 15>::from_f32(-0.25).unwrap().to_string(), "-0.25");
+        assert_eq!(UQ::<4, 4>::MAX.to_string(), "15.9375");
+        assert_eq!(UQ::<4, 4>::ZERO.to_string(), "0");
+        assert_eq!(Q::<4, 4>::from_raw(-33).unwrap().to_string(), "-2.0625");
+        assert_eq!(
+            UQ::<0, 64>::MAX.to_string(),
+            "0.9999999999999999999457898913757247782996273599565029144287109375"
+        );
+        assert_eq!(Q::<64, 0>::MIN.to_string(), "-9223372036854775808");
+        assert_eq!(format!("{:?}", UQ::<2, 2>::MAX), "3.75");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        type Gain = Q<1, 15>;
+        let gain = Gain::from_f32(-0.25).unwrap();
+        assert_eq!(serde_json::to_string(&gain).unwrap(), "-0.25");
+        assert_eq!(serde_json::from_str::<Gain>("-0.25").unwrap(), gain);
+        assert_eq!(
+            serde_json::from_str::<Gain>("1.5").unwrap_err().to_string(),
+            "value out of range for Q"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ufmt")]
+    fn test_ufmt() {
+        use heapless::String;
+        use ufmt::uwrite;
+
+        let mut buffer = String::<16>::new();
+        uwrite!(buffer, "{:?}", Q::<4, 4>::from_raw(-33).unwrap()).unwrap();
+        assert_eq!(buffer.as_str(), "-2.0625");
+    }
+}
@@ -0,0 +1,40 @@
+//! Registers shared by the unit tests of the layout, diff and render modules
+
+use crate::{bit_register, FieldInfo, FieldKind, RegisterInfo, RegisterLayout};
+
+bit_register! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum OperationMode: u8 {
+        Idle = 0,
+        Active = 1,
+    }
+}
+
+bit_register! {
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct StatusRegister: u16 {
+        pub ready: bool => [3],
+        pub mode: OperationMode => [4:5],
+        pub count: u8 => [8:15],
+    }
+}
+
+/// Layout of [`StatusRegister`]
+pub const LAYOUT: RegisterInfo = StatusRegister::LAYOUT;
+
+/// The `mode` field of [`StatusRegister`], an enum in bits 4 to 5
+pub const MODE: FieldInfo = LAYOUT.fields[1];
+
+/// An unsigned field in bits 0 to 7 without a range, scale, `none` value or table, for tests to
+/// adapt with struct update syntax
+pub const BASE: FieldInfo = FieldInfo {
+    name: "base",
+    lsb: 0,
+    msb: 7,
+    kind: FieldKind::Unsigned,
+    valid: &[],
+    scale: None,
+    none: None,
+    table: &[],
+    doc: &[],
+};
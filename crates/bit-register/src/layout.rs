@@ -0,0 +1,419 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
+use crate::encoding::{bcd_decode, gray_decode};
+use crate::fixed::Decimal;
+
+/// Description of a single enum variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantInfo {
+    /// Name of the variant
+    pub name: &'static str,
+    /// Numeric value of the variant
+    pub value: u64,
+    /// Lines of the variant's doc comments, as written
+    pub doc: &'static [&'static str],
+}
+
+/// How the raw bits of a field are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A boolean flag
+    Bool,
+    /// An unsigned integer
+    Unsigned,
+    /// A fixed-point value with `frac` fractional bits, in two's complement if it is signed
+    Fixed {
+        /// Whether the value is signed
+        signed: bool,
+        /// Number of fractional bits
+        frac: u32,
+        /// Number of bits in the value, including the sign bit
+        bits: u32,
+    },
+    /// A decimal number stored as packed BCD, one digit per nibble
+    Bcd,
+    /// An unsigned integer stored as Gray code
+    Gray,
+    /// An enum with the listed variants
+    Enum(&'static [VariantInfo]),
+}
+
+impl FieldKind {
+    /// Whether a field of this kind can be `width` bits wide
+    ///
+    /// Fixed-point values must fill their field exactly, so that their sign bit is the top bit of
+    /// the field. Fields of every other kind can have any width.
+    pub const fn fits_width(&self, width: u32) -> bool {
+        match self {
+            FieldKind::Fixed { bits, .. } => *bits == width,
+            _ => true,
+        }
+    }
+}
+
+/// An inclusive range of values a field accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueRange {
+    /// Lowest valid value
+    pub min: u64,
+    /// Highest valid value
+    pub max: u64,
+}
+
+impl ValueRange {
+    /// Whether the range contains a value
+    pub const fn contains(&self, value: u64) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// Linear conversion between the raw bits of a field and a physical value
+///
+/// The physical value is `raw * scale + offset`, in the units of the field's type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldScale {
+    /// Physical value of one step of the raw bits, such as 16 for 16 mV per LSB
+    pub scale: i64,
+    /// Physical value of raw bits of zero
+    pub offset: i64,
+    /// Unit of the physical value, or empty if it has none
+    pub unit: &'static str,
+}
+
+impl FieldScale {
+    /// Physical value of the raw bits of a field
+    pub const fn physical(&self, raw: u64) -> i128 {
+        raw as i128 * self.scale as i128 + self.offset as i128
+    }
+
+    /// Raw bits of the nearest step to a physical value, rounding halfway values away from zero
+    ///
+    /// The result may be negative or too large for the field, which callers must check.
+    pub const fn raw(&self, physical: i128) -> i128 {
+        let scale = self.scale as i128;
+        let difference = physical - self.offset as i128;
+        let raw = difference / scale;
+        let remainder = difference % scale;
+        if 2 * remainder.abs() < scale.abs() {
+            raw
+        } else if (difference < 0) == (scale < 0) {
+            raw + 1
+        } else {
+            raw - 1
+        }
+    }
+}
+
+/// Description of a single field of a register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// Name of the field
+    pub name: &'static str,
+    /// Lowest bit of the field
+    pub lsb: u32,
+    /// Highest bit of the field
+    pub msb: u32,
+    /// How the raw bits of the field are interpreted
+    pub kind: FieldKind,
+    /// Raw values the field accepts, or empty if it accepts any value which fits in its bits
+    pub valid: &'static [ValueRange],
+    /// Conversion of the raw bits to a physical value, if the field is scaled
+    pub scale: Option<FieldScale>,
+    /// Raw value which means no value, if the field holds an `Option`
+    pub none: Option<u64>,
+    /// Values of the raw bits as indexes, or empty if the field has no lookup table
+    pub table: &'static [i128],
+    /// Lines of the field's doc comments, as written
+    pub doc: &'static [&'static str],
+}
+
+impl FieldInfo {
+    /// Number of bits in the field
+    pub const fn width(&self) -> u32 {
+        self.msb - self.lsb + 1
+    }
+
+    /// Mask of the field's bits, shifted down to bit 0
+    pub const fn value_mask(&self) -> u64 {
+        if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width()) - 1
+        }
+    }
+
+    /// Mask of the field's bits within the register
+    pub const fn mask(&self) -> u64 {
+        self.value_mask() << self.lsb
+    }
+
+    /// Extract the raw bits of the field from a register value
+    pub const fn extract(&self, register: u64) -> u64 {
+        (register >> self.lsb) & self.value_mask()
+    }
+
+    /// Whether the field accepts a raw value, which must also fit in its bits
+    ///
+    /// The raw value of `None` is always accepted, as valid values only restrict `Some` values.
+    /// Fields with a lookup table only accept the indexes of its values.
+    pub const fn is_valid(&self, raw: u64) -> bool {
+        if raw > self.value_mask() || (!self.table.is_empty() && raw >= self.table.len() as u64) {
+            return false;
+        }
+        if self.valid.is_empty() || matches!(self.none, Some(none) if none == raw) {
+            return true;
+        }
+        let mut i = 0;
+        while i < self.valid.len() {
+            if self.valid[i].contains(raw) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+/// Description of a register and its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterInfo {
+    /// Name of the register
+    pub name: &'static str,
+    /// Number of bits in the underlying type
+    pub bits: u32,
+    /// Fields of the register, in declaration order
+    pub fields: &'static [FieldInfo],
+    /// Lines of the register's doc comments, as written
+    pub doc: &'static [&'static str],
+}
+
+impl RegisterInfo {
+    /// Find a field by name
+    pub fn field(&self, name: &str) -> Option<&'static FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// Trait for field types which can describe how their raw bits are interpreted
+pub trait FieldType {
+    /// How the raw bits of the field are interpreted
+    const KIND: FieldKind;
+}
+
+impl FieldType for bool {
+    const KIND: FieldKind = FieldKind::Bool;
+}
+
+macro_rules! impl_unsigned_field_type {
+    ($($t:ty),*) => {
+        $(
+            impl FieldType for $t {
+                const KIND: FieldKind = FieldKind::Unsigned;
+            }
+        )*
+    }
+}
+
+impl_unsigned_field_type!(u8, u16, u32, u64);
+impl_unsigned_field_type!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64);
+
+/// Looks up the kind of a field type, with [`FieldKindFallback`](__FieldKindFallback) as the
+/// fallback for types which do not implement [`FieldType`]
+///
+/// Inherent associated constants take precedence over trait ones, so
+/// `{ use __FieldKindFallback as _; __FieldKindOf::<T>::KIND }` is `T::KIND` when `T` implements
+/// `FieldType`, and `FieldKind::Unsigned` otherwise.
+#[doc(hidden)]
+pub struct __FieldKindOf<T: ?Sized>(PhantomData<T>);
+
+impl<T: FieldType + ?Sized> __FieldKindOf<T> {
+    pub const KIND: FieldKind = T::KIND;
+}
+
+/// Describes field types which do not implement [`FieldType`] as unsigned integers
+#[doc(hidden)]
+pub trait __FieldKindFallback {
+    const KIND: FieldKind = FieldKind::Unsigned;
+}
+
+impl<T: ?Sized> __FieldKindFallback for __FieldKindOf<T> {}
+
+/// Trait for registers which can describe their field layout
+pub trait RegisterLayout {
+    /// Description of the register and its fields
+    const LAYOUT: RegisterInfo;
+}
+
+/// The raw bits of a field, displayed according to the field's kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldValue {
+    /// The field the bits belong to
+    pub field: &'static FieldInfo,
+    /// The raw bits of the field, shifted down to bit 0
+    pub raw: u64,
+}
+
+impl FieldValue {
+    /// Name of the enum variant matching the raw bits, if the field is an enum
+    pub fn variant_name(&self) -> Option<&'static str> {
+        match self.field.kind {
+            FieldKind::Enum(variants) => variants
+                .iter()
+                .find(|variant| variant.value == self.raw)
+                .map(|variant| variant.name),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.none == Some(self.raw) {
+            return f.write_str("None");
+        }
+        match self.field.kind {
+            FieldKind::Bool if self.raw <= 1 => write!(f, "{}", self.raw == 1),
+            FieldKind::Unsigned if !self.field.table.is_empty() => {
+                match self.field.table.get(self.raw as usize) {
+                    Some(value) => write!(f, "{}", value),
+                    None => write!(f, "<invalid {:#x}>", self.raw),
+                }
+            }
+            FieldKind::Unsigned => match self.field.scale {
+                Some(scale) if scale.unit.is_empty() => write!(f, "{}", scale.physical(self.raw)),
+                Some(scale) => write!(f, "{} {}", scale.physical(self.raw), scale.unit),
+                None => write!(f, "{}", self.raw),
+            },
+            FieldKind::Fixed { signed, frac, bits } => {
+                // Signed values are sign extended from the top bit of their type, like decoding
+                let shift = 128 - bits;
+                let raw = if signed {
+                    ((self.raw as i128) << shift) >> shift
+                } else {
+                    self.raw as i128
+                };
+                f.write_str(Decimal::new(raw, frac).as_str())
+            }
+            FieldKind::Bcd => match bcd_decode(self.raw) {
+                Some(value) => write!(f, "{}", value),
+                None => write!(f, "<invalid {:#x}>", self.raw),
+            },
+            FieldKind::Gray => write!(f, "{}", gray_decode(self.raw)),
+            FieldKind::Enum(_) => match self.variant_name() {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "<invalid {:#x}>", self.raw),
+            },
+            _ => write!(f, "<invalid {:#x}>", self.raw),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::*;
+    use crate::fixtures::{BASE, MODE};
+
+    #[test]
+    fn test_field_masks() {
+        assert_eq!(MODE.width(), 2);
+        assert_eq!(MODE.value_mask(), 0b11);
+        assert_eq!(MODE.mask(), 0b11_0000);
+        assert_eq!(MODE.extract(0b01_1111), 0b01);
+
+        let full = FieldInfo {
+            name: "full",
+            msb: 63,
+            ..BASE
+        };
+        assert_eq!(full.value_mask(), u64::MAX);
+        assert_eq!(full.extract(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_field_value_display() {
+        let value = |field, raw| FieldValue { field, raw }.to_string();
+
+        assert_eq!(value(&MODE, 1), "Active");
+        assert_eq!(value(&MODE, 3), "<invalid 0x3>");
+
+        const FLAG: FieldInfo = FieldInfo {
+            name: "flag",
+            msb: 0,
+            kind: FieldKind::Bool,
+            ..BASE
+        };
+        assert_eq!(value(&FLAG, 1), "true");
+        assert_eq!(value(&FLAG, 0), "false");
+
+        const COUNT: FieldInfo = FieldInfo {
+            name: "count",
+            ..BASE
+        };
+        assert_eq!(value(&COUNT, 42), "42");
+
+        const GAIN: FieldInfo = FieldInfo {
+            name: "gain",
+            kind: FieldKind::Fixed {
+                signed: true,
+                frac: 6,
+                bits: 8,
+            },
+            ..BASE
+        };
+        assert_eq!(value(&GAIN, 0x60), "1.5");
+        assert_eq!(value(&GAIN, 0xe0), "-0.5");
+
+        // Values are sign extended from the top bit of their type like decoding, even in layouts
+        // which were not checked by `bit_register!`
+        const WIDE: FieldInfo = FieldInfo { msb: 9, ..GAIN };
+        assert_eq!(value(&WIDE, 0x0e0), "-0.5");
+        const NARROW: FieldInfo = FieldInfo { msb: 5, ..GAIN };
+        assert_eq!(value(&NARROW, 0x20), "0.5");
+
+        const SECONDS: FieldInfo = FieldInfo {
+            name: "seconds",
+            msb: 6,
+            kind: FieldKind::Bcd,
+            ..BASE
+        };
+        assert_eq!(value(&SECONDS, 0x59), "59");
+        assert_eq!(value(&SECONDS, 0x5a), "<invalid 0x5a>");
+
+        const POSITION: FieldInfo = FieldInfo {
+            name: "position",
+            msb: 3,
+            kind: FieldKind::Gray,
+            ..BASE
+        };
+        assert_eq!(value(&POSITION, 0b0011), "2");
+        assert_eq!(value(&POSITION, 0b1000), "15");
+
+        const CHANNEL: FieldInfo = FieldInfo {
+            name: "channel",
+            msb: 3,
+            valid: &[ValueRange { min: 0, max: 7 }],
+            none: Some(0xf),
+            ..BASE
+        };
+        assert_eq!(value(&CHANNEL, 0xf), "None");
+        assert_eq!(value(&CHANNEL, 3), "3");
+        assert!(CHANNEL.is_valid(0xf));
+        assert!(!CHANNEL.is_valid(0xe));
+
+        const TIMEOUT: FieldInfo = FieldInfo {
+            name: "timeout",
+            msb: 2,
+            table: &[1, 4, 16, 64],
+            ..BASE
+        };
+        assert_eq!(value(&TIMEOUT, 2), "16");
+        assert_eq!(value(&TIMEOUT, 4), "<invalid 0x4>");
+        assert!(TIMEOUT.is_valid(3));
+        assert!(!TIMEOUT.is_valid(4));
+    }
+}
@@ -14,9 +14,21 @@
 //! - Define enum types with automatic conversion to/from bit representations
 //! - Type-safe access to register bit fields with compile-time checking
 //! - Range validation for field values to prevent overflow
+//! - Restricting fields to ranges of valid values
+//! - Scaled fields which hold physical values, such as millivolts
+//! - Signed and unsigned Q-format fixed-point fields with [`Q`] and [`UQ`]
+//! - BCD and Gray code fields with [`Bcd`] and [`Gray`]
+//! - `Option` fields where a sentinel value such as `0xff` means `None`
+//! - Lookup tables mapping the raw bits of a field to non-linear values
+//! - `NonZeroU8` to `NonZeroU64` fields which reject a bit pattern of zero
+//! - Register-level validation of invariants between fields
 //! - Support for various integer sizes (u8, u16, u32, u64)
 //! - Support for different field types (boolean, numeric, enum)
 //! - Fully compatible with no_std environments
+//! - Optional serde support, as a field map or the raw value
+//! - Optional `defmt::Format` implementations for logging
+//! - Optional `ufmt` implementations for targets without `core::fmt`
+//! - Optional `#[derive(BitRegister)]` alternative to the macro, with the `derive` feature
 //!
 //! ## Defining a Register Struct
 //!
@@ -72,6 +84,57 @@
 //! }
 //! ```
 //!
+//! ## Bit Numbering
+//!
+//! Bits are numbered from the least significant end by default. Registers from specs which
+//! number bits from the most significant end can say so with `#[bit_register(bit_order = msb0)]`
+//! and use the datasheet's numbers as written. Ranges can also be given in descending order,
+//! e.g. `[7:4]` as in Verilog, which is the same as `[4:7]`.
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(bit_order = msb0)]
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct Header: u16 {
+//!         pub version: u8 => [0:3],   // bits 15-12 when counted from the least significant end
+//!         pub length: u8 => [15:8]    // bits 7-0
+//!     }
+//! }
+//!
+//! let header = Header::try_from(0xa05cu16).unwrap();
+//! assert_eq!(header, Header { version: 0xa, length: 0x5c });
+//! ```
+//!
+//! Layouts, diffs and rendered tables always count from the least significant end. Bits which do
+//! not fit in the underlying type are a compile error:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     pub struct Example: u8 {
+//!         pub value: u8 => [4:8]
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
+//! So is giving the bit order more than once:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(bit_order = msb0, bit_order = lsb0)]
+//!     pub struct Example: u8 {
+//!         pub value: u8 => [0:7]
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
 //! ## Error Handling
 //!
 //! The crate provides error handling for value validation:
@@ -94,593 +157,4311 @@
 //! let result: Result<u8, _> = invalid.try_into();
 //! assert!(result.is_err());  // Error: value exceeds maximum for bit width
 //! ```
+//!
+//! ## Valid Values
+//!
+//! A field can be restricted to the values a datasheet allows with `in`, followed by values or
+//! inclusive ranges separated by `|`. The check applies to the field's raw bits and runs on every
+//! conversion, in both directions, and the ranges are recorded in
+//! [`FieldInfo::valid`].
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct ClockRegister: u16 {
+//!         pub divider: u8 => [0:3] in 1..=10,
+//!         pub source: u8 => [4:5] in 0 | 2,
+//!         pub enabled: bool => [8]
+//!     }
+//! }
+//!
+//! assert_eq!(ClockRegister::try_from(0x0000u16), Err("divider is not in 1..=10"));
+//! assert_eq!(ClockRegister::try_from(0x0011u16), Err("source is not in 0 | 2"));
+//!
+//! let clock = ClockRegister { divider: 11, source: 0, enabled: true };
+//! assert_eq!(TryInto::<u16>::try_into(clock), Err("divider is not in 1..=10"));
+//! ```
+//!
+//! ## Scaled Fields
+//!
+//! A field with `scale` holds a physical value of `raw * scale + offset` in its own integer type,
+//! such as millivolts. `offset` and `unit` are optional, and steps which are not whole numbers
+//! need a smaller unit. The field type must implement [`ScaledType`], as the integer types up to
+//! 64 bits do. Fixed-point types are already scaled by their fraction bits, so they cannot have a
+//! `scale`. Encoding rounds to the nearest step, and the scale is recorded in
+//! [`FieldInfo::scale`] so diffs and rendered tables show physical values.
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct ChargerRegister: u16 {
+//!         pub vbat: u16 => [0:7] scale 16 offset 2000 unit "mV",
+//!         pub temperature: i32 => [8:15] scale 500 offset -40000 unit "m°C"
+//!     }
+//! }
+//!
+//! let charger = ChargerRegister::try_from(0x5004u16).unwrap();
+//! assert_eq!(charger, ChargerRegister { vbat: 2064, temperature: 0 });
+//!
+//! // 4100 mV is between steps 131 and 132, and rounds to the nearest
+//! let charger = ChargerRegister { vbat: 4100, temperature: 25000 };
+//! assert_eq!(TryInto::<u16>::try_into(charger), Ok(0x8283));
+//!
+//! let charger = ChargerRegister { vbat: 1000, temperature: 0 };
+//! assert_eq!(
+//!     TryInto::<u16>::try_into(charger),
+//!     Err("vbat is out of range for its scale and bit width")
+//! );
+//! ```
+//!
+//! A scale of zero is a compile error:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     pub struct Example: u8 {
+//!         pub level: u8 => [0:7] scale 0
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
+//! So is a scaled fixed-point field:
+//!
+//! ```rust,compile_fail
+//! use bit_register::{bit_register, Q};
+//!
+//! type Gain = Q<2, 6>;
+//!
+//! bit_register! {
+//!     pub struct Example: u8 {
+//!         pub gain: Gain => [0:7] scale 2
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
+//! ## Fixed-Point Fields
+//!
+//! [`Q<INT, FRAC>`](Q) and [`UQ<INT, FRAC>`](UQ) hold two's complement and unsigned
+//! fixed-point values with `INT` integer bits and `FRAC` fractional bits, so `Q<1, 15>` is the
+//! common Q15 format. For signed values `INT` includes the sign bit, and the field must be
+//! exactly `INT + FRAC` bits wide. `bit_register!` takes a field type as a path without generic
+//! parameters, other than `Option`, so fixed-point types need an alias:
+//!
+//! ```rust
+//! use bit_register::{bit_register, Q, UQ};
+//!
+//! type Gain = Q<2, 6>;
+//! type Coefficient = UQ<0, 8>;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct AmplifierRegister: u16 {
+//!         pub gain: Gain => [0:7],
+//!         pub coefficient: Coefficient => [8:15]
+//!     }
+//! }
+//!
+//! let amplifier = AmplifierRegister {
+//!     gain: Gain::from_f32(-0.5).unwrap(),
+//!     coefficient: Coefficient::from_f64(0.75).unwrap(),
+//! };
+//! assert_eq!(TryInto::<u16>::try_into(amplifier), Ok(0xc0e0));
+//!
+//! // Checked conversions reject values out of range, saturating ones clamp them
+//! assert_eq!(Gain::from_f32(2.0), None);
+//! assert_eq!(Gain::saturating_from_f32(2.0), Gain::MAX);
+//! assert_eq!(Gain::MAX.to_string(), "1.984375");
+//! ```
+//!
+//! Conversions from floats round to the nearest step, and values display as exact decimals. A
+//! field which is not exactly as wide as its fixed-point type is a compile error, as its sign bit
+//! would not be the top bit of the field:
+//!
+//! ```rust,compile_fail
+//! use bit_register::{bit_register, Q};
+//!
+//! type Gain = Q<2, 6>;
+//!
+//! bit_register! {
+//!     pub struct Example: u16 {
+//!         pub gain: Gain => [0:9]
+//!     }
+//! }
+//! # let _ = Example::try_from(0u16);
+//! ```
+//!
+//! ## BCD and Gray Code Fields
+//!
+//! [`Bcd<T>`](Bcd) holds a decimal number stored as packed BCD, one digit per nibble, and
+//! [`Gray<T>`](Gray) holds an unsigned integer stored as Gray code. Decoding a BCD field fails
+//! with `bit pattern is not valid BCD` if a nibble is not a decimal digit. Like fixed-point
+//! types, they need an alias in `bit_register!`:
+//!
+//! ```rust
+//! use bit_register::{bit_register, Bcd, Gray};
+//!
+//! type Digits = Bcd<u8>;
+//! type Position = Gray<u8>;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct RtcTime: u16 {
+//!         pub seconds: Digits => [0:6],
+//!         pub minutes: Digits => [8:14]
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct EncoderRegister: u8 {
+//!         pub position: Position => [0:3]
+//!     }
+//! }
+//!
+//! let time = RtcTime::try_from(0x3059u16).unwrap();
+//! assert_eq!(time.seconds.get(), 59);
+//! assert_eq!(time.minutes.get(), 30);
+//! assert_eq!(RtcTime::try_from(0x305au16), Err("bit pattern is not valid BCD"));
+//!
+//! // Values with more digits than the type holds are rejected up front
+//! assert_eq!(Digits::new(100), None);
+//!
+//! let encoder = EncoderRegister { position: Position::new(2) };
+//! assert_eq!(TryInto::<u8>::try_into(encoder), Ok(0b0011));
+//! ```
+//!
+//! ## Option Fields
+//!
+//! A field of type `Option<T>` with `none` decodes that raw value to `None` and encodes `None`
+//! back to it. `Some` values are converted like a field of type `T`, and may not use the raw
+//! value of `None`. Valid values with `in` only restrict `Some` values.
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct FaultRegister: u16 {
+//!         pub channel: Option<u8> => [0:7] none 0xff,
+//!         pub code: Option<u8> => [8:11] none 0 in 1..=9
+//!     }
+//! }
+//!
+//! let fault = FaultRegister::try_from(0x00ffu16).unwrap();
+//! assert_eq!(fault, FaultRegister { channel: None, code: None });
+//!
+//! let fault = FaultRegister { channel: Some(3), code: Some(2) };
+//! assert_eq!(TryInto::<u16>::try_into(fault), Ok(0x0203));
+//!
+//! let fault = FaultRegister { channel: Some(0xff), code: None };
+//! assert_eq!(
+//!     TryInto::<u16>::try_into(fault),
+//!     Err("channel is the raw value reserved for None")
+//! );
+//! ```
+//!
+//! The raw value of `None` is recorded in [`FieldInfo::none`], and must fit in the field's bits:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     pub struct Example: u8 {
+//!         pub channel: Option<u8> => [0:3] none 0xff
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
+//! ## Lookup Table Fields
+//!
+//! Timeouts, prescalers and similar settings are often encoded as an index into a fixed list of
+//! values. An integer field with `table` decodes its raw bits as an index into the table, and
+//! encodes a value as its index. Values which are not in the table cannot be encoded, and raw
+//! bits past the end of the table cannot be decoded.
+//!
+//! ```rust
+//! use bit_register::{bit_register, RegisterLayout};
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct WatchdogControl: u8 {
+//!         pub timeout_ms: u16 => [0:1] table [1, 4, 16, 64],
+//!         pub enabled: bool => [7]
+//!     }
+//! }
+//!
+//! let watchdog = WatchdogControl::try_from(0x82u8).unwrap();
+//! assert_eq!(watchdog, WatchdogControl { timeout_ms: 16, enabled: true });
+//!
+//! let watchdog = WatchdogControl { timeout_ms: 10, enabled: true };
+//! assert_eq!(
+//!     TryInto::<u8>::try_into(watchdog),
+//!     Err("timeout_ms is not in its lookup table")
+//! );
+//! assert_eq!(WatchdogControl::LAYOUT.fields[0].table, &[1, 4, 16, 64]);
+//! ```
+//!
+//! The table is recorded in [`FieldInfo::table`], and must fit in the field's bits:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     pub struct Example: u8 {
+//!         pub timeout_ms: u16 => [0:1] table [1, 4, 16, 64, 256]
+//!     }
+//! }
+//! # let _ = Example::try_from(0u8);
+//! ```
+//!
+//! ## Non-Zero Fields
+//!
+//! Dividers and counters which must never be zero can use [`NonZeroU8`](core::num::NonZeroU8)
+//! to `NonZeroU64`, by name, by path or through an alias. Decoding a bit pattern of zero fails
+//! with its own error. An `Option` of a non-zero type with `none 0` reads zero as `None`. The
+//! `const` option matches field types by their tokens, so it needs the non-zero types named
+//! directly or by their `core::num` or `std::num` path.
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//! use core::num::NonZeroU8;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct ClockDivider: u16 {
+//!         pub divider: NonZeroU8 => [0:7],
+//!         pub repeat: Option<NonZeroU8> => [8:11] none 0
+//!     }
+//! }
+//!
+//! let clock = ClockDivider::try_from(0x0005u16).unwrap();
+//! assert_eq!(clock.divider.get(), 5);
+//! assert_eq!(clock.repeat, None);
+//! assert_eq!(
+//!     ClockDivider::try_from(0x0300u16),
+//!     Err("bit pattern is zero for target type NonZeroU8")
+//! );
+//! ```
+//!
+//! ## Register Validation
+//!
+//! Some combinations of field values are illegal even though every field fits in its bits. A
+//! register with `#[bit_register(validate = path::to_fn)]` is checked as a whole by a
+//! `fn(&Register) -> Result<(), &'static str>`, which runs after the fields are decoded by
+//! `TryFrom` and before they are encoded by `TryInto`. Its error is returned unchanged. With
+//! `#[bit_register(const)]` it also runs in `try_decode_const` and `try_encode_const`, so it must
+//! be a `const fn`.
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub enum ConverterMode: u8 {
+//!         Buck = 0,
+//!         Boost = 1
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     #[bit_register(validate = check_converter)]
+//!     pub struct ConverterControl: u8 {
+//!         pub mode: ConverterMode => [0],
+//!         pub current_limit: u8 => [1:4]
+//!     }
+//! }
+//!
+//! fn check_converter(control: &ConverterControl) -> Result<(), &'static str> {
+//!     if matches!(control.mode, ConverterMode::Boost) && control.current_limit > 3 {
+//!         Err("boost mode needs a current limit of at most 3")
+//!     } else {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let control = ConverterControl { mode: ConverterMode::Boost, current_limit: 8 };
+//! assert_eq!(
+//!     TryInto::<u8>::try_into(control),
+//!     Err("boost mode needs a current limit of at most 3")
+//! );
+//! assert!(ConverterControl::try_from(0x11u8).is_err());
+//! ```
+//!
+//! A validation function which is not a `const fn` is a compile error with the `const` option:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(const, validate = check_window)]
+//!     pub struct Window: u16 {
+//!         pub low: u8 => [0:7],
+//!         pub high: u8 => [8:15]
+//!     }
+//! }
+//!
+//! fn check_window(window: &Window) -> Result<(), &'static str> {
+//!     if window.low > window.high { Err("low must not exceed high") } else { Ok(()) }
+//! }
+//! # let _ = Window::try_from(0u16);
+//! ```
+//!
+//! So is giving more than one validation function:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(validate = check_order, validate = check_range)]
+//!     pub struct Window: u16 {
+//!         pub low: u8 => [0:7],
+//!         pub high: u8 => [8:15]
+//!     }
+//! }
+//!
+//! fn check_order(window: &Window) -> Result<(), &'static str> {
+//!     if window.low > window.high { Err("low must not exceed high") } else { Ok(()) }
+//! }
+//!
+//! fn check_range(window: &Window) -> Result<(), &'static str> {
+//!     if window.high > 200 { Err("high must not exceed 200") } else { Ok(()) }
+//! }
+//! # let _ = Window::try_from(0u16);
+//! ```
+//!
+//! ## Const Conversions
+//!
+//! Enums always provide `const fn` conversions, and registers with `#[bit_register(const)]` do
+//! too, so reset values and init tables can be built at compile time:
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub enum OperationMode: u8 {
+//!         Idle = 0,
+//!         Active = 1
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[bit_register(const)]
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct ControlRegister: u16 {
+//!         pub enabled: bool => [0],
+//!         pub mode: OperationMode => [1:2]
+//!     }
+//! }
+//!
+//! const CFG: u16 = ControlRegister {
+//!     enabled: true,
+//!     mode: OperationMode::Active,
+//! }
+//! .encode_const();
+//! const DECODED: ControlRegister = ControlRegister::decode_const(CFG);
+//!
+//! assert_eq!(CFG, 0b011);
+//! assert_eq!(DECODED.mode, OperationMode::Active);
+//! ```
+//!
+//! Invalid values are reported at compile time:
+//!
+//! ```rust,compile_fail
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(const)]
+//!     pub struct Example: u8 {
+//!         pub value: u8 => [0:3]
+//!     }
+//! }
+//!
+//! const INVALID: u8 = Example { value: 16 }.encode_const();
+//! # let _ = INVALID;
+//! ```
+//!
+//! The `try_encode_const` and `try_decode_const` variants return a `Result` instead. As trait
+//! methods cannot be called in `const` contexts, the fields are converted by their type as
+//! written: `bool`, the unsigned integers and `NonZeroU8` to `NonZeroU64` are converted
+//! directly, and any other type must provide `encode_const` and `try_decode_const` like the
+//! enums generated by this macro, [`Q`], [`UQ`], [`Bcd`] and [`Gray`]. Registers without the
+//! option only use the [`TryFromBits`] and [`TryIntoBits`] traits, so their fields can have any
+//! type implementing them, including aliases of the unsigned integers.
+//!
+//! ## Partial Writes
+//!
+//! Registers with `#[bit_register(partial)]` also get a `Partial` type where all fields are
+//! optional. It produces a `(mask, value)` pair for buses that support masked writes, or applies
+//! the set fields to an existing raw value:
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[bit_register(partial)]
+//!     pub struct StatusRegister: u16 {
+//!         pub enabled: bool => [0],
+//!         pub mode: u8 => [1:3],
+//!         pub error_code: u8 => [4:7]
+//!     }
+//! }
+//!
+//! let partial = PartialStatusRegister::new().with_enabled(true).with_mode(2);
+//! assert_eq!(partial.try_mask_value(), Ok((0b1111, 0b0101)));
+//! assert_eq!(partial.try_apply(0xFFFF), Ok(0xFFF5));
+//!
+//! // Set fields are range checked
+//! assert!(PartialStatusRegister::new().with_mode(8).try_mask_value().is_err());
+//! ```
+//!
+//! The setters are named `with_<field>`, so fields can share names with methods such as
+//! `try_apply`. As a partial register does not know the other fields, its register's `validate`
+//! function is not run. Partial registers are `Clone`, `Copy`, `Debug` and `PartialEq` when the
+//! types of all their fields are.
+//!
+//! ## Matching Raw Values
+//!
+//! Registers with `#[bit_register(pattern)]` also get a `Pattern` type for checking raw values,
+//! where fields can be constrained to an exact value or any of a set of values:
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq, Clone, Copy)]
+//!     pub enum OperationMode: u8 {
+//!         Idle = 0,
+//!         Active = 1,
+//!         LowPower = 2
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[bit_register(pattern)]
+//!     pub struct StatusRegister: u16 {
+//!         pub ready: bool => [3],
+//!         pub mode: OperationMode => [4:5]
+//!     }
+//! }
+//!
+//! const READY_AND_IDLE: StatusRegisterPattern =
+//!     StatusRegisterPattern::new().with_ready(true).with_mode(OperationMode::Idle);
+//! assert!(READY_AND_IDLE.matches(0b0000_1000));
+//! assert_eq!(READY_AND_IDLE.try_mask_expected(), Ok((0b0011_1000, 0b0000_1000)));
+//!
+//! let awake = StatusRegisterPattern::new()
+//!     .with_mode_any_of(&[OperationMode::Active, OperationMode::LowPower]);
+//! assert!(awake.matches(0b0010_0000));
+//! assert!(!awake.matches(0b0000_0000));
+//! ```
+//!
+//! Like the setters of partial registers, the constraints are named `with_<field>` and
+//! `with_<field>_any_of`, so fields can share names with methods such as `matches`, but not a
+//! field `x` with a field `x_any_of`. Patterns are `Clone`, `Copy` and `Debug` when the types of
+//! all their fields are.
+//!
+//! ## Comparing Register Values
+//!
+//! Registers describe their fields through [`RegisterLayout`], which is used to compare two
+//! values field by field. Fields are compared by converting copies of them, so registers whose
+//! field types are all `Clone` have a `diff` method. [`assert_register_eq!`] prints only the
+//! differing fields on failure:
+//!
+//! ```rust
+//! use bit_register::{assert_register_eq, bit_register};
+//!
+//! bit_register! {
+//!     pub struct StatusRegister: u16 {
+//!         pub enabled: bool => [0],
+//!         pub mode: u8 => [1:3]
+//!     }
+//! }
+//!
+//! let written = StatusRegister { enabled: true, mode: 2 };
+//! let read_back = StatusRegister { enabled: true, mode: 3 };
+//!
+//! let diff = written.diff(&read_back);
+//! assert_eq!(diff.iter().count(), 1);
+//! assert_eq!(diff.to_string(), "StatusRegister:\n  mode [1:3]: 2 -> 3");
+//!
+//! assert_register_eq!(written, written);
+//! ```
+//!
+//! Field types can implement [`FieldType`] to describe how their raw bits are interpreted, as
+//! `bool`, the unsigned integers and the enums generated by this macro do. Other field types are
+//! described as unsigned integers.
+//!
+//! ## Rendering Bit Layouts
+//!
+//! [`BitLayout`] renders a raw value as a table of bits like the register figures in datasheets,
+//! including the reserved bits which are not mapped to any field. It writes into any
+//! [`core::fmt::Write`] without allocating:
+//!
+//! ```rust
+//! use bit_register::{bit_register, BitLayout};
+//!
+//! bit_register! {
+//!     pub struct StatusRegister: u16 {
+//!         pub enabled: bool => [0],
+//!         pub mode: u8 => [1:3]
+//!     }
+//! }
+//!
+//! let table = BitLayout::of::<StatusRegister>(0x8005u16).to_string();
+//! assert_eq!(
+//!     table,
+//!     "StatusRegister = 0x8005\n\
+//!      \x20bits  | field      | raw            | value\n\
+//!      -------+------------+----------------+-------\n\
+//!      \x2015:4  | (reserved) | 0b100000000000 |\n\
+//!      \x203:1   | mode       | 0b010          | 2\n\
+//!      \x200     | enabled    | 0b1            | true\n"
+//! );
+//! ```
+//!
+//! ## Serde Support
+//!
+//! With the `serde` feature enabled, registers and enums implement `Serialize` and
+//! `Deserialize`. Registers are represented as a map of field names to values, with enum fields
+//! as their variant names. Deserialization checks every field against its bits exactly like
+//! `TryInto`, so an invalid configuration is rejected when it is loaded rather than when it is
+//! written to the hardware. Registers are checked by converting copies of their fields, so only
+//! registers whose field types are all `Clone` implement the traits.
+//!
+//! Options for the generated code are given in a `#[bit_register(...)]` attribute. A type
+//! with `#[bit_register(serde = raw)]` is represented as its underlying value instead, which is
+//! validated like `TryFrom`:
+//!
+//! ```rust
+//! use bit_register::bit_register;
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+//!     pub enum OperationMode: u8 {
+//!         Idle = 0,
+//!         Active = 1
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[derive(Debug, PartialEq, Eq)]
+//!     pub struct ControlRegister: u16 {
+//!         pub enabled: bool => [0],
+//!         pub mode: OperationMode => [1:2]
+//!     }
+//! }
+//!
+//! bit_register! {
+//!     #[bit_register(serde = raw)]
+//!     #[derive(Debug, PartialEq, Eq)]
+//!     pub struct StatusRegister: u16 {
+//!         pub ready: bool => [3],
+//!         pub count: u8 => [8:15]
+//!     }
+//! }
+//!
+//! # #[cfg(feature = "serde")]
+//! # {
+//! let control = ControlRegister { enabled: true, mode: OperationMode::Active };
+//! assert_eq!(
+//!     serde_json::to_string(&control).unwrap(),
+//!     r#"{"enabled":true,"mode":"Active"}"#
+//! );
+//!
+//! let status = StatusRegister { ready: true, count: 2 };
+//! assert_eq!(serde_json::to_string(&status).unwrap(), "520");
+//!
+//! // `mode` only has 2 bits
+//! assert!(serde_json::from_str::<ControlRegister>(r#"{"enabled":true,"mode":4}"#).is_err());
+//! # }
+//! ```
+//!
+//! ## defmt Support
+//!
+//! With the `defmt` feature enabled, registers and enums implement `defmt::Format`, so they can
+//! be logged without pulling in `core::fmt`. Registers print their field names and values, and
+//! enums print their variant names. A type with `#[bit_register(defmt = raw)]` only prints its
+//! underlying value in hex, which is the most compact representation on the wire.
+//!
+//! The implementations only refer to the `defmt` crate re-exported by this crate, so a crate
+//! defining registers does not need to depend on `defmt` itself.
+//!
+//! Options can be combined, e.g. `#[bit_register(serde = raw, defmt = raw)]`.
+//!
+//! ## ufmt Support
+//!
+//! For targets which cannot afford `core::fmt`, the `ufmt` feature implements `ufmt::uDebug`
+//! and `ufmt::uDisplay` for registers and enums. Both print the field names and values of a
+//! register, e.g. `ControlRegister { enabled: true, mode: Active }`, and the variant name of an
+//! enum. They can be written to any `ufmt::uWrite`, such as a `heapless::String` buffer.
+//!
+//! Unknown options are a compile error:
+//!
+//! ```compile_fail
+//! # use bit_register::bit_register;
+//! bit_register! {
+//!     #[bit_register(serde = binary)]
+//!     pub struct StatusRegister: u16 {
+//!         pub ready: bool => [3]
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+pub mod defmt_support;
+mod diff;
+mod encoding;
+mod fixed;
+#[cfg(test)]
+mod fixtures;
+mod layout;
+mod pattern;
+mod render;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_support;
+mod traits;
+pub use diff::*;
+pub use encoding::*;
+pub use fixed::*;
+pub use layout::*;
+pub use pattern::*;
+pub use render::*;
+pub use traits::*;
+
+/// Derive macro alternative to [`bit_register!`], see the `bit-register-derive` crate
+#[cfg(feature = "derive")]
+pub use bit_register_derive::BitRegister;
+
+// Re-export num_traits for use in the macro
+pub extern crate num_traits;
+
+// Re-export paste for generating type names in the macro
+#[doc(hidden)]
+pub extern crate paste;
+
+// Re-export serde for the implementations generated by the macro
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub extern crate serde;
+
+// Re-export defmt for the implementations generated by the macro
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+pub extern crate defmt;
+
+// Re-export ufmt for the implementations generated by the macro
+#[cfg(feature = "ufmt")]
+#[doc(hidden)]
+pub extern crate ufmt;
+
+/// Normalize the bits of a field, given in either order and counted from either end, to the
+/// `(lsb, msb)` counted from the least significant end. Used by the macro in `const` items, so
+/// bits which do not fit in the underlying type are a compile error.
+#[doc(hidden)]
+pub const fn __bit_range(bits: usize, msb0: bool, first: usize, last: usize) -> (usize, usize) {
+    let (low, high) = if first <= last {
+        (first, last)
+    } else {
+        (last, first)
+    };
+    if high >= bits {
+        panic!("field bits do not fit in the underlying type");
+    }
+    if msb0 {
+        (bits - 1 - high, bits - 1 - low)
+    } else {
+        (low, high)
+    }
+}
+
+/// A macro for defining registers with fields that map to specific bits in an underlying type.
+///
+/// The macro provides automatic conversion between the register types and their
+/// underlying bit representations, with range checking and error handling. Underlying types
+/// are an unsigned integer type.
+#[macro_export]
+macro_rules! bit_register {
+    // Entrypoint for types with attributes, which may include `#[bit_register(...)]` options
+    (#$attr:tt $($rest:tt)*) => {
+        bit_register!(@attrs [] [] #$attr $($rest)*);
+    };
+
+    // Collect `#[bit_register(...)]` options, keeping all other attributes in order
+    (@attrs [$($option:tt)*] [$($attr:tt)*] #[bit_register($($new_option:tt)*)] $($rest:tt)*) => {
+        bit_register!(@attrs [$($option)* $($new_option)*,] [$($attr)*] $($rest)*);
+    };
+    (@attrs [$($option:tt)*] [$($attr:tt)*] #$new_attr:tt $($rest:tt)*) => {
+        bit_register!(@attrs [$($option)*] [$($attr)* #$new_attr] $($rest)*);
+    };
+    (@attrs [$($option:tt)*] [$($attr:tt)*] $($rest:tt)*) => {
+        bit_register!(@options [$($option)*] $($attr)* $($rest)*);
+    };
+
+    // Define an enum type which can be used as a bit register
+    (
+        $(@options [$($option:tt)*])?
+        $(#$attr:tt)*
+        $vis:vis enum $name:ident: $repr_type:ty {
+            $(
+                $(#$variant_attr:tt)*
+                $variant:ident = $value:expr
+            ),+ $(,)?
+        }
+    ) => {
+        $(#$attr)*
+        #[repr($repr_type)]
+        $vis enum $name {
+            $(
+                $(#$variant_attr)*
+                $variant = $value,
+            )+
+        }
+
+        impl $crate::NumBytes for $name {
+            const NUM_BYTES: usize = <$repr_type as $crate::NumBytes>::NUM_BYTES;
+        }
+
+        impl<T: Copy + TryFrom<$repr_type>> $crate::TryIntoBits<T> for $name {
+            fn try_into_bits(self) -> Result<T, &'static str> {
+                // Convert enum to its underlying numeric type then to target type
+                (self as $repr_type).try_into_bits()
+            }
+        }
+
+        impl<T: Copy> $crate::TryFromBits<T> for $name where $repr_type: TryFrom<T> {
+            fn try_from_bits(bits: T) -> Result<Self, &'static str> {
+                // Convert the bits to the enum's representation type
+                let value = <$repr_type>::try_from_bits(bits)?;
+
+                // Match the numeric value to the corresponding enum variant
+                match value {
+                    $(
+                        $value => Ok(Self::$variant),
+                    )+
+                    _ => Err(concat!("Invalid value for enum ", stringify!($name))),
+                }
+            }
+        }
+
+        impl $crate::FieldType for $name {
+            const KIND: $crate::FieldKind = $crate::FieldKind::Enum(&[
+                $(
+                    $crate::VariantInfo {
+                        name: stringify!($variant),
+                        value: $value as u64,
+                        doc: bit_register!(@doc [] $(#$variant_attr)*),
+                    },
+                )+
+            ]);
+        }
+
+        // Not every enum uses its const conversions
+        #[allow(dead_code)]
+        impl $name {
+            /// Convert the variant to its underlying numeric value, usable in `const` contexts
+            pub const fn encode_const(&self) -> $repr_type {
+                match self {
+                    $(
+                        Self::$variant => $value,
+                    )+
+                }
+            }
+
+            /// Try to convert a numeric value to the corresponding variant, usable in `const` contexts
+            pub const fn try_decode_const(raw: $repr_type) -> Result<Self, &'static str> {
+                match raw {
+                    $(
+                        $value => Ok(Self::$variant),
+                    )+
+                    _ => Err(concat!("Invalid value for enum ", stringify!($name))),
+                }
+            }
+
+            /// Convert a numeric value to the corresponding variant, usable in `const` contexts
+            ///
+            /// Panics if the value does not match any variant, which is a compile error when
+            /// evaluated in a `const` context.
+            pub const fn decode_const(raw: $repr_type) -> Self {
+                match Self::try_decode_const(raw) {
+                    Ok(variant) => variant,
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        }
+
+        bit_register!(@check_options [$($($option)*)?]);
+
+        $crate::__bit_register_serde!(enum [$($($option)*)?] $name: $repr_type {
+            $($variant = $value),+
+        });
+
+        $crate::__bit_register_defmt!(enum [$($($option)*)?] $name: $repr_type {
+            $($variant),+
+        });
+
+        $crate::__bit_register_ufmt!(enum $name { $($variant),+ });
+    };
+
+    // Structs without attributes have no options
+    ($vis:vis struct $($rest:tt)*) => {
+        bit_register!(@options [] $vis struct $($rest)*);
+    };
+
+    // Define a struct type which can be used as a bit register
+    (
+        @options $options:tt
+        $(#$attr:tt)*
+        $vis:vis struct $name:ident: $underlying_type:ty {
+            $(
+                $(#$field_attr:tt)*
+                $field_vis:vis $field_name:ident: $($field_type:ident)::+ $(<$($field_param:ident)::+>)? => $field_bits:tt $(none $none:literal)? $(in $($valid_min:literal $(..= $valid_max:literal)?)|+)?
+                    $(table [$($table:literal),+ $(,)?])? $(scale $scale:literal $(offset $offset:literal)? $(unit $unit:literal)?)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#$attr)*
+        $vis struct $name {
+            $(
+                $(#$field_attr)*
+                $field_vis $field_name: $($field_type)::+ $(<$($field_param)::+>)?,
+            )*
+        }
+
+        impl $crate::NumBytes for $name {
+            const NUM_BYTES: usize = <$underlying_type as $crate::NumBytes>::NUM_BYTES;
+        }
+
+        impl TryFrom<$underlying_type> for $name {
+            type Error = &'static str;
+
+            fn try_from(value: $underlying_type) -> Result<Self, Self::Error> {
+                $(
+                    let $field_name = bit_register!(@extract_bits $underlying_type, $options, value, $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                )*
+
+                let register = Self {
+                    $(
+                        $field_name,
+                    )*
+                };
+                bit_register!(@validate $options, &register);
+                Ok(register)
+            }
+        }
+
+        impl TryInto<$underlying_type> for $name {
+            type Error = &'static str;
+
+            fn try_into(self) -> Result<$underlying_type, Self::Error> {
+                bit_register!(@validate $options, &self);
+                let mut value: $underlying_type = 0;
+                $(
+                    // Handle bit packing for each field
+                    value |= bit_register!(@pack_bits $underlying_type, $options, self.$field_name, $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                )*
+                Ok(value)
+            }
+        }
+
+        impl $crate::BitRegister<$underlying_type> for $name {}
+
+        bit_register!(@check_options $options);
+
+        $crate::__bit_register_serde!(struct $options $name: $underlying_type {
+            $($field_name: $($field_type)::+ $(<$($field_param)::+>)?),*
+        });
+
+        $crate::__bit_register_defmt!(struct $options $name: $underlying_type {
+            $($field_name: $($field_type)::+ $(<$($field_param)::+>)?),*
+        });
+
+        $crate::__bit_register_ufmt!(struct $name { $($field_name: $($field_type)::+ $(<$($field_param)::+>)?),* });
+
+        impl $crate::RegisterLayout for $name {
+            const LAYOUT: $crate::RegisterInfo = $crate::RegisterInfo {
+                name: stringify!($name),
+                bits: (<$underlying_type as $crate::NumBytes>::NUM_BYTES * 8) as u32,
+                fields: &[
+                    $(
+                        bit_register!(@field_info $underlying_type, $options, [$(#$field_attr)*] $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]),
+                    )*
+                ],
+                doc: bit_register!(@doc [] $(#$attr)*),
+            };
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Compare the fields of two register values
+            ///
+            /// The returned diff iterates over the fields which differ, and renders them with
+            /// `Display`. Fields are compared by their raw bits, so values which do not fit in
+            /// their field are still reported. Registers can only be compared if the types of
+            /// all their fields are `Clone`.
+            pub fn diff(&self, other: &Self) -> $crate::RegisterDiff<{ <$name as $crate::RegisterLayout>::LAYOUT.fields.len() }>
+            where
+                $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+            {
+                $crate::RegisterDiff::new(
+                    &<$name as $crate::RegisterLayout>::LAYOUT,
+                    [
+                        $(
+                            bit_register!(@raw_bits $underlying_type, $($field_type)::+, &self.$field_name, [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]).unwrap_or(u64::MAX),
+                        )*
+                    ],
+                    [
+                        $(
+                            bit_register!(@raw_bits $underlying_type, $($field_type)::+, &other.$field_name, [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]).unwrap_or(u64::MAX),
+                        )*
+                    ],
+                )
+            }
+        }
+
+        bit_register!(@if_option const, $options, {
+            #[allow(dead_code)]
+            impl $name {
+                /// Try to convert the register to its underlying value, usable in `const` contexts
+                pub const fn try_encode_const(&self) -> Result<$underlying_type, &'static str> {
+                    bit_register!(@validate $options, self);
+                    let mut value: $underlying_type = 0;
+                    $(
+                        value |= bit_register!(@const_pack_bits $underlying_type, $options, &self.$field_name, $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                    )*
+                    Ok(value)
+                }
+
+                /// Convert the register to its underlying value, usable in `const` contexts
+                ///
+                /// Panics if a field does not fit in its bits, which is a compile error when
+                /// evaluated in a `const` context.
+                pub const fn encode_const(&self) -> $underlying_type {
+                    match self.try_encode_const() {
+                        Ok(value) => value,
+                        Err(e) => panic!("{}", e),
+                    }
+                }
+
+                /// Try to convert an underlying value to the register, usable in `const` contexts
+                pub const fn try_decode_const(value: $underlying_type) -> Result<Self, &'static str> {
+                    $(
+                        let $field_name = bit_register!(@const_extract_bits $underlying_type, $options, value, $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                    )*
+
+                    let register = Self {
+                        $(
+                            $field_name,
+                        )*
+                    };
+                    bit_register!(@validate $options, &register);
+                    Ok(register)
+                }
+
+                /// Convert an underlying value to the register, usable in `const` contexts
+                ///
+                /// Panics if a field holds an invalid bit pattern, which is a compile error when
+                /// evaluated in a `const` context.
+                pub const fn decode_const(value: $underlying_type) -> Self {
+                    match Self::try_decode_const(value) {
+                        Ok(register) => register,
+                        Err(e) => panic!("{}", e),
+                    }
+                }
+            }
+        });
+
+        bit_register!(@if_option partial, $options, {
+            $crate::paste::paste! {
+                #[doc = concat!("A partial [`", stringify!($name), "`] where every field is optional, for masked writes")]
+                #[allow(dead_code)]
+                $vis struct [<Partial $name>] {
+                    $(
+                        #[doc = concat!("Value for `", stringify!($field_name), "`, left untouched when `None`")]
+                        $field_vis $field_name: Option<$($field_type)::+ $(<$($field_param)::+>)?>,
+                    )*
+                }
+
+                #[allow(dead_code)]
+                impl [<Partial $name>] {
+                    /// Create a partial register with no fields set
+                    pub const fn new() -> Self {
+                        Self {
+                            $(
+                                $field_name: None,
+                            )*
+                        }
+                    }
+
+                    $(
+                        #[doc = concat!("Set `", stringify!($field_name), "`")]
+                        pub const fn [<with_ $field_name>](mut self, value: $($field_type)::+ $(<$($field_param)::+>)?) -> Self {
+                            self.$field_name = Some(value);
+                            self
+                        }
+                    )*
+
+                    /// Try to convert the set fields to a `(mask, value)` pair in the underlying type
+                    ///
+                    /// The mask has the bits of every set field, and the value holds their packed
+                    /// values. Set fields are range checked like `TryInto`.
+                    pub fn try_mask_value(&self) -> Result<($underlying_type, $underlying_type), &'static str>
+                    where
+                        $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                    {
+                        let mut mask: $underlying_type = 0;
+                        let mut value: $underlying_type = 0;
+                        $(
+                            if let Some(field_value) = &self.$field_name {
+                                mask |= bit_register!(@field_mask $underlying_type, $options, $field_bits);
+                                value |= bit_register!(@pack_bits $underlying_type, $options, Clone::clone(field_value), $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                            }
+                        )*
+                        Ok((mask, value))
+                    }
+
+                    /// Try to apply the set fields to an existing underlying value, leaving the bits
+                    /// of unset fields untouched
+                    pub fn try_apply(&self, raw: $underlying_type) -> Result<$underlying_type, &'static str>
+                    where
+                        $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                    {
+                        let (mask, value) = self.try_mask_value()?;
+                        Ok((raw & !mask) | value)
+                    }
+                }
+
+                impl Default for [<Partial $name>] {
+                    fn default() -> Self {
+                        Self::new()
+                    }
+                }
+
+                impl From<$name> for [<Partial $name>] {
+                    fn from(register: $name) -> Self {
+                        Self {
+                            $(
+                                $field_name: Some(register.$field_name),
+                            )*
+                        }
+                    }
+                }
+
+                // The field types are only known to be `Clone`, `Copy`, `Debug` or `PartialEq` where
+                // the partial register is used, so these are derived by hand with higher-ranked
+                // bounds
+                #[automatically_derived]
+                impl Clone for [<Partial $name>]
+                where
+                    $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                {
+                    fn clone(&self) -> Self {
+                        Self {
+                            $(
+                                $field_name: Clone::clone(&self.$field_name),
+                            )*
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl Copy for [<Partial $name>]
+                where
+                    $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: Copy,)*
+                {
+                }
+
+                #[automatically_derived]
+                impl core::fmt::Debug for [<Partial $name>]
+                where
+                    $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: core::fmt::Debug,)*
+                {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        f.debug_struct(concat!("Partial", stringify!($name)))
+                            $(
+                                .field(stringify!($field_name), &self.$field_name)
+                            )*
+                            .finish()
+                    }
+                }
+
+                #[automatically_derived]
+                impl PartialEq for [<Partial $name>]
+                where
+                    $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: PartialEq,)*
+                {
+                    fn eq(&self, other: &Self) -> bool {
+                        true $(&& self.$field_name == other.$field_name)*
+                    }
+                }
+            }
+        });
+
+        bit_register!(@if_option pattern, $options, {
+            $crate::paste::paste! {
+
+                #[doc = concat!("A pattern matching raw [`", stringify!($name), "`] values with per-field constraints")]
+                #[allow(dead_code)]
+                $vis struct [<$name Pattern>]<'a> {
+                    $(
+                        $field_name: $crate::FieldPattern<'a, $($field_type)::+ $(<$($field_param)::+>)?>,
+                    )*
+                    _values: core::marker::PhantomData<&'a ()>,
+                }
+
+                #[allow(dead_code)]
+                impl<'a> [<$name Pattern>]<'a> {
+                    /// Create a pattern which matches any value
+                    pub const fn new() -> Self {
+                        Self {
+                            $(
+                                $field_name: $crate::FieldPattern::Any,
+                            )*
+                            _values: core::marker::PhantomData,
+                        }
+                    }
+
+                    $(
+                        #[doc = concat!("Require `", stringify!($field_name), "` to have exactly this value")]
+                        pub const fn [<with_ $field_name>](mut self, value: $($field_type)::+ $(<$($field_param)::+>)?) -> Self {
+                            self.$field_name = $crate::FieldPattern::Exactly(value);
+                            self
+                        }
+
+                        #[doc = concat!("Require `", stringify!($field_name), "` to have any of these values")]
+                        pub const fn [<with_ $field_name _any_of>](mut self, values: &'a [$($field_type)::+ $(<$($field_param)::+>)?]) -> Self {
+                            self.$field_name = $crate::FieldPattern::AnyOf(values);
+                            self
+                        }
+                    )*
+
+                    /// Try to convert the pattern to a `(mask, expected)` pair in the underlying type
+                    ///
+                    /// A raw value matches when `raw & mask == expected`. Fails if an exact value does
+                    /// not fit in its field, or if a field has an any-of constraint which cannot be
+                    /// expressed as a single mask.
+                    pub fn try_mask_expected(&self) -> Result<($underlying_type, $underlying_type), &'static str>
+                    where
+                        $(for<'b> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                    {
+                        let mut mask: $underlying_type = 0;
+                        let mut expected: $underlying_type = 0;
+                        $(
+                            match &self.$field_name {
+                                $crate::FieldPattern::Any => {}
+                                $crate::FieldPattern::Exactly(field_value) => {
+                                    mask |= bit_register!(@field_mask $underlying_type, $options, $field_bits);
+                                    expected |= bit_register!(@pack_bits $underlying_type, $options, Clone::clone(field_value), $field_name, $($field_type)::+, $field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                                }
+                                $crate::FieldPattern::AnyOf(_) => {
+                                    return Err(concat!(stringify!($field_name), " has an any-of constraint which cannot be expressed as a mask"));
+                                }
+                            }
+                        )*
+                        Ok((mask, expected))
+                    }
+
+                    /// Check whether a raw value satisfies every field constraint
+                    ///
+                    /// Values which do not fit in their field, or are not among its valid values, never
+                    /// match.
+                    pub fn matches(&self, raw: $underlying_type) -> bool
+                    where
+                        $(for<'b> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                    {
+                        $(
+                            let field_bits = bit_register!(@field_bits $underlying_type, $options, raw, $field_bits);
+                            let field_valid = bit_register!(@field_is_valid field_bits, [$($($valid_min $(..= $valid_max)?)|+)?], [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]);
+                            let field_matches = match &self.$field_name {
+                                $crate::FieldPattern::Any => true,
+                                $crate::FieldPattern::Exactly(field_value) => {
+                                    field_valid && bit_register!(@raw_bits $underlying_type, $($field_type)::+, field_value, [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]) == Some(field_bits)
+                                }
+                                $crate::FieldPattern::AnyOf(field_values) => field_valid && field_values.iter().any(|field_value| {
+                                    bit_register!(@raw_bits $underlying_type, $($field_type)::+, field_value, [$(<$($field_param)::+>)? $(none $none)? $(table [$($table),+])? $($scale $(offset $offset)? $(unit $unit)?)?]) == Some(field_bits)
+                                }),
+                            };
+                            if !field_matches {
+                                return false;
+                            }
+                        )*
+                        true
+                    }
+                }
+
+                impl Default for [<$name Pattern>]<'_> {
+                    fn default() -> Self {
+                        Self::new()
+                    }
+                }
+
+                // As for partial registers, the field types are only known to be `Clone`, `Copy` or
+                // `Debug` where the pattern is used
+                #[automatically_derived]
+                impl Clone for [<$name Pattern>]<'_>
+                where
+                    $(for<'b> $($field_type)::+ $(<$($field_param)::+>)?: Clone,)*
+                {
+                    fn clone(&self) -> Self {
+                        Self {
+                            $(
+                                $field_name: Clone::clone(&self.$field_name),
+                            )*
+                            _values: core::marker::PhantomData,
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl Copy for [<$name Pattern>]<'_>
+                where
+                    $(for<'b> $($field_type)::+ $(<$($field_param)::+>)?: Copy,)*
+                {
+                }
+
+                #[automatically_derived]
+                impl core::fmt::Debug for [<$name Pattern>]<'_>
+                where
+                    $(for<'b> $($field_type)::+ $(<$($field_param)::+>)?: core::fmt::Debug,)*
+                {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        f.debug_struct(concat!(stringify!($name), "Pattern"))
+                            $(
+                                .field(stringify!($field_name), &self.$field_name)
+                            )*
+                            .finish()
+                    }
+                }
+            }
+        });
+    };
+
+    // Options given in `#[bit_register(...)]` attributes, separated by commas. The brackets
+    // after `@check_option` record whether `bit_order` and `validate` have been given.
+    (@check_options [$($option:tt)*]) => {
+        bit_register!(@check_option [] [] $($option)*);
+    };
+    (@check_option $order:tt $check:tt $(,)?) => {};
+    (@check_option $order:tt $check:tt serde = raw $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order $check $($($rest)*)?);
+    };
+    (@check_option $order:tt $check:tt defmt = raw $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order $check $($($rest)*)?);
+    };
+    (@check_option [bit_order] $check:tt bit_order = $value:ident $(, $($rest:tt)*)?) => {
+        compile_error!("bit_order is given more than once");
+    };
+    (@check_option [] $check:tt bit_order = msb0 $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option [bit_order] $check $($($rest)*)?);
+    };
+    (@check_option [] $check:tt bit_order = lsb0 $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option [bit_order] $check $($($rest)*)?);
+    };
+    (@check_option $order:tt [validate] validate = $validate:path $(, $($rest:tt)*)?) => {
+        compile_error!("validate is given more than once");
+    };
+    (@check_option $order:tt [] validate = $validate:path $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order [validate] $($($rest)*)?);
+    };
+    (@check_option $order:tt $check:tt const $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order $check $($($rest)*)?);
+    };
+    (@check_option $order:tt $check:tt partial $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order $check $($($rest)*)?);
+    };
+    (@check_option $order:tt $check:tt pattern $(, $($rest:tt)*)?) => {
+        bit_register!(@check_option $order $check $($($rest)*)?);
+    };
+    (@check_option $order:tt $check:tt $($option:tt)*) => {
+        compile_error!(concat!("unknown bit_register option: ", stringify!($($option)*)));
+    };
+
+    // Whether the options number bits from the most significant end
+    (@msb0 [bit_order = msb0 $($rest:tt)*]) => {
+        true
+    };
+    (@msb0 [$skip:tt $($rest:tt)*]) => {
+        bit_register!(@msb0 [$($rest)*])
+    };
+    (@msb0 []) => {
+        false
+    };
+
+    // Run the `validate` option on a register, returning its error
+    (@validate [validate = $validate:path $(, $($rest:tt)*)?], $register:expr) => {
+        match $validate($register) {
+            Ok(()) => {}
+            Err(e) => return Err(e),
+        }
+    };
+    (@validate [$skip:tt $($rest:tt)*], $register:expr) => {
+        bit_register!(@validate [$($rest)*], $register)
+    };
+    (@validate [], $register:expr) => {};
+
+    // Expand the items in braces if an option without a value is given. Options are skipped
+    // whole, so a `validate` function named like an option does not enable it.
+    (@if_option const, [const, $($rest:tt)*], { $($item:tt)* }) => {
+        $($item)*
+    };
+    (@if_option partial, [partial, $($rest:tt)*], { $($item:tt)* }) => {
+        $($item)*
+    };
+    (@if_option pattern, [pattern, $($rest:tt)*], { $($item:tt)* }) => {
+        $($item)*
+    };
+    (@if_option $option:tt, [$skip:ident = $value:path, $($rest:tt)*], $items:tt) => {
+        bit_register!(@if_option $option, [$($rest)*], $items);
+    };
+    (@if_option $option:tt, [$skip:tt, $($rest:tt)*], $items:tt) => {
+        bit_register!(@if_option $option, [$($rest)*], $items);
+    };
+    (@if_option $option:tt, [$(,)*], $items:tt) => {};
+
+    // The `(lsb, msb)` of a single bit, counted from the least significant end
+    (@range $underlying_type:ty, $options:tt, [$bit:literal]) => {
+        bit_register!(@range $underlying_type, $options, [$bit:$bit])
+    };
+
+    // The `(lsb, msb)` of a range of bits in either order, counted from the least significant end
+    (@range $underlying_type:ty, $options:tt, [$first:literal:$last:literal]) => {
+        $crate::__bit_range(
+            <$underlying_type as $crate::NumBytes>::NUM_BYTES * 8,
+            bit_register!(@msb0 $options),
+            $first,
+            $last,
+        )
+    };
+
+    // Fields with a `none` value, a lookup table or a scale are converted from their raw bits
+    (@extract_bits $underlying_type:ty, $options:tt, $value:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, [$($scale:tt)+]) => {
+        {
+            let extracted_value = bit_register!(@field_bits $underlying_type, $options, $value, $field_bits) as $underlying_type;
+            bit_register!(@check_field_valid $field_name, extracted_value, $valid, [$($scale)+]);
+            bit_register!(@from_raw $field_name, $($field_type)::+, extracted_value, [$($scale)+])
+        }
+    };
+
+    // Extract the bits of a field and convert them to the field type
+    (@extract_bits $underlying_type:ty, $options:tt, $value:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, []) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+
+            // Calculate how many bits are in this field
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+
+            // Create a mask with BIT_COUNT number of 1s
+            // Handle the case where BIT_COUNT is the full width of the underlying type
+            let mask: $underlying_type = if BIT_COUNT >= (<$underlying_type as $crate::NumBytes>::NUM_BYTES * 8) {
+                <$underlying_type>::MAX
+            } else {
+                ((1 as $underlying_type) << BIT_COUNT) - 1
+            };
+
+            // Extract the relevant bits by right-shifting to the start position
+            // and then masking to keep only the bits we want
+            let extracted_value = ($value >> RANGE.0) & mask;
+            bit_register!(@check_valid $field_name, extracted_value, $valid);
+
+            // Convert the extracted bits to the field type
+            $crate::TryFromBits::try_from_bits(extracted_value)?
+        }
+    };
+
+    // Fields with a `none` value, a lookup table or a scale are converted to their raw bits
+    (@pack_bits $underlying_type:ty, $options:tt, $field_value:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, [$($scale:tt)+]) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+
+            let max_value: u64 = if BIT_COUNT >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << BIT_COUNT) - 1
+            };
+
+            let field_value: u64 = bit_register!(@to_raw $underlying_type, $field_name, $($field_type)::+, $field_value, max_value, [$($scale)+]);
+            bit_register!(@check_field_valid $field_name, field_value, $valid, [$($scale)+]);
+
+            (field_value as $underlying_type) << RANGE.0
+        }
+    };
+
+    // Pack the bits of a field
+    (@pack_bits $underlying_type:ty, $options:tt, $field_value:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, []) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+
+            // Calculate how many bits are needed for this field
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+            const FIELD_TYPE_BITS: usize = <$($field_type)::+ as $crate::NumBytes>::NUM_BYTES * 8;
+
+            // Calculate the maximum value that can fit in the bit field
+            // We need to handle this carefully to avoid overflow
+            let max_value = if BIT_COUNT >= 64 {
+                u64::MAX // Special case for fields that use all available bits
+            } else {
+                (1u64 << BIT_COUNT) - 1 // 2^BIT_COUNT - 1
+            };
+
+            let field_value: $underlying_type = $crate::TryIntoBits::try_into_bits($field_value)?;
+
+            // Check if the value fits in the allocated bits
+            // We skip this check if the field type uses fewer or equal bits than we've allocated
+            if BIT_COUNT < FIELD_TYPE_BITS {
+                if field_value as u64 > max_value {
+                    return Err(concat!(stringify!($field_name), " exceeds maximum value for its bit width"));
+                }
+            }
+            bit_register!(@check_valid $field_name, field_value, $valid);
+
+            // Create a mask for the field value before shifting it into position
+            let field_mask = if BIT_COUNT >= FIELD_TYPE_BITS {
+                <$underlying_type>::MAX // Use all bits if BIT_COUNT exceeds underlying type size
+            } else {
+                ((1 as $underlying_type) << BIT_COUNT) - 1 // Create a mask of BIT_COUNT 1s
+            };
+
+            // Mask the value and shift it to the correct position
+            ((field_value) & field_mask) << RANGE.0
+        }
+    };
+
+    // Const extraction of the bits of a field, widened to u64 before converting to the field type
+    (@const_extract_bits $underlying_type:ty, $options:tt, $value:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, $scale:tt) => {
+        {
+            let extracted_value = bit_register!(@field_bits $underlying_type, $options, $value, $field_bits);
+            bit_register!(@check_field_valid $field_name, extracted_value, $valid, $scale);
+
+            match bit_register!(@const_from_raw $field_name, $($field_type)::+, extracted_value, $scale) {
+                Ok(field_value) => field_value,
+                Err(e) => return Err(e),
+            }
+        }
+    };
+
+    // Raw bits of a field, widened to u64
+    (@field_bits $underlying_type:ty, $options:tt, $value:expr, $field_bits:tt) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+
+            let mask: u64 = if BIT_COUNT >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << BIT_COUNT) - 1
+            };
+
+            (($value >> RANGE.0) as u64) & mask
+        }
+    };
+
+    // Const packing of the bits of a field, the field value is widened to u64 for the range check
+    (@const_pack_bits $underlying_type:ty, $options:tt, $field_ref:expr, $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, $scale:tt) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+
+            let max_value: u64 = if BIT_COUNT >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << BIT_COUNT) - 1
+            };
+
+            let field_value: u64 = bit_register!(@const_to_raw $field_name, $($field_type)::+, $field_ref, max_value, $scale);
+            bit_register!(@check_field_valid $field_name, field_value, $valid, $scale);
+
+            (field_value as $underlying_type) << RANGE.0
+        }
+    };
+
+    // Description of the bits of a field, which are always counted from the least significant end
+    (@field_info $underlying_type:ty, $options:tt, [$($attr:tt)*] $field_name:ident, $($field_type:ident)::+, $field_bits:tt, $valid:tt, $scale:tt) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+            const KIND: $crate::FieldKind = bit_register!(@field_kind $($field_type)::+, $scale);
+            const _: () = assert!(
+                KIND.fits_width((RANGE.1 - RANGE.0 + 1) as u32),
+                concat!(stringify!($field_name), " must be exactly as wide as its fixed-point type")
+            );
+
+            $crate::FieldInfo {
+                name: stringify!($field_name),
+                lsb: RANGE.0 as u32,
+                msb: RANGE.1 as u32,
+                kind: KIND,
+                valid: bit_register!(@valid_ranges $valid),
+                scale: bit_register!(@field_scale $scale),
+                none: bit_register!(@field_none RANGE, $scale),
+                table: bit_register!(@field_table RANGE, $scale),
+                doc: bit_register!(@doc [] $($attr)*),
+            }
+        }
+    };
+
+    // Return an error if the raw bits of a field are not among its valid values
+    (@check_valid $field_name:ident, $raw:expr, []) => {};
+    (@check_valid $field_name:ident, $raw:expr, [$first_min:literal $(..= $first_max:literal)? $(| $min:literal $(..= $max:literal)?)*]) => {
+        if !bit_register!(@is_valid $raw, [$first_min $(..= $first_max)? $(| $min $(..= $max)?)*]) {
+            return Err(concat!(
+                stringify!($field_name),
+                " is not in ",
+                stringify!($first_min),
+                $("..=", stringify!($first_max),)?
+                $(" | ", stringify!($min), $("..=", stringify!($max),)?)*
+            ));
+        }
+    };
+
+    // Valid values only restrict the raw bits of `Some` values in `Option` fields
+    (@check_field_valid $field_name:ident, $raw:expr, [], $scale:tt) => {};
+    (@check_field_valid $field_name:ident, $raw:expr, $valid:tt, [<$($inner:ident)::+> none $none:literal]) => {
+        if $raw != $none {
+            bit_register!(@check_valid $field_name, $raw, $valid);
+        }
+    };
+    (@check_field_valid $field_name:ident, $raw:expr, $valid:tt, $scale:tt) => {
+        bit_register!(@check_valid $field_name, $raw, $valid);
+    };
+
+    // Whether the raw bits of a field are among its valid values, as a `bool`
+    (@is_valid $raw:expr, []) => {
+        true
+    };
+    (@is_valid $raw:expr, [$($min:literal $(..= $max:literal)?)|+]) => {
+        matches!($raw, $($min $(..= $max)?)|+)
+    };
+    (@field_is_valid $raw:expr, $valid:tt, [<$($inner:ident)::+> none $none:literal]) => {
+        $raw == $none || bit_register!(@is_valid $raw, $valid)
+    };
+    (@field_is_valid $raw:expr, $valid:tt, $scale:tt) => {
+        bit_register!(@is_valid $raw, $valid)
+    };
+
+    // Valid values of a field as inclusive ranges, where no ranges allow any value
+    (@valid_ranges [$($min:literal $(..= $max:literal)?)|*]) => {
+        &[
+            $(
+                $crate::ValueRange {
+                    min: $min,
+                    max: bit_register!(@first $($max)? $min),
+                },
+            )*
+        ]
+    };
+    (@first $value:literal $($rest:tt)*) => {
+        $value
+    };
+
+    // Scaled fields and fields with a lookup table hold a value computed from unsigned raw
+    // bits, and `Option` fields
+    // are described by the type of their `Some` values. Misused `Option` and `none` are
+    // reported here, as every field expands this rule with its type as written.
+    (@field_kind $($field_type:ident)::+, []) => {{
+        #[allow(unused_imports)]
+        use $crate::__FieldKindFallback as _;
+        $crate::__FieldKindOf::<$($field_type)::+>::KIND
+    }};
+    (@field_kind Option, [<$($inner:ident)::+> none $none:literal]) => {{
+        #[allow(unused_imports)]
+        use $crate::__FieldKindFallback as _;
+        $crate::__FieldKindOf::<$($inner)::+>::KIND
+    }};
+    (@field_kind Option, [<$($inner:ident)::+>]) => {
+        compile_error!("`Option` fields need a `none` value, such as `none 0xff`")
+    };
+    (@field_kind Option, [<$($inner:ident)::+> none $none:literal table $($rest:tt)*]) => {
+        compile_error!("`Option` fields cannot have a lookup table")
+    };
+    (@field_kind Option, [<$($inner:ident)::+> none $none:literal $($scale:tt)+]) => {
+        compile_error!("`Option` fields cannot be scaled")
+    };
+    (@field_kind $($field_type:ident)::+, [<$($param:tt)*]) => {
+        compile_error!("field types with generic parameters need a type alias, except `Option`")
+    };
+    (@field_kind $($field_type:ident)::+, [none $($rest:tt)*]) => {
+        compile_error!("only `Option` fields can have a `none` value")
+    };
+    (@field_kind $($field_type:ident)::+, [table $table:tt $($scale:tt)+]) => {
+        compile_error!("fields with a lookup table cannot be scaled")
+    };
+    (@field_kind $($field_type:ident)::+, $scale:tt) => {
+        $crate::FieldKind::Unsigned
+    };
+
+    // Raw value of `None` in an `Option` field, which must fit in the field's bits
+    (@field_none $range:ident, [<$($inner:ident)::+> none $none:literal]) => {
+        {
+            const NONE: u64 = $none;
+            const _: () = assert!(
+                $range.1 - $range.0 >= 63 || NONE >> ($range.1 - $range.0 + 1) == 0,
+                "the none value of a field must fit in its bits"
+            );
+            Some(NONE)
+        }
+    };
+    (@field_none $range:ident, $scale:tt) => {
+        None
+    };
+
+    // Values of the raw bits of a field with a lookup table, which must fit in the field's bits
+    (@field_table $range:ident, [table [$($table:literal),+]]) => {
+        {
+            // Every `u64` and `i64` value fits in an `i128`
+            const TABLE: &[i128] = &[$($table as i128),+];
+            const _: () = assert!(
+                $range.1 - $range.0 >= 63 || TABLE.len() as u64 <= 1 << ($range.1 - $range.0 + 1),
+                "the lookup table of a field must fit in its bits"
+            );
+            TABLE
+        }
+    };
+    (@field_table $range:ident, $scale:tt) => {
+        &[]
+    };
+    (@table $($field_type:ident)::+, [$($table:literal),+]) => {
+        {
+            const TABLE: &[$($field_type)::+] = &[$($table),+];
+            TABLE
+        }
+    };
+
+    // Index of a value in a lookup table, or the length of the table if it is not there
+    (@table_index $($field_type:ident)::+, $field_ref:expr, $table:tt) => {
+        {
+            let table = bit_register!(@table $($field_type)::+, $table);
+            let value: $($field_type)::+ = *$field_ref;
+            let mut index = 0;
+            while index < table.len() && table[index] != value {
+                index += 1;
+            }
+            index as u64
+        }
+    };
+
+    // Description of the scale of a field, if it has one
+    (@field_scale []) => {
+        None
+    };
+    (@field_scale [table $($rest:tt)*]) => {
+        None
+    };
+    (@field_scale [<$($option:tt)*]) => {
+        None
+    };
+    (@field_scale [none $($rest:tt)*]) => {
+        None
+    };
+    (@field_scale $scale:tt) => {
+        Some(bit_register!(@scale $scale))
+    };
+    (@scale [$scale:literal $(offset $offset:literal)? $(unit $unit:literal)?]) => {
+        {
+            const SCALE: $crate::FieldScale = $crate::FieldScale {
+                scale: $scale,
+                offset: bit_register!(@first $($offset)? 0),
+                unit: bit_register!(@first $($unit)? ""),
+            };
+            const _: () = assert!(SCALE.scale != 0, "the scale of a field must not be zero");
+            SCALE
+        }
+    };
+
+    // Conversion of the raw bits of a field with a `none` value, a lookup table or a scale to
+    // the field type, returning its error
+    (@from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, [<$($inner:ident)::+> none $none:literal]) => {
+        if $raw == $none {
+            None
+        } else {
+            Some($crate::TryFromBits::try_from_bits($raw)?)
+        }
+    };
+    (@from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, [table $table:tt]) => {
+        match bit_register!(@table $($field_type)::+, $table).get($raw as usize) {
+            Some(field_value) => *field_value,
+            None => return Err(concat!(stringify!($field_name), " is not an index of its lookup table")),
+        }
+    };
+    (@from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, $scale:tt) => {
+        match <$($field_type)::+ as $crate::ScaledType>::from_physical(bit_register!(@scale $scale).physical($raw as u64)) {
+            Some(field_value) => field_value,
+            None => return Err(concat!(stringify!($field_name), " does not fit in ", stringify!($($field_type)::+))),
+        }
+    };
+
+    // Conversion of a field with a `none` value, a lookup table or a scale to raw bits, returning
+    // an error if they exceed `max_value`. Scaled fields are rounded to the nearest step, and
+    // `Some` values of `Option` fields must not collide with the raw value of `None`.
+    (@to_raw $underlying_type:ty, $field_name:ident, $($field_type:ident)::+, $field_value:expr, $max_value:expr, [<$($inner:ident)::+> none $none:literal]) => {
+        match $field_value {
+            None => $none,
+            Some(field_value) => {
+                let field_value: $underlying_type = $crate::TryIntoBits::try_into_bits(field_value)?;
+                if field_value as u64 > $max_value {
+                    return Err(concat!(stringify!($field_name), " exceeds maximum value for its bit width"));
+                }
+                if field_value as u64 == $none {
+                    return Err(concat!(stringify!($field_name), " is the raw value reserved for None"));
+                }
+                field_value as u64
+            }
+        }
+    };
+    (@to_raw $underlying_type:ty, $field_name:ident, $($field_type:ident)::+, $field_value:expr, $max_value:expr, [table $table:tt]) => {
+        {
+            // The table fits in the field's bits, so every index of it does too
+            let _ = $max_value;
+            let field_value: $($field_type)::+ = $field_value;
+            match bit_register!(@table $($field_type)::+, $table).iter().position(|value| *value == field_value) {
+                Some(index) => index as u64,
+                None => return Err(concat!(stringify!($field_name), " is not in its lookup table")),
+            }
+        }
+    };
+    (@to_raw $underlying_type:ty, $field_name:ident, $($field_type:ident)::+, $field_value:expr, $max_value:expr, $scale:tt) => {
+        {
+            let raw = bit_register!(@scale $scale).raw(<$($field_type)::+ as $crate::ScaledType>::to_physical($field_value));
+            if raw < 0 || raw > $max_value as i128 {
+                return Err(concat!(stringify!($field_name), " is out of range for its scale and bit width"));
+            }
+            raw as u64
+        }
+    };
+
+    // Raw bits of a copy of a field without range checks, for comparisons, or `None` for values
+    // which have no raw bits: those which cannot be converted to the underlying type at all, and
+    // `Some` values which would be read back as `None`, and values missing from a lookup table.
+    (@raw_bits $underlying_type:ty, $($field_type:ident)::+, $field_ref:expr, []) => {
+        match $crate::TryIntoBits::<$underlying_type>::try_into_bits(Clone::clone($field_ref)) {
+            Ok(bits) => Some(bits as u64),
+            Err(_) => None,
+        }
+    };
+    (@raw_bits $underlying_type:ty, $($field_type:ident)::+, $field_ref:expr, [<$($inner:ident)::+> none $none:literal]) => {
+        match $field_ref {
+            None => Some($none),
+            Some(field_value) => match bit_register!(@raw_bits $underlying_type, $($inner)::+, field_value, []) {
+                Some($none) => None,
+                bits => bits,
+            },
+        }
+    };
+    (@raw_bits $underlying_type:ty, $($field_type:ident)::+, $field_ref:expr, [table $table:tt]) => {
+        {
+            let table = bit_register!(@table $($field_type)::+, $table);
+            let field_value: &$($field_type)::+ = $field_ref;
+            table.iter().position(|value| value == field_value).map(|index| index as u64)
+        }
+    };
+    (@raw_bits $underlying_type:ty, $($field_type:ident)::+, $field_ref:expr, $scale:tt) => {
+        Some(bit_register!(@scale $scale).raw(<$($field_type)::+ as $crate::ScaledType>::to_physical(Clone::clone($field_ref))) as u64)
+    };
+
+    // Try to encode copies of the fields of a register reference, for implementations which
+    // cannot consume the register
+    (@try_encode_ref $name:ident, $underlying_type:ty, $register:expr, [$($field_name:ident),*]) => {
+        TryInto::<$underlying_type>::try_into($name {
+            $(
+                $field_name: Clone::clone(&$register.$field_name),
+            )*
+        })
+    };
+
+    // Const conversion of the raw bits of a field to the field type, returning a `Result`
+    (@const_from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, []) => {
+        bit_register!(@const_from_bits $($field_type)::+, $raw)
+    };
+    (@const_from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, [<$($inner:ident)::+> none $none:literal]) => {
+        {
+            let raw: u64 = $raw;
+            if raw == $none {
+                Ok(None)
+            } else {
+                match bit_register!(@const_from_bits $($inner)::+, raw) {
+                    Ok(value) => Ok(Some(value)),
+                    Err(e) => Err(e),
+                }
+            }
+        }
+    };
+    (@const_from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, [table $table:tt]) => {
+        {
+            let table = bit_register!(@table $($field_type)::+, $table);
+            let raw: u64 = $raw;
+            if raw < table.len() as u64 {
+                Ok(table[raw as usize])
+            } else {
+                Err(concat!(stringify!($field_name), " is not an index of its lookup table"))
+            }
+        }
+    };
+    (@const_from_raw $field_name:ident, $($field_type:ident)::+, $raw:expr, $scale:tt) => {
+        {
+            let physical = bit_register!(@scale $scale).physical($raw);
+            if physical < <$($field_type)::+ as $crate::ScaledType>::MIN || physical > <$($field_type)::+ as $crate::ScaledType>::MAX {
+                Err(concat!(stringify!($field_name), " does not fit in ", stringify!($($field_type)::+)))
+            } else {
+                Ok(physical as $($field_type)::+)
+            }
+        }
+    };
+
+    // Const conversion of a field reference to raw bits, returning an error if they exceed
+    // `max_value`. Scaled fields are rounded to the nearest step, fields with a lookup table
+    // are looked up in it, and `Some` values of `Option` fields must not collide with the raw
+    // value of `None`.
+    (@const_to_raw $field_name:ident, $($field_type:ident)::+, $field_ref:expr, $max_value:expr, []) => {
+        {
+            let field_value: u64 = bit_register!(@const_into_bits $($field_type)::+, $field_ref);
+            if field_value > $max_value {
+                return Err(concat!(stringify!($field_name), " exceeds maximum value for its bit width"));
+            }
+            field_value
+        }
+    };
+    (@const_to_raw $field_name:ident, $($field_type:ident)::+, $field_ref:expr, $max_value:expr, [<$($inner:ident)::+> none $none:literal]) => {
+        match $field_ref {
+            None => $none,
+            Some(field_value) => {
+                let field_value: u64 = bit_register!(@const_into_bits $($inner)::+, field_value);
+                if field_value > $max_value {
+                    return Err(concat!(stringify!($field_name), " exceeds maximum value for its bit width"));
+                }
+                if field_value == $none {
+                    return Err(concat!(stringify!($field_name), " is the raw value reserved for None"));
+                }
+                field_value
+            }
+        }
+    };
+    (@const_to_raw $field_name:ident, $($field_type:ident)::+, $field_ref:expr, $max_value:expr, [table $table:tt]) => {
+        {
+            // The table fits in the field's bits, so every index of it does too
+            let _ = $max_value;
+            let index = bit_register!(@table_index $($field_type)::+, $field_ref, $table);
+            if index >= bit_register!(@table $($field_type)::+, $table).len() as u64 {
+                return Err(concat!(stringify!($field_name), " is not in its lookup table"));
+            }
+            index
+        }
+    };
+    (@const_to_raw $field_name:ident, $($field_type:ident)::+, $field_ref:expr, $max_value:expr, $scale:tt) => {
+        {
+            let raw = bit_register!(@scale $scale).raw(*$field_ref as i128);
+            if raw < 0 || raw > $max_value as i128 {
+                return Err(concat!(stringify!($field_name), " is out of range for its scale and bit width"));
+            }
+            raw as u64
+        }
+    };
+
+    // Collect the lines of `#[doc]` attributes, skipping all other attributes
+    (@doc [$($line:expr,)*]) => {
+        &[$($line),*]
+    };
+    (@doc [$($line:expr,)*] #[doc = $new_line:expr] $($rest:tt)*) => {
+        bit_register!(@doc [$($line,)* $new_line,] $($rest)*)
+    };
+    (@doc [$($line:expr,)*] #$attr:tt $($rest:tt)*) => {
+        bit_register!(@doc [$($line,)*] $($rest)*)
+    };
+
+    // Mask of the bits of a field in the underlying type
+    (@field_mask $underlying_type:ty, $options:tt, $field_bits:tt) => {
+        {
+            const RANGE: (usize, usize) = bit_register!(@range $underlying_type, $options, $field_bits);
+            const BIT_COUNT: usize = (RANGE.1 - RANGE.0) + 1;
+
+            let mask: u64 = if BIT_COUNT >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << BIT_COUNT) - 1
+            };
+
+            (mask << RANGE.0) as $underlying_type
+        }
+    };
+
+    // Const conversion of a field reference to u64, primitives are cast directly while any
+    // other type must provide `encode_const` like the enums generated by this macro. Tokens
+    // are matched rather than types, so `NonZero` integers are only recognised by their name or
+    // their `core::num` or `std::num` path, not through an alias
+    (@const_into_bits bool, $field_ref:expr) => { *$field_ref as u64 };
+    (@const_into_bits u8, $field_ref:expr) => { *$field_ref as u64 };
+    (@const_into_bits u16, $field_ref:expr) => { *$field_ref as u64 };
+    (@const_into_bits u32, $field_ref:expr) => { *$field_ref as u64 };
+    (@const_into_bits u64, $field_ref:expr) => { *$field_ref };
+    (@const_into_bits $(core::num::)? $(std::num::)? NonZeroU8, $field_ref:expr) => { $field_ref.get() as u64 };
+    (@const_into_bits $(core::num::)? $(std::num::)? NonZeroU16, $field_ref:expr) => { $field_ref.get() as u64 };
+    (@const_into_bits $(core::num::)? $(std::num::)? NonZeroU32, $field_ref:expr) => { $field_ref.get() as u64 };
+    (@const_into_bits $(core::num::)? $(std::num::)? NonZeroU64, $field_ref:expr) => { $field_ref.get() };
+    (@const_into_bits $($field_type:ident)::+, $field_ref:expr) => { <$($field_type)::+>::encode_const($field_ref) as u64 };
+
+    // Const conversion of u64 bits to a field type, mirroring the `TryFromBits` impls
+    (@const_from_bits bool, $bits:expr) => {
+        match $bits {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err("bit pattern too large for target type bool"),
+        }
+    };
+    (@const_from_bits u8, $bits:expr) => { bit_register!(@const_from_bits_uint u8, $bits) };
+    (@const_from_bits u16, $bits:expr) => { bit_register!(@const_from_bits_uint u16, $bits) };
+    (@const_from_bits u32, $bits:expr) => { bit_register!(@const_from_bits_uint u32, $bits) };
+    (@const_from_bits u64, $bits:expr) => { Ok::<u64, &'static str>($bits) };
+    (@const_from_bits $(core::num::)? $(std::num::)? NonZeroU8, $bits:expr) => { bit_register!(@const_from_bits_non_zero NonZeroU8, u8, $bits) };
+    (@const_from_bits $(core::num::)? $(std::num::)? NonZeroU16, $bits:expr) => { bit_register!(@const_from_bits_non_zero NonZeroU16, u16, $bits) };
+    (@const_from_bits $(core::num::)? $(std::num::)? NonZeroU32, $bits:expr) => { bit_register!(@const_from_bits_non_zero NonZeroU32, u32, $bits) };
+    (@const_from_bits $(core::num::)? $(std::num::)? NonZeroU64, $bits:expr) => { bit_register!(@const_from_bits_non_zero NonZeroU64, u64, $bits) };
+    (@const_from_bits $($field_type:ident)::+, $bits:expr) => {
+        {
+            let bits: u64 = $bits;
+            // The raw type is inferred from the `try_decode_const` signature
+            let raw = bits as _;
+            if raw as u64 != bits {
+                Err(concat!("bit pattern too large for target type ", stringify!($($field_type)::+)))
+            } else {
+                <$($field_type)::+>::try_decode_const(raw)
+            }
+        }
+    };
+
+    (@const_from_bits_uint $uint:ty, $bits:expr) => {
+        {
+            let bits: u64 = $bits;
+            if bits > <$uint>::MAX as u64 {
+                Err(concat!("bit pattern too large for target type ", stringify!($uint)))
+            } else {
+                Ok(bits as $uint)
+            }
+        }
+    };
+
+    (@const_from_bits_non_zero $non_zero:ident, $uint:ty, $bits:expr) => {
+        {
+            let bits: u64 = $bits;
+            if bits > <$uint>::MAX as u64 {
+                Err(concat!("bit pattern too large for target type ", stringify!($non_zero)))
+            } else {
+                match ::core::num::$non_zero::new(bits as $uint) {
+                    Some(value) => Ok(value),
+                    None => Err(concat!("bit pattern is zero for target type ", stringify!($non_zero))),
+                }
+            }
+        }
+    };
+}
+
+/// Generates serde implementations for a type defined by [`bit_register!`]
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_serde {
+    // Select the representation from the `serde` option, defaulting to a field map
+    ($kind:ident [serde = raw $($rest:tt)*] $($definition:tt)*) => {
+        $crate::__bit_register_serde!(@raw $kind $($definition)*);
+    };
+    ($kind:ident [$skip:tt $($rest:tt)*] $($definition:tt)*) => {
+        $crate::__bit_register_serde!($kind [$($rest)*] $($definition)*);
+    };
+    ($kind:ident [] $($definition:tt)*) => {
+        $crate::__bit_register_serde!(@fields $kind $($definition)*);
+    };
+
+    // Enums as their variant names
+    (@fields enum $name:ident: $repr_type:ty { $($variant:ident = $value:expr),+ }) => {
+        const _: () = {
+            const VARIANTS: &[&str] = &[$(stringify!($variant)),+];
+            const VALUES: &[$repr_type] = &[$($value),+];
+
+            impl $crate::serde::Serialize for $name {
+                fn serialize<S: $crate::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let variant = match self {
+                        $(
+                            Self::$variant => stringify!($variant),
+                        )+
+                    };
+                    $crate::serde_support::serialize_variant(serializer, stringify!($name), VARIANTS, variant)
+                }
+            }
+
+            impl<'de> $crate::serde::Deserialize<'de> for $name {
+                fn deserialize<D: $crate::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let index = $crate::serde_support::deserialize_variant(deserializer, stringify!($name), VARIANTS)?;
+                    Self::try_decode_const(VALUES[index]).map_err($crate::serde::de::Error::custom)
+                }
+            }
+        };
+    };
+
+    // Enums as their underlying numeric value
+    (@raw enum $name:ident: $repr_type:ty { $($variant:ident = $value:expr),+ }) => {
+        impl $crate::serde::Serialize for $name {
+            fn serialize<S: $crate::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $crate::serde::Serialize::serialize(&self.encode_const(), serializer)
+            }
+        }
+
+        impl<'de> $crate::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let raw = <$repr_type as $crate::serde::Deserialize>::deserialize(deserializer)?;
+                Self::try_decode_const(raw).map_err($crate::serde::de::Error::custom)
+            }
+        }
+    };
+
+    // Registers as a map of field names to values, validated like `TryInto`
+    (@fields struct $name:ident: $underlying_type:ty { $($field_name:ident: $field_type:ty),* }) => {
+        const _: () = {
+            const FIELDS: &[&str] = &[$(stringify!($field_name)),*];
+
+            // The bounds are higher-ranked, so registers with other field types still compile,
+            // only without serde support
+            impl $crate::serde::Serialize for $name
+            where
+                $(for<'a> $field_type: Clone + $crate::serde::Serialize,)*
+            {
+                fn serialize<S: $crate::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    use $crate::serde::ser::{Error, SerializeStruct};
+
+                    $crate::bit_register!(@try_encode_ref $name, $underlying_type, self, [$($field_name),*])
+                        .map_err(S::Error::custom)?;
+                    let mut state = serializer.serialize_struct(stringify!($name), FIELDS.len())?;
+                    $(
+                        state.serialize_field(stringify!($field_name), &self.$field_name)?;
+                    )*
+                    state.end()
+                }
+            }
+
+            impl<'de> $crate::serde::Deserialize<'de> for $name
+            where
+                $(for<'a> $field_type: Clone + $crate::serde::Deserialize<'de>,)*
+            {
+                fn deserialize<D: $crate::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    struct RegisterVisitor;
+
+                    impl<'de> $crate::serde::de::Visitor<'de> for RegisterVisitor
+                    where
+                        $(for<'a> $field_type: Clone + $crate::serde::Deserialize<'de>,)*
+                    {
+                        type Value = $name;
+
+                        fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            f.write_str(concat!("struct ", stringify!($name)))
+                        }
+
+                        fn visit_map<A: $crate::serde::de::MapAccess<'de>>(self, mut map: A) -> Result<$name, A::Error> {
+                            use $crate::serde::de::Error;
+
+                            $(
+                                let mut $field_name: Option<$field_type> = None;
+                            )*
+                            while let Some(key) = $crate::serde_support::next_field(&mut map, FIELDS)? {
+                                match key {
+                                    $(
+                                        stringify!($field_name) => {
+                                            if $field_name.is_some() {
+                                                return Err(A::Error::duplicate_field(stringify!($field_name)));
+                                            }
+                                            $field_name = Some(map.next_value()?);
+                                        }
+                                    )*
+                                    _ => {}
+                                }
+                            }
+
+                            let register = $name {
+                                $(
+                                    $field_name: $field_name
+                                        .ok_or_else(|| A::Error::missing_field(stringify!($field_name)))?,
+                                )*
+                            };
+                            $crate::bit_register!(@try_encode_ref $name, $underlying_type, register, [$($field_name),*])
+                                .map_err(A::Error::custom)?;
+                            Ok(register)
+                        }
+
+                        fn visit_seq<A: $crate::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<$name, A::Error> {
+                            use $crate::serde::de::Error;
+
+                            let register = $name {
+                                $(
+                                    $field_name: $crate::serde_support::next_element(
+                                        &mut seq,
+                                        FIELDS,
+                                        stringify!($field_name),
+                                        &self,
+                                    )?,
+                                )*
+                            };
+                            $crate::bit_register!(@try_encode_ref $name, $underlying_type, register, [$($field_name),*])
+                                .map_err(A::Error::custom)?;
+                            Ok(register)
+                        }
+                    }
+
+                    deserializer.deserialize_struct(stringify!($name), FIELDS, RegisterVisitor)
+                }
+            }
+        };
+    };
+
+    // Registers as their underlying value, validated like `TryFrom`
+    (@raw struct $name:ident: $underlying_type:ty { $($field_name:ident: $field_type:ty),* }) => {
+        impl $crate::serde::Serialize for $name
+        where
+            $(for<'a> $field_type: Clone,)*
+        {
+            fn serialize<S: $crate::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let value = $crate::bit_register!(@try_encode_ref $name, $underlying_type, self, [$($field_name),*])
+                    .map_err(<S::Error as $crate::serde::ser::Error>::custom)?;
+                $crate::serde::Serialize::serialize(&value, serializer)
+            }
+        }
+
+        impl<'de> $crate::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <$underlying_type as $crate::serde::Deserialize>::deserialize(deserializer)?;
+                Self::try_from(value).map_err($crate::serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// Generates serde implementations for a type defined by [`bit_register!`]
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_serde {
+    ($($definition:tt)*) => {};
+}
+
+/// Generates `defmt::Format` implementations for a type defined by [`bit_register!`]
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_defmt {
+    // Select the representation from the `defmt` option, defaulting to field names and values
+    ($kind:ident [defmt = raw $($rest:tt)*] $($definition:tt)*) => {
+        $crate::__bit_register_defmt!(@raw $kind $($definition)*);
+    };
+    ($kind:ident [$skip:tt $($rest:tt)*] $($definition:tt)*) => {
+        $crate::__bit_register_defmt!($kind [$($rest)*] $($definition)*);
+    };
+    ($kind:ident [] $($definition:tt)*) => {
+        $crate::__bit_register_defmt!(@fields $kind $($definition)*);
+    };
+
+    // Enums as their variant names
+    (@fields enum $name:ident: $repr_type:ty { $($variant:ident),+ }) => {
+        impl $crate::defmt::Format for $name {
+            fn format(&self, f: $crate::defmt::Formatter<'_>) {
+                let variant = match self {
+                    $(
+                        Self::$variant => stringify!($variant),
+                    )+
+                };
+                $crate::defmt_support::variant(f, variant);
+            }
+        }
+    };
+
+    // Enums as their underlying numeric value in hex
+    (@raw enum $name:ident: $repr_type:ty { $($variant:ident),+ }) => {
+        impl $crate::defmt::Format for $name {
+            fn format(&self, f: $crate::defmt::Formatter<'_>) {
+                $crate::defmt_support::raw(f, stringify!($name), self.encode_const());
+            }
+        }
+    };
+
+    // Registers as their field names and values
+    (@fields struct $name:ident: $underlying_type:ty { $($field_name:ident: $field_type:ty),* }) => {
+        impl $crate::defmt::Format for $name
+        where
+            $(for<'a> $field_type: $crate::defmt::Format,)*
+        {
+            // The separator is not read after the last field
+            #[allow(unused_assignments)]
+            fn format(&self, f: $crate::defmt::Formatter<'_>) {
+                $crate::defmt_support::register_start(f, stringify!($name));
+                let mut separator = "";
+                $(
+                    $crate::defmt_support::field(f, separator, stringify!($field_name), &self.$field_name);
+                    separator = ", ";
+                )*
+                $crate::defmt_support::register_end(f);
+            }
+        }
+    };
+
+    // Registers as their underlying value in hex
+    (@raw struct $name:ident: $underlying_type:ty { $($field_name:ident: $field_type:ty),* }) => {
+        impl $crate::defmt::Format for $name
+        where
+            $(for<'a> $field_type: Clone,)*
+        {
+            fn format(&self, f: $crate::defmt::Formatter<'_>) {
+                match $crate::bit_register!(@try_encode_ref $name, $underlying_type, self, [$($field_name),*]) {
+                    Ok(value) => $crate::defmt_support::raw(f, stringify!($name), value),
+                    Err(e) => $crate::defmt_support::raw_error(f, stringify!($name), e),
+                }
+            }
+        }
+    };
+}
+
+/// Generates `defmt::Format` implementations for a type defined by [`bit_register!`]
+#[cfg(not(feature = "defmt"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_defmt {
+    ($($definition:tt)*) => {};
+}
+
+/// Generates `ufmt::uDebug` and `ufmt::uDisplay` implementations for a type defined by
+/// [`bit_register!`]
+#[cfg(feature = "ufmt")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_ufmt {
+    // Enums as their variant names
+    (enum $name:ident { $($variant:ident),+ }) => {
+        impl $crate::ufmt::uDebug for $name {
+            fn fmt<W>(&self, f: &mut $crate::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: $crate::ufmt::uWrite + ?Sized,
+            {
+                $crate::ufmt::uDisplay::fmt(self, f)
+            }
+        }
+
+        impl $crate::ufmt::uDisplay for $name {
+            fn fmt<W>(&self, f: &mut $crate::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: $crate::ufmt::uWrite + ?Sized,
+            {
+                f.write_str(match self {
+                    $(
+                        Self::$variant => stringify!($variant),
+                    )+
+                })
+            }
+        }
+    };
+
+    // Registers as their field names and values
+    (struct $name:ident { $($field_name:ident: $($field_type:ident)::+ $(<$($field_param:ident)::+>)?),* }) => {
+        // The bounds are higher-ranked, so registers with other field types still compile, only
+        // without ufmt support
+        impl $crate::ufmt::uDebug for $name
+        where
+            $(for<'a> $($field_type)::+ $(<$($field_param)::+>)?: $crate::ufmt::uDebug,)*
+        {
+            fn fmt<W>(&self, f: &mut $crate::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: $crate::ufmt::uWrite + ?Sized,
+            {
+                f.debug_struct(stringify!($name))?
+                    $(
+                        .field(stringify!($field_name), &self.$field_name)?
+                    )*
+                    .finish()
+            }
+        }
+
+        impl $crate::ufmt::uDisplay for $name
+        where
+            $(for<'a> $crate::__bit_register_ufmt!(@display_type $($field_type)::+ $(<$($field_param)::+>)?): $crate::ufmt::uDisplay,)*
+        {
+            // The separator is not read after the last field
+            #[allow(unused_assignments)]
+            fn fmt<W>(&self, f: &mut $crate::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: $crate::ufmt::uWrite + ?Sized,
+            {
+                f.write_str(concat!(stringify!($name), " { "))?;
+                let mut separator = "";
+                $(
+                    f.write_str(separator)?;
+                    f.write_str(concat!(stringify!($field_name), ": "))?;
+                    $crate::__bit_register_ufmt!(@display f, &self.$field_name $(, $($field_param)::+)?);
+                    separator = ", ";
+                )*
+                f.write_str(" }")
+            }
+        }
+    };
+
+    // ufmt has no `uDisplay` for `Option`, so `Option` fields display their `Some` value
+    (@display_type $($field_type:ident)::+) => {
+        $($field_type)::+
+    };
+    (@display_type $($field_type:ident)::+ <$($field_param:ident)::+>) => {
+        $($field_param)::+
+    };
+    (@display $f:ident, $value:expr) => {
+        $crate::ufmt::uDisplay::fmt($value, $f)?
+    };
+    (@display $f:ident, $value:expr, $($field_param:ident)::+) => {
+        match $value {
+            Some(value) => $crate::ufmt::uDisplay::fmt(value, $f)?,
+            None => $f.write_str("None")?,
+        }
+    };
+}
+
+/// Generates `ufmt::uDebug` and `ufmt::uDisplay` implementations for a type defined by
+/// [`bit_register!`]
+#[cfg(not(feature = "ufmt"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bit_register_ufmt {
+    ($($definition:tt)*) => {};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic_register() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct BasicRegister: u16 {
+                pub flag: bool => [0],
+                pub small_field: u8 => [1:4],
+            }
+        }
+
+        // Test construction
+        let register = BasicRegister {
+            flag: true,
+            small_field: 7, // Max for 4 bits
+        };
+
+        // Test conversion to bits
+        let value: u16 = register.try_into().unwrap();
+
+        // Test conversion back from bits
+        let round_trip = BasicRegister::try_from(value).unwrap();
+
+        // Verify values
+        assert!(round_trip.flag);
+        assert_eq!(round_trip.small_field, 7);
+
+        // Test invalid value
+        let invalid = BasicRegister {
+            flag: false,
+            small_field: 16, // Too large for 4 bits
+        };
+
+        assert!(TryInto::<u16>::try_into(invalid).is_err());
+    }
+
+    #[test]
+    fn test_different_underlying_types() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct U8Register: u8 {
+                pub field: u8 => [0:6],
+            }
+        }
+
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct U32Register: u32 {
+                pub field: u16 => [0:15],
+            }
+        }
+
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct U64Register: u64 {
+                pub field: u32 => [0:31],
+            }
+        }
+
+        // Test u8 register
+        let u8_reg = U8Register { field: 127 };
+        let u8_val: u8 = u8_reg.try_into().unwrap();
+        assert_eq!(u8_val, 127);
+
+        // Test u32 register
+        let u32_reg = U32Register { field: 32767 };
+        let u32_val: u32 = u32_reg.try_into().unwrap();
+        assert_eq!(u32_val, 32767);
+
+        // Test u64 register
+        let u64_reg = U64Register { field: 2147483647 };
+        let u64_val: u64 = u64_reg.try_into().unwrap();
+        assert_eq!(u64_val, 2147483647);
+    }
+
+    #[test]
+    fn test_enum_widening() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            enum EnumRegister: u8 {
+                Variant1 = 0b0001,
+                Variant2 = 0b0010,
+            }
+        }
+
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct EnumRegister2: u16 {
+                pub enum_field: EnumRegister => [0:7],
+            }
+        }
+
+        // The u8 enum is widened into a u16 register
+        let register = EnumRegister2 {
+            enum_field: EnumRegister::Variant2,
+        };
+        let value: u16 = register.try_into().unwrap();
+        assert_eq!(value, 0b0010);
+        assert_eq!(
+            EnumRegister2::try_from(value).unwrap().enum_field,
+            EnumRegister::Variant2
+        );
+    }
+
+    #[test]
+    fn test_multiple_fields() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct ComplexRegister: u32 {
+                pub flag1: bool => [0],
+                pub flag2: bool => [1],
+                pub small_field: u8 => [2:9],
+                pub medium_field: u16 => [10:25],
+                pub large_flag: bool => [31],
+            }
+        }
+
+        let register = ComplexRegister {
+            flag1: true,
+            flag2: false,
+            small_field: 255,
+            medium_field: 1000, // A smaller value that fits in 16 bits
+            large_flag: true,
+        };
+
+        let value: u32 = register.try_into().unwrap();
+        let round_trip = ComplexRegister::try_from(value).unwrap();
+
+        assert!(round_trip.flag1);
+        assert!(!round_trip.flag2);
+        assert_eq!(round_trip.small_field, 255);
+        assert_eq!(round_trip.medium_field, 1000);
+        assert!(round_trip.large_flag);
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct EdgeRegister: u64 {
+                // Single bit at position 0
+                pub lowest_bit: bool => [0],
+                // Single bit at highest position
+                pub highest_bit: bool => [63],
+                // Maximum range for u32
+                pub full_u32: u32 => [16:47],
+            }
+        }
+
+        let register = EdgeRegister {
+            lowest_bit: true,
+            highest_bit: true,
+            full_u32: u32::MAX,
+        };
+
+        let value: u64 = register.try_into().unwrap();
+        let expected = 1u64 | (1u64 << 63) | ((u32::MAX as u64) << 16);
+        assert_eq!(value, expected);
+
+        let round_trip = EdgeRegister::try_from(value).unwrap();
+        assert!(round_trip.lowest_bit);
+        assert!(round_trip.highest_bit);
+        assert_eq!(round_trip.full_u32, u32::MAX);
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        bit_register! {
+            pub struct RangeRegister: u16 {
+                pub small_field: u8 => [0:3],
+            }
+        }
+
+        // Valid value: 4 bits can hold 0-15
+        let valid = RangeRegister { small_field: 15 };
+        assert!(TryInto::<u16>::try_into(valid).is_ok());
+
+        // Invalid value: too large for 4 bits
+        let invalid = RangeRegister { small_field: 16 };
+        assert!(TryInto::<u16>::try_into(invalid).is_err());
+    }
+
+    #[test]
+    fn test_from_raw_value() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct FromRawRegister: u32 {
+                pub flag: bool => [0],
+                pub field1: u8 => [1:8],
+                pub field2: u16 => [9:24],
+            }
+        }
+
+        // Create a raw value
+        let raw_value: u32 = 1 | (255 << 1) | (0xFFFF << 9);
+
+        // Convert to register
+        let register = FromRawRegister::try_from(raw_value).unwrap();
+
+        // Verify fields were extracted correctly
+        assert!(register.flag);
+        assert_eq!(register.field1, 255);
+        assert_eq!(register.field2, 0xFFFF);
+    }
+
+    #[test]
+    fn test_one_bit_enum() {
+        // First test with On/Off naming
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            enum OneBitEnum: u8 {
+                Off = 0b0,
+                On = 0b1,
+            }
+        }
+
+        // Alternative test with different naming scheme
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            enum BooleanState: u8 {
+                False = 0,
+                True = 1,
+            }
+        }
+
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct EnumRegister: u8 {
+                pub enum_field: OneBitEnum => [0],
+            }
+        }
+
+        // Test with Off (0)
+        let register = EnumRegister {
+            enum_field: OneBitEnum::Off,
+        };
+
+        // Verify conversion to bits
+        let bits: u8 = register.try_into().unwrap();
+        assert_eq!(bits, 0); // Bit 0 should be 0
+
+        // Verify round-trip conversion
+        let round_trip = EnumRegister::try_from(bits).unwrap();
+        assert_eq!(round_trip.enum_field, OneBitEnum::Off);
+
+        // Test with On (1)
+        let register = EnumRegister {
+            enum_field: OneBitEnum::On,
+        };
+
+        // Verify conversion to bits
+        let bits: u8 = register.try_into().unwrap();
+        assert_eq!(bits, 1); // Bit 0 should be 1
+
+        // Verify round-trip conversion
+        let round_trip = EnumRegister::try_from(bits).unwrap();
+        assert_eq!(round_trip.enum_field, OneBitEnum::On);
 
-mod traits;
-pub use traits::*;
+        // Test with multiple fields
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct ComplexRegister: u8 {
+                pub enum_field: OneBitEnum => [0],
+                pub flag: bool => [1],
+                pub value: u8 => [2:4],
+            }
+        }
 
-// Re-export num_traits for use in the macro
-pub extern crate num_traits;
+        let register = ComplexRegister {
+            enum_field: OneBitEnum::On, // 1 at bit 0
+            flag: true,                 // 1 at bit 1
+            value: 3,                   // 011 at bits 2-4
+        };
 
-/// A macro for defining registers with fields that map to specific bits in an underlying type.
-///
-/// The macro provides automatic conversion between the register types and their
-/// underlying bit representations, with range checking and error handling. Underlying types
-/// are an unsigned integer type.
-#[macro_export]
-macro_rules! bit_register {
-    // Entrypoint for defining an enum type which can be used as a bit register
-    (
-        $(#[$attr:meta])*
-        $vis:vis enum $name:ident: $repr_type:ty {
-            $(
-                $(#[$variant_attr:meta])*
-                $variant:ident = $value:expr
-            ),+ $(,)?
+        // Expected bits:
+        // Bit 0: enum_field = 1
+        // Bit 1: flag = 1
+        // Bits 2-4: value = 3 (011 in binary)
+        //          0011 1001
+        let expected: u8 = 0b00001111;
+
+        // Verify conversion to bits
+        let bits: u8 = register.try_into().unwrap();
+        assert_eq!(bits, expected);
+
+        // Verify round-trip conversion
+        let round_trip = ComplexRegister::try_from(bits).unwrap();
+        assert_eq!(round_trip.enum_field, OneBitEnum::On);
+        assert!(round_trip.flag);
+        assert_eq!(round_trip.value, 3);
+
+        // Test the BooleanState enum with different naming scheme
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            struct BooleanRegister: u8 {
+                pub state: BooleanState => [0],
+            }
         }
-    ) => {
-        $(#[$attr])*
-        #[repr($repr_type)]
-        $vis enum $name {
-            $(
-                $(#[$variant_attr])*
-                $variant = $value,
-            )+
+
+        // Test with False (0)
+        let register = BooleanRegister {
+            state: BooleanState::False,
+        };
+
+        // Verify conversion to bits
+        let bits: u8 = register.try_into().unwrap();
+        assert_eq!(bits, 0);
+
+        // Verify round-trip conversion
+        let round_trip = BooleanRegister::try_from(bits).unwrap();
+        assert_eq!(round_trip.state, BooleanState::False);
+
+        // Test with True (1)
+        let register = BooleanRegister {
+            state: BooleanState::True,
+        };
+
+        // Verify conversion to bits
+        let bits: u8 = register.try_into().unwrap();
+        assert_eq!(bits, 1);
+
+        // Verify round-trip conversion
+        let round_trip = BooleanRegister::try_from(bits).unwrap();
+        assert_eq!(round_trip.state, BooleanState::True);
+    }
+
+    #[test]
+    fn test_enum_serialization() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            enum TestEnum: u32 {
+                Variant1 = 0b0001,
+                Variant2 = 0b0010,
+            }
         }
 
-        impl $crate::NumBytes for $name {
-            const NUM_BYTES: usize = <$repr_type as $crate::NumBytes>::NUM_BYTES;
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct TestEnumRegister: u32 {
+                pub variant1: TestEnum => [2:5],
+            }
         }
 
-        impl<T: Copy + TryFrom<$repr_type>> $crate::TryIntoBits<T> for $name {
-            fn try_into_bits(self) -> Result<T, &'static str> {
-                // Convert enum to its underlying numeric type then to target type
-                (self as $repr_type).try_into_bits()
+        let register = TestEnumRegister {
+            variant1: TestEnum::Variant1,
+        };
+        let value: u32 = register.try_into().unwrap();
+        let round_trip = TestEnumRegister::try_from(value).unwrap();
+        assert_eq!(round_trip.variant1, TestEnum::Variant1);
+    }
+
+    #[test]
+    fn test_auto_enum_definition() {
+        // Test the new enum definition with automatic TryToFromBits implementation
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
+                LowPower = 2,
+                Sleep = 3
             }
         }
 
-        impl<T: Copy> $crate::TryFromBits<T> for $name where $repr_type: TryFrom<T> {
-            fn try_from_bits(bits: T) -> Result<Self, &'static str> {
-                // Convert the bits to the enum's representation type
-                let value = <$repr_type>::try_from_bits(bits)?;
+        // Test conversion to bits
+        let mode = OperationMode::Active;
+        let bits: u8 = TryIntoBits::try_into_bits(mode).unwrap();
+        assert_eq!(bits, 1);
 
-                // Match the numeric value to the corresponding enum variant
-                match value {
-                    $(
-                        $value => Ok(Self::$variant),
-                    )+
-                    _ => Err(concat!("Invalid value for enum ", stringify!($name))),
-                }
+        // Test conversion from bits
+        let round_trip = OperationMode::try_from_bits(bits).unwrap();
+        assert_eq!(round_trip, OperationMode::Active);
+
+        // Test invalid value
+        let result = OperationMode::try_from_bits(4u8);
+        assert!(result.is_err());
+
+        // Test using the enum in a register
+        bit_register! {
+            #[bit_register(partial)]
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct ControlRegister: u32 {
+                pub enabled: bool => [0],
+                pub mode: OperationMode => [1:2],
+                pub priority: u8 => [3:5]
             }
         }
-    };
 
-    // Define a struct type which can be used as a bit register
-    (
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident: $underlying_type:ty {
-            $(
-                $(#[$field_attr:meta])*
-                $field_vis:vis $field_name:ident: $field_type:tt => $field_bits:tt
-            ),* $(,)?
+        let register = ControlRegister {
+            enabled: true,
+            mode: OperationMode::LowPower,
+            priority: 3,
+        };
+
+        // Test conversion to bits
+        let value: u32 = register.try_into().unwrap();
+        // Expected: bit 0 set (enabled), bits 1-2 = 2 (LowPower), bits 3-5 = 3 (priority)
+        let expected = 0b0001_1101;
+        assert_eq!(value, expected);
+
+        // Test conversion from bits
+        let round_trip = ControlRegister::try_from(value).unwrap();
+        assert!(round_trip.enabled);
+        assert_eq!(round_trip.mode, OperationMode::LowPower);
+        assert_eq!(round_trip.priority, 3);
+    }
+
+    #[test]
+    fn test_const_conversions() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
+                LowPower = 2,
+            }
         }
-    ) => {
-        $(#[$attr])*
-        $vis struct $name {
-            $(
-                $(#[$field_attr])*
-                $field_vis $field_name: $field_type,
-            )*
+
+        bit_register! {
+            #[bit_register(const, partial)]
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct ControlRegister: u16 {
+                pub enabled: bool => [0],
+                pub mode: OperationMode => [1:2],
+                pub priority: u8 => [3:5],
+                pub divider: u16 => [6:15],
+            }
         }
 
-        impl $crate::NumBytes for $name {
-            const NUM_BYTES: usize = <$underlying_type as $crate::NumBytes>::NUM_BYTES;
+        // Both directions are evaluated at compile time
+        const RESET: u16 = ControlRegister {
+            enabled: true,
+            mode: OperationMode::LowPower,
+            priority: 3,
+            divider: 0x3FF,
         }
+        .encode_const();
+        const DECODED: ControlRegister = ControlRegister::decode_const(RESET);
+        const MODE: OperationMode = OperationMode::decode_const(1);
+
+        assert_eq!(RESET, 0b1111_1111_1101_1101);
+        assert_eq!(
+            DECODED,
+            ControlRegister {
+                enabled: true,
+                mode: OperationMode::LowPower,
+                priority: 3,
+                divider: 0x3FF,
+            }
+        );
+        assert_eq!(MODE, OperationMode::Active);
+        assert_eq!(OperationMode::Active.encode_const(), 1);
+
+        // The const paths agree with the trait based conversions
+        let register = ControlRegister::try_from(RESET).unwrap();
+        assert_eq!(register.try_encode_const(), Ok(RESET));
+        assert_eq!(TryInto::<u16>::try_into(register), Ok(RESET));
+
+        // Out of range fields and invalid bit patterns are reported as errors
+        let invalid = ControlRegister {
+            enabled: false,
+            mode: OperationMode::Idle,
+            priority: 8,
+            divider: 0,
+        };
+        assert_eq!(
+            invalid.try_encode_const(),
+            Err("priority exceeds maximum value for its bit width")
+        );
+        assert_eq!(
+            ControlRegister::try_decode_const(0b110),
+            Err("Invalid value for enum OperationMode")
+        );
+        assert_eq!(
+            OperationMode::try_decode_const(3),
+            Err("Invalid value for enum OperationMode")
+        );
+    }
 
-        impl TryFrom<$underlying_type> for $name {
-            type Error = &'static str;
+    #[test]
+    fn test_custom_field_types() {
+        // Only implements the conversion traits, without `const` conversions or `FieldType`
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        struct Celsius(u8);
 
-            fn try_from(value: $underlying_type) -> Result<Self, Self::Error> {
-                $(
-                    let $field_name = bit_register!(@extract_bits $underlying_type, value, $field_type, $field_bits);
-                )*
+        impl NumBytes for Celsius {
+            const NUM_BYTES: usize = 1;
+        }
 
-                Ok(Self {
-                    $(
-                        $field_name,
-                    )*
-                })
+        impl TryFromBits<u16> for Celsius {
+            fn try_from_bits(bits: u16) -> Result<Self, &'static str> {
+                u8::try_from_bits(bits).map(Celsius)
             }
         }
 
-        impl TryInto<$underlying_type> for $name {
-            type Error = &'static str;
+        impl TryIntoBits<u16> for Celsius {
+            fn try_into_bits(self) -> Result<u16, &'static str> {
+                Ok(self.0.into())
+            }
+        }
 
-            fn try_into(self) -> Result<$underlying_type, Self::Error> {
-                let mut value: $underlying_type = 0;
-                $(
-                    // Handle bit packing for each field
-                    value |= bit_register!(@pack_bits $underlying_type, self.$field_name, $field_name, $field_type, $field_bits);
-                )*
-                Ok(value)
+        // Not even `Clone`, so registers with it cannot be compared
+        #[derive(Debug, PartialEq, Eq)]
+        struct Alarm(bool);
+
+        impl NumBytes for Alarm {
+            const NUM_BYTES: usize = 1;
+        }
+
+        impl TryFromBits<u16> for Alarm {
+            fn try_from_bits(bits: u16) -> Result<Self, &'static str> {
+                bool::try_from_bits(bits).map(Alarm)
             }
         }
 
-        impl $crate::BitRegister<$underlying_type> for $name {}
-    };
+        impl TryIntoBits<u16> for Alarm {
+            fn try_into_bits(self) -> Result<u16, &'static str> {
+                self.0.try_into_bits()
+            }
+        }
 
-    // Extract a single bit, convert to range
-    (@extract_bits $underlying_type:ty, $value:expr, $field_type:ty, [$bit:literal]) => {
-        bit_register!(@extract_bits_impl $underlying_type, $value, $field_type, [$bit:$bit])
-    };
+        impl FieldType for Alarm {
+            const KIND: FieldKind = FieldKind::Bool;
+        }
 
-    // Extract a range of bits
-    (@extract_bits $underlying_type:ty, $value:expr, $field_type:ty, [$start:literal:$end:literal]) => {
-        bit_register!(@extract_bits_impl $underlying_type, $value, $field_type, [$start:$end])
-    };
+        type Gain = u8;
 
-    // Generic implementation for extracting bits from an unsigned integer type
-    (@extract_bits_impl $underlying_type:ty, $value:expr, $field_type:ty, [$start:literal:$end:literal]) => {
-        {
-            // Calculate how many bits are in this field
-            const BIT_COUNT: usize = ($end - $start) + 1;
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct SensorRegister: u16 {
+                pub temperature: Celsius => [0:7],
+                pub gain: Gain => [8:11] in 1..=8,
+                pub offset: Option<Gain> => [12:15] none 0xf,
+            }
+        }
 
-            // Create a mask with BIT_COUNT number of 1s
-            // Handle the case where BIT_COUNT is the full width of the underlying type
-            let mask: $underlying_type = if BIT_COUNT >= (<$underlying_type as $crate::NumBytes>::NUM_BYTES * 8) {
-                <$underlying_type>::MAX
-            } else {
-                ((1 as $underlying_type) << BIT_COUNT) - 1
-            };
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct AlarmRegister: u16 {
+                pub alarm: Alarm => [0],
+                pub level: Gain => [1:4],
+            }
+        }
+
+        let sensor = SensorRegister::try_from(0xf219u16).unwrap();
+        assert_eq!(
+            sensor,
+            SensorRegister {
+                temperature: Celsius(0x19),
+                gain: 2,
+                offset: None,
+            }
+        );
+        assert_eq!(TryInto::<u16>::try_into(sensor), Ok(0xf219));
+        assert_eq!(
+            SensorRegister::try_from(0xf019u16),
+            Err("gain is not in 1..=8")
+        );
+
+        let warmer = SensorRegister {
+            temperature: Celsius(0x20),
+            gain: 2,
+            offset: Some(3),
+        };
+        let sensor = SensorRegister::try_from(0xf219u16).unwrap();
+        assert_eq!(sensor.diff(&warmer).iter().count(), 2);
+
+        let alarm = AlarmRegister::try_from(0x0bu16).unwrap();
+        assert_eq!(
+            alarm,
+            AlarmRegister {
+                alarm: Alarm(true),
+                level: 5,
+            }
+        );
+        assert_eq!(TryInto::<u16>::try_into(alarm), Ok(0x0b));
 
-            // Extract the relevant bits by right-shifting to the start position
-            // and then masking to keep only the bits we want
-            let extracted_value = ($value >> $start) & mask;
+        // Field types without `FieldType` are described as unsigned
+        assert_eq!(SensorRegister::LAYOUT.fields[0].kind, FieldKind::Unsigned);
+        assert_eq!(AlarmRegister::LAYOUT.fields[0].kind, FieldKind::Bool);
+    }
 
-            // Convert the extracted bits to the field type
-            $crate::TryFromBits::try_from_bits(extracted_value)?
+    #[test]
+    fn test_const_enum_too_narrow() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            enum Narrow: u8 {
+                Low = 1,
+            }
         }
-    };
 
+        bit_register! {
+            #[bit_register(const)]
+            #[derive(Debug, PartialEq, Eq)]
+            struct WideRegister: u16 {
+                pub field: Narrow => [0:15],
+            }
+        }
 
-    // Pack a single bit field
-    (@pack_bits $underlying_type:ty, $field_value:expr, $field_name:ident, $field_type:tt, [$bit:literal]) => {
-        bit_register!(@pack_bits $underlying_type, $field_value, $field_name, $field_type, [$bit:$bit])
-    };
+        // 0x101 must not be truncated to the valid variant 0x01
+        assert_eq!(
+            WideRegister::try_decode_const(0x101),
+            Err("bit pattern too large for target type Narrow")
+        );
+        assert_eq!(
+            WideRegister::try_decode_const(0x1),
+            Ok(WideRegister { field: Narrow::Low })
+        );
+    }
 
-    // Pack a range of bits
-    (@pack_bits $underlying_type:ty, $field_value:expr, $field_name:ident, $field_type:tt, [$start:literal:$end:literal]) => {
-        {
-            // Calculate how many bits are needed for this field
-            const BIT_COUNT: usize = ($end - $start) + 1;
-            const FIELD_TYPE_BITS: usize = <$field_type as $crate::NumBytes>::NUM_BYTES * 8;
+    #[test]
+    #[should_panic(expected = "small_field exceeds maximum value for its bit width")]
+    fn test_encode_const_panics() {
+        bit_register! {
+            #[bit_register(const)]
+            pub struct PanicRegister: u8 {
+                pub small_field: u8 => [0:3],
+            }
+        }
 
-            // Calculate the maximum value that can fit in the bit field
-            // We need to handle this carefully to avoid overflow
-            let max_value = if BIT_COUNT >= 64 {
-                u64::MAX // Special case for fields that use all available bits
-            } else {
-                (1u64 << BIT_COUNT) - 1 // 2^BIT_COUNT - 1
-            };
+        let _ = PanicRegister { small_field: 16 }.encode_const();
+    }
 
-            let field_value: $underlying_type = $crate::TryIntoBits::try_into_bits($field_value)?;
+    #[test]
+    fn test_partial_register() {
+        extern crate std;
+        use std::format;
 
-            // Check if the value fits in the allocated bits
-            // We skip this check if the field type uses fewer or equal bits than we've allocated
-            if BIT_COUNT < FIELD_TYPE_BITS {
-                if field_value as u64 > max_value {
-                    return Err(concat!(stringify!($field_name), " exceeds maximum value for its bit width"));
-                }
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
+                LowPower = 2,
             }
+        }
 
-            // Create a mask for the field value before shifting it into position
-            let field_mask = if BIT_COUNT >= FIELD_TYPE_BITS {
-                <$underlying_type>::MAX // Use all bits if BIT_COUNT exceeds underlying type size
-            } else {
-                ((1 as $underlying_type) << BIT_COUNT) - 1 // Create a mask of BIT_COUNT 1s
-            };
-
-            // Mask the value and shift it to the correct position
-            ((field_value) & field_mask) << $start
+        bit_register! {
+            #[bit_register(partial)]
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct ControlRegister: u16 {
+                pub enabled: bool => [0],
+                pub mode: OperationMode => [1:2],
+                pub priority: u8 => [3:5],
+                pub divider: u16 => [6:15],
+            }
         }
-    };
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        // Only set two fields
+        let partial = PartialControlRegister::new()
+            .with_mode(OperationMode::Active)
+            .with_priority(5);
+        assert_eq!(partial.enabled, None);
+        assert_eq!(partial.try_mask_value(), Ok((0b11_1110, 0b10_1010)));
+
+        // Bits of unset fields are left untouched
+        assert_eq!(partial.try_apply(0xFFFF), Ok(0b1111_1111_1110_1011));
+        assert_eq!(partial.try_apply(0x0000), Ok(0b0000_0000_0010_1010));
+
+        // No fields set is a no-op
+        let empty = PartialControlRegister::default();
+        assert_eq!(empty.try_mask_value(), Ok((0, 0)));
+        assert_eq!(empty.try_apply(0x1234), Ok(0x1234));
+
+        // Pending writes can be copied, compared and logged
+        let copy = partial;
+        assert_eq!(copy, partial);
+        assert_ne!(copy, empty);
+        assert_eq!(
+            format!("{:?}", empty),
+            "PartialControlRegister { enabled: None, mode: None, priority: None, divider: None }"
+        );
+
+        // Set fields are range checked
+        let invalid = PartialControlRegister::new().with_priority(8);
+        assert_eq!(
+            invalid.try_mask_value(),
+            Err("priority exceeds maximum value for its bit width")
+        );
+        assert!(invalid.try_apply(0).is_err());
+
+        // A full register converts to a partial with every field set
+        let full = PartialControlRegister::from(ControlRegister {
+            enabled: true,
+            mode: OperationMode::LowPower,
+            priority: 3,
+            divider: 0x3FF,
+        });
+        assert_eq!(full.try_mask_value(), Ok((0xFFFF, 0b1111_1111_1101_1101)));
+
+        // Partial registers can be built at compile time
+        const WRITE: PartialControlRegister = PartialControlRegister::new().with_enabled(true);
+        assert_eq!(WRITE.try_mask_value(), Ok((0b1, 0b1)));
+    }
 
     #[test]
-    fn test_basic_register() {
+    fn test_partial_register_method_names() {
+        // Setters are prefixed, so fields can share names with the partial register's methods
         bit_register! {
+            #[bit_register(partial)]
             #[derive(Debug, PartialEq, Eq)]
-            pub struct BasicRegister: u16 {
-                pub flag: bool => [0],
-                pub small_field: u8 => [1:4],
+            pub struct CommandRegister: u8 {
+                pub try_apply: bool => [0],
+                pub try_mask_value: u8 => [1:3],
             }
         }
 
-        // Test construction
-        let register = BasicRegister {
-            flag: true,
-            small_field: 7, // Max for 4 bits
-        };
+        let partial = PartialCommandRegister::new()
+            .with_try_apply(true)
+            .with_try_mask_value(5);
+        assert_eq!(partial.try_apply, Some(true));
+        assert_eq!(partial.try_mask_value(), Ok((0b1111, 0b1011)));
+        assert_eq!(partial.try_apply(0xf0), Ok(0xfb));
 
-        // Test conversion to bits
-        let value: u16 = register.try_into().unwrap();
+        // Registers without the `partial` option leave the name of the partial register free
+        bit_register! {
+            pub struct ResetRegister: u8 {
+                pub reset: bool => [0],
+            }
+        }
 
-        // Test conversion back from bits
-        let round_trip = BasicRegister::try_from(value).unwrap();
+        struct PartialResetRegister;
+        let _ = (ResetRegister::try_from(1), PartialResetRegister);
+    }
 
-        // Verify values
-        assert_eq!(round_trip.flag, true);
-        assert_eq!(round_trip.small_field, 7);
+    #[test]
+    fn test_register_pattern() {
+        extern crate std;
+        use std::format;
 
-        // Test invalid value
-        let invalid = BasicRegister {
-            flag: false,
-            small_field: 16, // Too large for 4 bits
-        };
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
+                LowPower = 2,
+            }
+        }
 
-        assert!(TryInto::<u16>::try_into(invalid).is_err());
+        bit_register! {
+            #[bit_register(pattern)]
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct StatusRegister: u16 {
+                pub ready: bool => [3],
+                pub mode: OperationMode => [4:5],
+                pub count: u8 => [8:11],
+            }
+        }
+
+        // Is bit 3 set and mode == Idle?
+        const READY_AND_IDLE: StatusRegisterPattern = StatusRegisterPattern::new()
+            .with_ready(true)
+            .with_mode(OperationMode::Idle);
+        assert_eq!(
+            READY_AND_IDLE.try_mask_expected(),
+            Ok((0b0011_1000, 0b0000_1000))
+        );
+        assert!(READY_AND_IDLE.matches(0b0000_1000));
+        assert!(READY_AND_IDLE.matches(0xFF0F));
+        assert!(!READY_AND_IDLE.matches(0b0001_1000));
+        assert!(!READY_AND_IDLE.matches(0b0000_0000));
+
+        // An empty pattern matches anything
+        let any = StatusRegisterPattern::default();
+        assert!(any.matches(0xFFFF));
+        assert_eq!(any.try_mask_expected(), Ok((0, 0)));
+
+        // Patterns can be reused and logged
+        let copy = READY_AND_IDLE;
+        assert!(copy.matches(0b0000_1000) && READY_AND_IDLE.matches(0b0000_1000));
+        assert_eq!(
+            format!("{:?}", READY_AND_IDLE),
+            "StatusRegisterPattern { ready: Exactly(true), mode: Exactly(Idle), count: Any }"
+        );
+
+        // Any-of constraints
+        let active = StatusRegisterPattern::new()
+            .with_mode_any_of(&[OperationMode::Active, OperationMode::LowPower]);
+        assert!(!active.matches(0b00_0000));
+        assert!(active.matches(0b01_0000));
+        assert!(active.matches(0b10_0000));
+        assert!(!active.matches(0b11_0000));
+        assert_eq!(
+            active.try_mask_expected(),
+            Err("mode has an any-of constraint which cannot be expressed as a mask")
+        );
+        assert!(!StatusRegisterPattern::new()
+            .with_count_any_of(&[])
+            .matches(0));
+
+        // Values which do not fit in their field never match
+        let invalid = StatusRegisterPattern::new().with_count_any_of(&[0x10]);
+        assert!(!invalid.matches(0x0000));
+        assert_eq!(
+            StatusRegisterPattern::new()
+                .with_count(0x10)
+                .try_mask_expected(),
+            Err("count exceeds maximum value for its bit width")
+        );
     }
 
     #[test]
-    fn test_different_underlying_types() {
+    fn test_register_pattern_method_names() {
+        // Constraints are prefixed, so fields can share names with the pattern's methods
         bit_register! {
+            #[bit_register(partial, pattern)]
             #[derive(Debug, PartialEq, Eq)]
-            struct U8Register: u8 {
-                pub field: u8 => [0:6],
+            pub struct EventRegister: u8 {
+                pub new: bool => [0],
+                pub matches: u8 => [1:3],
+                pub try_mask_expected: bool => [4],
             }
         }
 
+        let pattern = EventRegisterPattern::new()
+            .with_new(true)
+            .with_matches_any_of(&[2, 3]);
+        assert!(pattern.matches(0b0101));
+        assert!(!pattern.matches(0b0011));
+        assert_eq!(
+            EventRegisterPattern::new()
+                .with_try_mask_expected(true)
+                .try_mask_expected(),
+            Ok((0b1_0000, 0b1_0000))
+        );
+        assert_eq!(
+            PartialEventRegister::new().with_new(true).try_apply(0),
+            Ok(0b1)
+        );
+
+        // Without the `pattern` option, fields may be named like the any-of constraints
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            struct U32Register: u32 {
-                pub field: u16 => [0:15],
+            pub struct ChannelRegister: u8 {
+                pub channel: u8 => [0:3],
+                pub channel_any_of: u8 => [4:7],
             }
         }
 
+        assert!(ChannelRegister::try_from(0x21).is_ok());
+    }
+
+    #[test]
+    fn test_msb0_bit_order() {
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            struct U64Register: u64 {
-                pub field: u32 => [0:31],
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum Kind: u8 {
+                Data = 0,
+                Control = 2,
             }
         }
 
-        // Test u8 register
-        let u8_reg = U8Register { field: 127 };
-        let u8_val: u8 = u8_reg.try_into().unwrap();
-        assert_eq!(u8_val, 127);
+        // Bit 0 is the most significant bit of the underlying type
+        bit_register! {
+            #[bit_register(const, partial, pattern)]
+            #[bit_register(bit_order = msb0)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct Msb0Header: u16 {
+                pub version: u8 => [0:3],
+                pub kind: Kind => [4:5],
+                pub urgent: bool => [7],
+                pub length: u8 => [8:15],
+            }
+        }
 
-        // Test u32 register
-        let u32_reg = U32Register { field: 32767 };
-        let u32_val: u32 = u32_reg.try_into().unwrap();
-        assert_eq!(u32_val, 32767);
+        bit_register! {
+            #[bit_register(const, partial)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct Lsb0Header: u16 {
+                pub version: u8 => [12:15],
+                pub kind: Kind => [10:11],
+                pub urgent: bool => [8],
+                pub length: u8 => [0:7],
+            }
+        }
 
-        // Test u64 register
-        let u64_reg = U64Register { field: 2147483647 };
-        let u64_val: u64 = u64_reg.try_into().unwrap();
-        assert_eq!(u64_val, 2147483647);
+        for raw in 0..=u16::MAX {
+            let msb0 = Msb0Header::try_from(raw);
+            let lsb0 = Lsb0Header::try_from(raw);
+            assert_eq!(
+                msb0.map(|h| (h.version, h.kind, h.urgent, h.length)),
+                lsb0.map(|h| (h.version, h.kind, h.urgent, h.length))
+            );
+        }
+
+        let msb0 = Msb0Header {
+            version: 0xa,
+            kind: Kind::Control,
+            urgent: true,
+            length: 0x5c,
+        };
+        let lsb0 = Lsb0Header {
+            version: 0xa,
+            kind: Kind::Control,
+            urgent: true,
+            length: 0x5c,
+        };
+        assert_eq!(TryInto::<u16>::try_into(msb0), Ok(0xa95c));
+        assert_eq!(TryInto::<u16>::try_into(lsb0), Ok(0xa95c));
+        assert_eq!(msb0.encode_const(), lsb0.encode_const());
+        assert_eq!(Msb0Header::decode_const(0xa95c), msb0);
+        assert_eq!(
+            PartialMsb0Header::new()
+                .with_kind(Kind::Control)
+                .try_mask_value(),
+            PartialLsb0Header::new()
+                .with_kind(Kind::Control)
+                .try_mask_value()
+        );
+        assert!(Msb0HeaderPattern::new().with_urgent(true).matches(0x0100));
+        assert_eq!(
+            TryInto::<u16>::try_into(Msb0Header {
+                version: 16,
+                ..msb0
+            }),
+            Err("version exceeds maximum value for its bit width")
+        );
+
+        // Layouts always count from the least significant end
+        assert_eq!(Msb0Header::LAYOUT.fields, Lsb0Header::LAYOUT.fields);
     }
 
     #[test]
-    fn test_enum_widening() {
+    fn test_descending_ranges() {
         bit_register! {
             #[derive(Debug, PartialEq, Eq)]
-            enum EnumRegister: u8 {
-                Variant1 = 0b0001,
-                Variant2 = 0b0010,
+            pub struct Descending: u32 {
+                pub low: u8 => [7:0],
+                pub high: u16 => [31:16],
             }
         }
 
         bit_register! {
+            #[bit_register(bit_order = msb0)]
             #[derive(Debug, PartialEq, Eq)]
-            struct EnumRegister2: u16 {
-                pub enum_field: EnumRegister => [0:7],
+            pub struct DescendingMsb0: u32 {
+                pub high: u16 => [15:0],
+                pub low: u8 => [31:24],
             }
         }
+
+        let descending = Descending::try_from(0x1234_0056).unwrap();
+        assert_eq!((descending.low, descending.high), (0x56, 0x1234));
+        let msb0 = DescendingMsb0::try_from(0x1234_0056).unwrap();
+        assert_eq!((msb0.low, msb0.high), (0x56, 0x1234));
+        assert_eq!(TryInto::<u32>::try_into(msb0), Ok(0x1234_0056));
+        assert_eq!(
+            (
+                Descending::LAYOUT.fields[1].lsb,
+                Descending::LAYOUT.fields[1].msb
+            ),
+            (16, 31)
+        );
+        assert_eq!(
+            (
+                DescendingMsb0::LAYOUT.fields[0].lsb,
+                DescendingMsb0::LAYOUT.fields[0].msb
+            ),
+            (16, 31)
+        );
     }
 
     #[test]
-    fn test_multiple_fields() {
+    fn test_valid_values() {
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            pub struct ComplexRegister: u32 {
-                pub flag1: bool => [0],
-                pub flag2: bool => [1],
-                pub small_field: u8 => [2:9],
-                pub medium_field: u16 => [10:25],
-                pub large_flag: bool => [31],
+            #[bit_register(const, partial, pattern)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct ClockRegister: u16 {
+                pub divider: u8 => [0:3] in 1..=10,
+                pub source: u8 => [4:5] in 0 | 2,
+                pub enabled: bool => [8],
             }
         }
 
-        let register = ComplexRegister {
-            flag1: true,
-            flag2: false,
-            small_field: 255,
-            medium_field: 1000, // A smaller value that fits in 16 bits
-            large_flag: true,
+        let clock = ClockRegister {
+            divider: 10,
+            source: 2,
+            enabled: true,
+        };
+        assert_eq!(TryInto::<u16>::try_into(clock), Ok(0x012a));
+        assert_eq!(ClockRegister::try_from(0x012a), Ok(clock));
+
+        // Constraints apply when decoding
+        assert_eq!(
+            ClockRegister::try_from(0x0120),
+            Err("divider is not in 1..=10")
+        );
+        assert_eq!(
+            ClockRegister::try_from(0x011a),
+            Err("source is not in 0 | 2")
+        );
+        assert_eq!(
+            ClockRegister::try_decode_const(0x010b),
+            Err("divider is not in 1..=10")
+        );
+
+        // And when encoding, after checking the value fits
+        let invalid = ClockRegister {
+            divider: 11,
+            ..clock
         };
+        assert_eq!(
+            TryInto::<u16>::try_into(invalid),
+            Err("divider is not in 1..=10")
+        );
+        assert_eq!(invalid.try_encode_const(), Err("divider is not in 1..=10"));
+        assert_eq!(
+            TryInto::<u16>::try_into(ClockRegister {
+                divider: 16,
+                ..clock
+            }),
+            Err("divider exceeds maximum value for its bit width")
+        );
+        assert_eq!(
+            PartialClockRegister::new().with_source(1).try_mask_value(),
+            Err("source is not in 0 | 2")
+        );
+
+        // Patterns never match invalid values, even when their bits do
+        assert!(!ClockRegisterPattern::new().with_divider(11).matches(0x000b));
+        assert!(ClockRegisterPattern::new().with_divider(10).matches(0x000a));
+        let sources = ClockRegisterPattern::new().with_source_any_of(&[1, 2]);
+        assert!(!sources.matches(0x0010));
+        assert!(sources.matches(0x0020));
+
+        let divider = ClockRegister::LAYOUT.fields[0];
+        assert_eq!(divider.valid, &[ValueRange { min: 1, max: 10 }]);
+        assert!(divider.is_valid(1) && divider.is_valid(10));
+        assert!(!divider.is_valid(0) && !divider.is_valid(11) && !divider.is_valid(16));
+        assert_eq!(
+            ClockRegister::LAYOUT.fields[1].valid,
+            &[ValueRange { min: 0, max: 0 }, ValueRange { min: 2, max: 2 }]
+        );
+        assert_eq!(ClockRegister::LAYOUT.fields[2].valid, &[]);
+        assert!(ClockRegister::LAYOUT.fields[2].is_valid(1));
+        assert!(!ClockRegister::LAYOUT.fields[2].is_valid(2));
+    }
 
-        let value: u32 = register.try_into().unwrap();
-        let round_trip = ComplexRegister::try_from(value).unwrap();
+    #[test]
+    fn test_scaled_fields() {
+        extern crate std;
+        use std::string::ToString;
 
-        assert_eq!(round_trip.flag1, true);
-        assert_eq!(round_trip.flag2, false);
-        assert_eq!(round_trip.small_field, 255);
-        assert_eq!(round_trip.medium_field, 1000);
-        assert_eq!(round_trip.large_flag, true);
+        bit_register! {
+            #[bit_register(const, partial)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct ChargerRegister: u16 {
+                pub vbat: u16 => [0:7] scale 16 offset 2000 unit "mV",
+                pub temperature: i32 => [8:15] scale 500 offset -40000 unit "m°C",
+            }
+        }
+
+        // Decoding gives physical values
+        let charger = ChargerRegister::try_from(0x5004).unwrap();
+        assert_eq!(
+            charger,
+            ChargerRegister {
+                vbat: 2064,
+                temperature: 0
+            }
+        );
+        assert_eq!(ChargerRegister::try_decode_const(0x5004), Ok(charger));
+        assert_eq!(TryInto::<u16>::try_into(charger), Ok(0x5004));
+        assert_eq!(charger.try_encode_const(), Ok(0x5004));
+
+        // Encoding rounds to the nearest step
+        for (vbat, raw) in [(2071, 0x04), (2072, 0x05), (2056, 0x04), (2055, 0x03)] {
+            let charger = ChargerRegister { vbat, ..charger };
+            assert_eq!(TryInto::<u16>::try_into(charger), Ok(0x5000 | raw));
+            assert_eq!(charger.try_encode_const(), Ok(0x5000 | raw));
+        }
+        let cold = ChargerRegister {
+            temperature: -39700,
+            ..charger
+        };
+        assert_eq!(TryInto::<u16>::try_into(cold), Ok(0x0104));
+
+        // Values outside the steps the bits can hold are rejected
+        for vbat in [1991, 6089, 0, u16::MAX] {
+            assert_eq!(
+                TryInto::<u16>::try_into(ChargerRegister { vbat, ..charger }),
+                Err("vbat is out of range for its scale and bit width")
+            );
+        }
+        assert_eq!(
+            PartialChargerRegister::new()
+                .with_temperature(-50000)
+                .try_mask_value(),
+            Err("temperature is out of range for its scale and bit width")
+        );
+
+        // Raw values whose physical value does not fit in the field type fail to decode
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct NarrowRegister: u8 {
+                pub level: u8 => [0:7] scale 2,
+            }
+        }
+        assert_eq!(
+            NarrowRegister::try_from(0x7f),
+            Ok(NarrowRegister { level: 254 })
+        );
+        assert_eq!(
+            NarrowRegister::try_from(0x80),
+            Err("level does not fit in u8")
+        );
+
+        // Metadata records the scale and unit, and values are displayed in it
+        let vbat = ChargerRegister::LAYOUT.fields[0];
+        assert_eq!(vbat.kind, FieldKind::Unsigned);
+        assert_eq!(
+            vbat.scale,
+            Some(FieldScale {
+                scale: 16,
+                offset: 2000,
+                unit: "mV"
+            })
+        );
+        assert_eq!(
+            NarrowRegister::LAYOUT.fields[0].scale,
+            Some(FieldScale {
+                scale: 2,
+                offset: 0,
+                unit: ""
+            })
+        );
+        let diff = charger.diff(&cold);
+        assert_eq!(
+            diff.to_string(),
+            "ChargerRegister:\n  temperature [8:15]: 0 m°C -> -39500 m°C"
+        );
     }
 
     #[test]
-    fn test_edge_cases() {
+    fn test_fixed_point_fields() {
+        extern crate std;
+        use std::string::ToString;
+
+        type Gain = Q<2, 6>;
+        type Coefficient = UQ<0, 8>;
+
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            pub struct EdgeRegister: u64 {
-                // Single bit at position 0
-                pub lowest_bit: bool => [0],
-                // Single bit at highest position
-                pub highest_bit: bool => [63],
-                // Maximum range for u32
-                pub full_u32: u32 => [16:47],
+            #[bit_register(const)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct AmplifierRegister: u16 {
+                pub gain: Gain => [0:7],
+                pub coefficient: Coefficient => [8:15],
             }
         }
 
-        let register = EdgeRegister {
-            lowest_bit: true,
-            highest_bit: true,
-            full_u32: u32::MAX,
+        let amplifier = AmplifierRegister {
+            gain: Gain::from_f32(-0.5).unwrap(),
+            coefficient: Coefficient::from_f32(0.75).unwrap(),
+        };
+        assert_eq!(TryInto::<u16>::try_into(amplifier), Ok(0xc0e0));
+        assert_eq!(amplifier.try_encode_const(), Ok(0xc0e0));
+        assert_eq!(AmplifierRegister::try_from(0xc0e0), Ok(amplifier));
+        assert_eq!(AmplifierRegister::try_decode_const(0xc0e0), Ok(amplifier));
+        assert_eq!(amplifier.gain.to_f32(), -0.5);
+
+        let louder = AmplifierRegister {
+            gain: Gain::saturating_from_f32(4.0),
+            ..amplifier
         };
+        assert_eq!(louder.gain, Gain::MAX);
+        assert_eq!(
+            amplifier.diff(&louder).to_string(),
+            "AmplifierRegister:\n  gain [0:7]: -0.5 -> 1.984375"
+        );
+        assert_eq!(
+            AmplifierRegister::LAYOUT.fields[0].kind,
+            FieldKind::Fixed {
+                signed: true,
+                frac: 6,
+                bits: 8
+            }
+        );
+    }
 
-        let value: u64 = register.try_into().unwrap();
-        let expected = 1u64 | (1u64 << 63) | ((u32::MAX as u64) << 16);
-        assert_eq!(value, expected);
+    #[test]
+    fn test_option_fields() {
+        extern crate std;
+        use std::string::ToString;
 
-        let round_trip = EdgeRegister::try_from(value).unwrap();
-        assert_eq!(round_trip.lowest_bit, true);
-        assert_eq!(round_trip.highest_bit, true);
-        assert_eq!(round_trip.full_u32, u32::MAX);
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum FaultMode: u8 {
+                Latched = 0,
+                Retry = 1,
+            }
+        }
+
+        bit_register! {
+            #[bit_register(const, partial, pattern)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct FaultStatus: u16 {
+                pub channel: Option<u8> => [0:7] none 0xff,
+                pub code: Option<u8> => [8:11] none 0 in 1..=9,
+                pub mode: Option<FaultMode> => [12:13] none 3,
+            }
+        }
+
+        let none = FaultStatus {
+            channel: None,
+            code: None,
+            mode: None,
+        };
+        assert_eq!(FaultStatus::try_from(0x30ff), Ok(none));
+        assert_eq!(FaultStatus::try_decode_const(0x30ff), Ok(none));
+        assert_eq!(TryInto::<u16>::try_into(none), Ok(0x30ff));
+        assert_eq!(none.try_encode_const(), Ok(0x30ff));
+
+        let fault = FaultStatus {
+            channel: Some(4),
+            code: Some(2),
+            mode: Some(FaultMode::Retry),
+        };
+        assert_eq!(FaultStatus::try_from(0x1204), Ok(fault));
+        assert_eq!(TryInto::<u16>::try_into(fault), Ok(0x1204));
+
+        // `Some` values are checked against the code space left by `None`
+        let invalid = |fault: FaultStatus| TryInto::<u16>::try_into(fault).unwrap_err();
+        assert_eq!(
+            invalid(FaultStatus {
+                channel: Some(0xff),
+                ..fault
+            }),
+            "channel is the raw value reserved for None"
+        );
+        assert_eq!(
+            invalid(FaultStatus {
+                code: Some(0),
+                ..fault
+            }),
+            "code is the raw value reserved for None"
+        );
+        assert_eq!(
+            invalid(FaultStatus {
+                code: Some(10),
+                ..fault
+            }),
+            "code is not in 1..=9"
+        );
+        assert_eq!(
+            invalid(FaultStatus {
+                code: Some(16),
+                ..fault
+            }),
+            "code exceeds maximum value for its bit width"
+        );
+        assert_eq!(FaultStatus::try_from(0x1a04), Err("code is not in 1..=9"));
+        assert_eq!(
+            FaultStatus::try_from(0x2204),
+            Err("Invalid value for enum FaultMode")
+        );
+
+        assert_eq!(
+            PartialFaultStatus::new()
+                .with_channel(None)
+                .try_apply(0x1204),
+            Ok(0x12ff)
+        );
+        assert!(FaultStatusPattern::new().with_code(None).matches(0x30ff));
+        // A `Some` holding the sentinel would be read back as `None`, so never matches
+        assert!(!FaultStatusPattern::new()
+            .with_channel(Some(0xff))
+            .matches(0x30ff));
+        assert!(!FaultStatusPattern::new()
+            .with_channel_any_of(&[Some(0xff)])
+            .matches(0x30ff));
+        assert_eq!(
+            fault.diff(&none).to_string(),
+            "FaultStatus:\n  channel [0:7]: 4 -> None\n  code [8:11]: 2 -> None\n  mode [12:13]: Retry -> None"
+        );
+
+        let layout = FaultStatus::LAYOUT;
+        assert_eq!(layout.fields[0].none, Some(0xff));
+        assert_eq!(layout.fields[0].kind, FieldKind::Unsigned);
+        assert!(layout.fields[1].is_valid(0));
+        assert!(matches!(layout.fields[2].kind, FieldKind::Enum(_)));
     }
 
     #[test]
-    fn test_out_of_range_values() {
+    fn test_table_fields() {
+        extern crate std;
+        use std::string::ToString;
+
         bit_register! {
-            pub struct RangeRegister: u16 {
-                pub small_field: u8 => [0:3],
+            #[bit_register(const, partial, pattern)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct WatchdogControl: u8 {
+                pub timeout: u16 => [0:1] table [1, 4, 16, 64],
+                pub prescaler: u32 => [4:6] table [1, 8, 64, 256, 1024],
+                pub enabled: bool => [7],
             }
         }
 
-        // Valid value: 4 bits can hold 0-15
-        let valid = RangeRegister { small_field: 15 };
-        assert!(TryInto::<u16>::try_into(valid).is_ok());
+        let watchdog = WatchdogControl {
+            timeout: 16,
+            prescaler: 256,
+            enabled: true,
+        };
+        assert_eq!(WatchdogControl::try_from(0xb2), Ok(watchdog));
+        assert_eq!(WatchdogControl::try_decode_const(0xb2), Ok(watchdog));
+        assert_eq!(TryInto::<u8>::try_into(watchdog), Ok(0xb2));
+        assert_eq!(watchdog.try_encode_const(), Ok(0xb2));
+
+        // Values missing from the table cannot be encoded, and unused indexes cannot be decoded
+        let invalid = WatchdogControl {
+            timeout: 5,
+            ..watchdog
+        };
+        assert_eq!(
+            TryInto::<u8>::try_into(invalid),
+            Err("timeout is not in its lookup table")
+        );
+        assert_eq!(
+            invalid.try_encode_const(),
+            Err("timeout is not in its lookup table")
+        );
+        assert_eq!(
+            WatchdogControl::try_from(0x50),
+            Err("prescaler is not an index of its lookup table")
+        );
+
+        assert_eq!(
+            PartialWatchdogControl::new()
+                .with_timeout(64)
+                .try_apply(0xb2),
+            Ok(0xb3)
+        );
+        assert!(WatchdogControlPattern::new()
+            .with_prescaler(256)
+            .matches(0xb2));
+        // Values missing from the table never match, even the first unused index
+        assert!(!WatchdogControlPattern::new()
+            .with_prescaler(2)
+            .matches(0x50));
+        assert!(!WatchdogControlPattern::new()
+            .with_prescaler_any_of(&[2])
+            .matches(0x50));
+        let slower = WatchdogControl {
+            timeout: 64,
+            ..watchdog
+        };
+        assert_eq!(
+            watchdog.diff(&slower).to_string(),
+            "WatchdogControl:\n  timeout [0:1]: 16 -> 64"
+        );
+
+        let layout = WatchdogControl::LAYOUT;
+        assert_eq!(layout.fields[0].table, &[1, 4, 16, 64]);
+        assert_eq!(layout.fields[0].kind, FieldKind::Unsigned);
+        assert!(layout.fields[1].is_valid(4));
+        assert!(!layout.fields[1].is_valid(5));
+        assert!(layout.fields[2].table.is_empty());
+
+        // Tables keep values which do not fit in an `i64`
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct PatternRegister: u8 {
+                pub pattern: u64 => [0:1] table [0, 0x8000_0000_0000_0000, 0xffff_ffff_ffff_ffff],
+            }
+        }
 
-        // Invalid value: too large for 4 bits
-        let invalid = RangeRegister { small_field: 16 };
-        assert!(TryInto::<u16>::try_into(invalid).is_err());
+        let all_set = PatternRegister { pattern: u64::MAX };
+        assert_eq!(PatternRegister::try_from(2), Ok(all_set));
+        assert_eq!(TryInto::<u8>::try_into(all_set), Ok(2));
+        assert_eq!(
+            PatternRegister::LAYOUT.fields[0].table,
+            &[0, 1 << 63, u64::MAX as i128]
+        );
+        assert_eq!(
+            FieldValue {
+                field: &PatternRegister::LAYOUT.fields[0],
+                raw: 2,
+            }
+            .to_string(),
+            "18446744073709551615"
+        );
     }
 
     #[test]
-    fn test_from_raw_value() {
+    fn test_non_zero_fields() {
+        use core::num::NonZeroU8;
+
+        bit_register! {
+            #[bit_register(const, partial, pattern)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct ClockDivider: u16 {
+                pub divider: NonZeroU8 => [0:7],
+                pub repeat: Option<NonZeroU8> => [8:11] none 0,
+                pub enabled: bool => [15],
+            }
+        }
+
+        let divider = |value| NonZeroU8::new(value).unwrap();
+        let clock = ClockDivider {
+            divider: divider(5),
+            repeat: Some(divider(3)),
+            enabled: true,
+        };
+        assert_eq!(ClockDivider::try_from(0x8305), Ok(clock));
+        assert_eq!(ClockDivider::try_decode_const(0x8305), Ok(clock));
+        assert_eq!(TryInto::<u16>::try_into(clock), Ok(0x8305));
+        assert_eq!(clock.try_encode_const(), Ok(0x8305));
+
+        // A divider of zero is rejected, while a repeat count of zero means `None`
+        assert_eq!(
+            ClockDivider::try_from(0x8300),
+            Err("bit pattern is zero for target type NonZeroU8")
+        );
+        assert_eq!(
+            ClockDivider::try_decode_const(0x8300),
+            Err("bit pattern is zero for target type NonZeroU8")
+        );
+        let once = ClockDivider {
+            repeat: None,
+            ..clock
+        };
+        assert_eq!(ClockDivider::try_from(0x8005), Ok(once));
+        assert_eq!(
+            TryInto::<u16>::try_into(ClockDivider {
+                repeat: Some(divider(16)),
+                ..clock
+            }),
+            Err("repeat exceeds maximum value for its bit width")
+        );
+
+        assert_eq!(
+            PartialClockDivider::new()
+                .with_divider(divider(2))
+                .try_apply(0x8305),
+            Ok(0x8302)
+        );
+        assert!(ClockDividerPattern::new()
+            .with_divider(divider(5))
+            .matches(0x8305));
+        assert_eq!(ClockDivider::LAYOUT.fields[0].kind, FieldKind::Unsigned);
+
+        // Const conversions also recognise the full path
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            pub struct FromRawRegister: u32 {
-                pub flag: bool => [0],
-                pub field1: u8 => [1:8],
-                pub field2: u16 => [9:24],
+            #[bit_register(const)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct PathDivider: u16 {
+                pub divider: core::num::NonZeroU16 => [0:7],
+                pub repeat: Option<core::num::NonZeroU32> => [8:11] none 0,
             }
         }
 
-        // Create a raw value
-        let raw_value: u32 = 1 | (255 << 1) | (0xFFFF << 9);
+        let path = PathDivider {
+            divider: core::num::NonZeroU16::new(5).unwrap(),
+            repeat: core::num::NonZeroU32::new(3),
+        };
+        assert_eq!(PathDivider::try_from(0x0305), Ok(path));
+        assert_eq!(PathDivider::try_decode_const(0x0305), Ok(path));
+        assert_eq!(path.try_encode_const(), Ok(0x0305));
+        assert_eq!(
+            PathDivider::try_decode_const(0x0300),
+            Err("bit pattern is zero for target type NonZeroU16")
+        );
 
-        // Convert to register
-        let register = FromRawRegister::try_from(raw_value).unwrap();
+        // Aliases convert through the bit traits
+        type Divider = NonZeroU8;
 
-        // Verify fields were extracted correctly
-        assert_eq!(register.flag, true);
-        assert_eq!(register.field1, 255);
-        assert_eq!(register.field2, 0xFFFF);
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct AliasDivider: u8 {
+                pub divider: Divider => [0:7],
+            }
+        }
+
+        assert_eq!(
+            AliasDivider::try_from(5),
+            Ok(AliasDivider {
+                divider: divider(5)
+            })
+        );
+        assert_eq!(
+            AliasDivider::try_from(0),
+            Err("bit pattern is zero for target type NonZeroU8")
+        );
     }
 
     #[test]
-    fn test_one_bit_enum() {
-        // First test with On/Off naming
+    fn test_validate() {
         bit_register! {
             #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-            enum OneBitEnum: u8 {
-                Off = 0b0,
-                On = 0b1,
+            pub enum ConverterMode: u8 {
+                Buck = 0,
+                Boost = 1,
             }
         }
 
-        // Alternative test with different naming scheme
         bit_register! {
+            #[bit_register(const)]
             #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-            enum BooleanState: u8 {
-                False = 0,
-                True = 1,
+            #[bit_register(validate = check_converter)]
+            pub struct ConverterControl: u8 {
+                pub mode: ConverterMode => [0],
+                pub current_limit: u8 => [1:4],
             }
         }
 
-        bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            struct EnumRegister: u8 {
-                pub enum_field: OneBitEnum => [0],
+        const fn check_converter(control: &ConverterControl) -> Result<(), &'static str> {
+            if matches!(control.mode, ConverterMode::Boost) && control.current_limit > 3 {
+                Err("boost mode needs a current limit of at most 3")
+            } else {
+                Ok(())
             }
         }
 
-        // Test with Off (0)
-        let register = EnumRegister {
-            enum_field: OneBitEnum::Off,
+        let buck = ConverterControl {
+            mode: ConverterMode::Buck,
+            current_limit: 8,
         };
+        assert_eq!(ConverterControl::try_from(0x10), Ok(buck));
+        assert_eq!(TryInto::<u8>::try_into(buck), Ok(0x10));
 
-        // Verify conversion to bits
-        let bits: u8 = register.try_into().unwrap();
-        assert_eq!(bits, 0); // Bit 0 should be 0
-
-        // Verify round-trip conversion
-        let round_trip = EnumRegister::try_from(bits).unwrap();
-        assert_eq!(round_trip.enum_field, OneBitEnum::Off);
-
-        // Test with On (1)
-        let register = EnumRegister {
-            enum_field: OneBitEnum::On,
+        // Every field fits in its bits, but the combination is rejected in both directions
+        let boost = ConverterControl {
+            mode: ConverterMode::Boost,
+            ..buck
         };
+        let invalid = "boost mode needs a current limit of at most 3";
+        assert_eq!(ConverterControl::try_from(0x11), Err(invalid));
+        assert_eq!(ConverterControl::try_decode_const(0x11), Err(invalid));
+        assert_eq!(TryInto::<u8>::try_into(boost), Err(invalid));
+        assert_eq!(boost.try_encode_const(), Err(invalid));
+
+        // Field errors are reported before the register is validated
+        assert_eq!(
+            TryInto::<u8>::try_into(ConverterControl {
+                current_limit: 16,
+                ..buck
+            }),
+            Err("current_limit exceeds maximum value for its bit width")
+        );
+
+        const SAFE: ConverterControl = ConverterControl::decode_const(0x07);
+        assert_eq!(SAFE.current_limit, 3);
+    }
 
-        // Verify conversion to bits
-        let bits: u8 = register.try_into().unwrap();
-        assert_eq!(bits, 1); // Bit 0 should be 1
+    #[test]
+    fn test_bcd_and_gray_fields() {
+        extern crate std;
+        use std::string::ToString;
 
-        // Verify round-trip conversion
-        let round_trip = EnumRegister::try_from(bits).unwrap();
-        assert_eq!(round_trip.enum_field, OneBitEnum::On);
+        type Digits = Bcd<u8>;
+        type Position = Gray<u8>;
 
-        // Test with multiple fields
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            struct ComplexRegister: u8 {
-                pub enum_field: OneBitEnum => [0],
-                pub flag: bool => [1],
-                pub value: u8 => [2:4],
+            #[bit_register(const)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct RtcTime: u32 {
+                pub seconds: Digits => [0:6],
+                pub minutes: Digits => [8:14],
+                pub hours: Digits => [16:21],
             }
         }
 
-        let register = ComplexRegister {
-            enum_field: OneBitEnum::On, // 1 at bit 0
-            flag: true,                 // 1 at bit 1
-            value: 3,                   // 011 at bits 2-4
-        };
+        bit_register! {
+            #[bit_register(const)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct EncoderRegister: u8 {
+                pub position: Position => [0:3],
+                pub valid: bool => [7],
+            }
+        }
 
-        // Expected bits:
-        // Bit 0: enum_field = 1
-        // Bit 1: flag = 1
-        // Bits 2-4: value = 3 (011 in binary)
-        //          0011 1001
-        let expected: u8 = 0b00001111;
+        let time = RtcTime {
+            seconds: Digits::new(59).unwrap(),
+            minutes: Digits::new(30).unwrap(),
+            hours: Digits::new(23).unwrap(),
+        };
+        assert_eq!(TryInto::<u32>::try_into(time), Ok(0x23_30_59));
+        assert_eq!(time.try_encode_const(), Ok(0x23_30_59));
+        assert_eq!(RtcTime::try_from(0x23_30_59), Ok(time));
+        assert_eq!(RtcTime::try_decode_const(0x23_30_59), Ok(time));
+        assert_eq!(
+            RtcTime::try_from(0x23_3a_59),
+            Err("bit pattern is not valid BCD")
+        );
+        assert_eq!(
+            RtcTime::try_decode_const(0x23_3a_59),
+            Err("bit pattern is not valid BCD")
+        );
+
+        // 99 hours are valid BCD, but need more bits than the field has
+        let late = RtcTime {
+            hours: Digits::new(99).unwrap(),
+            ..time
+        };
+        assert_eq!(
+            TryInto::<u32>::try_into(late),
+            Err("hours exceeds maximum value for its bit width")
+        );
+        assert_eq!(
+            time.diff(&RtcTime::try_from(0x23_31_00).unwrap())
+                .to_string(),
+            "RtcTime:\n  seconds [0:6]: 59 -> 0\n  minutes [8:14]: 30 -> 31"
+        );
+        assert_eq!(RtcTime::LAYOUT.fields[0].kind, FieldKind::Bcd);
+
+        let encoder = EncoderRegister {
+            position: Position::new(2),
+            valid: true,
+        };
+        assert_eq!(TryInto::<u8>::try_into(encoder), Ok(0x83));
+        assert_eq!(EncoderRegister::try_from(0x88).unwrap().position.get(), 15);
+        assert_eq!(EncoderRegister::try_decode_const(0x83), Ok(encoder));
+        assert_eq!(
+            TryInto::<u8>::try_into(EncoderRegister {
+                position: Position::new(16),
+                valid: true,
+            }),
+            Err("position exceeds maximum value for its bit width")
+        );
+        assert_eq!(EncoderRegister::LAYOUT.fields[0].kind, FieldKind::Gray);
+    }
 
-        // Verify conversion to bits
-        let bits: u8 = register.try_into().unwrap();
-        assert_eq!(bits, expected);
+    #[test]
+    fn test_register_layout() {
+        use crate::fixtures::BASE;
 
-        // Verify round-trip conversion
-        let round_trip = ComplexRegister::try_from(bits).unwrap();
-        assert_eq!(round_trip.enum_field, OneBitEnum::On);
-        assert_eq!(round_trip.flag, true);
-        assert_eq!(round_trip.value, 3);
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
+            }
+        }
 
-        // Test the BooleanState enum with different naming scheme
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            struct BooleanRegister: u8 {
-                pub state: BooleanState => [0],
+            pub struct StatusRegister: u16 {
+                pub ready: bool => [3],
+                pub mode: OperationMode => [4:5],
+                pub count: u8 => [8:15],
             }
         }
 
-        // Test with False (0)
-        let register = BooleanRegister {
-            state: BooleanState::False,
-        };
+        let layout = StatusRegister::LAYOUT;
+        assert_eq!(layout.name, "StatusRegister");
+        assert_eq!(layout.bits, 16);
+        assert_eq!(
+            layout.fields,
+            [
+                FieldInfo {
+                    name: "ready",
+                    lsb: 3,
+                    msb: 3,
+                    kind: FieldKind::Bool,
+                    ..BASE
+                },
+                FieldInfo {
+                    name: "mode",
+                    lsb: 4,
+                    msb: 5,
+                    kind: FieldKind::Enum(&[
+                        VariantInfo {
+                            name: "Idle",
+                            value: 0,
+                            doc: &[],
+                        },
+                        VariantInfo {
+                            name: "Active",
+                            value: 1,
+                            doc: &[],
+                        },
+                    ]),
+                    ..BASE
+                },
+                FieldInfo {
+                    name: "count",
+                    lsb: 8,
+                    msb: 15,
+                    ..BASE
+                },
+            ]
+        );
+        assert_eq!(layout.field("count").map(FieldInfo::mask), Some(0xFF00));
+        assert_eq!(layout.field("missing"), None);
+    }
 
-        // Verify conversion to bits
-        let bits: u8 = register.try_into().unwrap();
-        assert_eq!(bits, 0);
+    #[test]
+    fn test_register_layout_docs() {
+        bit_register! {
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub enum PowerState: u8 {
+                /// Clocks are gated
+                Off = 0,
+                #[doc = "Fully running"]
+                On = 1,
+            }
+        }
 
-        // Verify round-trip conversion
-        let round_trip = BooleanRegister::try_from(bits).unwrap();
-        assert_eq!(round_trip.state, BooleanState::False);
+        bit_register! {
+            /// Power control
+            ///
+            /// Writes take effect on the next cycle
+            #[derive(Debug, PartialEq)]
+            pub struct PowerControl: u8 {
+                /// Requested power state
+                #[allow(dead_code)]
+                pub state: PowerState => [0],
+                #[doc(hidden)]
+                pub spare: u8 => [4:7],
+            }
+        }
 
-        // Test with True (1)
-        let register = BooleanRegister {
-            state: BooleanState::True,
+        let layout = PowerControl::LAYOUT;
+        assert_eq!(
+            layout.doc,
+            [
+                " Power control",
+                "",
+                " Writes take effect on the next cycle"
+            ]
+        );
+        assert_eq!(layout.fields[0].doc, [" Requested power state"]);
+        assert!(layout.fields[1].doc.is_empty());
+
+        let FieldKind::Enum(variants) = layout.fields[0].kind else {
+            panic!("state is an enum");
         };
-
-        // Verify conversion to bits
-        let bits: u8 = register.try_into().unwrap();
-        assert_eq!(bits, 1);
-
-        // Verify round-trip conversion
-        let round_trip = BooleanRegister::try_from(bits).unwrap();
-        assert_eq!(round_trip.state, BooleanState::True);
+        assert_eq!(variants[0].doc, [" Clocks are gated"]);
+        assert_eq!(variants[1].doc, ["Fully running"]);
     }
 
     #[test]
-    fn test_enum_serialization() {
+    fn test_register_diff() {
+        extern crate std;
+        use std::string::ToString;
+
         bit_register! {
             #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-            enum TestEnum: u32 {
-                Variant1 = 0b0001,
-                Variant2 = 0b0010,
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                Active = 1,
             }
         }
 
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            pub struct TestEnumRegister: u32 {
-                pub variant1: TestEnum => [2:5],
+            #[derive(Debug)]
+            pub struct StatusRegister: u16 {
+                pub ready: bool => [3],
+                pub mode: OperationMode => [4:5],
+                pub count: u8 => [8:11],
             }
         }
 
-        let register = TestEnumRegister {
-            variant1: TestEnum::Variant1,
+        let written = StatusRegister {
+            ready: true,
+            mode: OperationMode::Active,
+            count: 3,
         };
-        let value: u32 = register.try_into().unwrap();
-        let round_trip = TestEnumRegister::try_from(value).unwrap();
-        assert_eq!(round_trip.variant1, TestEnum::Variant1);
+        let read_back = StatusRegister {
+            ready: true,
+            mode: OperationMode::Idle,
+            count: 16,
+        };
+
+        let diff = written.diff(&read_back);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.iter().count(), 2);
+
+        let mode = diff.iter().next().unwrap();
+        assert_eq!(mode.field.name, "mode");
+        assert_eq!((mode.field.lsb, mode.field.msb), (4, 5));
+        assert_eq!((mode.old.raw, mode.new.raw), (1, 0));
+        assert_eq!(mode.old.variant_name(), Some("Active"));
+        assert_eq!(mode.new.variant_name(), Some("Idle"));
+
+        // Values which do not fit in their field are still reported
+        assert_eq!(
+            diff.to_string(),
+            "StatusRegister:\n  mode [4:5]: Active -> Idle\n  count [8:11]: 3 -> 16"
+        );
+
+        assert!(written.diff(&written).is_empty());
+        assert_eq!(
+            BitLayout::of::<StatusRegister>(0x0310u16).to_string(),
+            "StatusRegister = 0x0310\n\
+             \x20bits  | field      | raw    | value\n\
+             -------+------------+--------+-------\n\
+             \x2015:12 | (reserved) | 0b0000 |\n\
+             \x2011:8  | count      | 0b0011 | 3\n\
+             \x207:6   | (reserved) | 0b00   |\n\
+             \x205:4   | mode       | 0b01   | Active\n\
+             \x203     | ready      | 0b0    | false\n\
+             \x202:0   | (reserved) | 0b000  |\n"
+        );
+        assert_register_eq!(read_back, read_back);
+        assert_register_eq!(written, written, "written {}", "twice");
     }
 
     #[test]
-    fn test_auto_enum_definition() {
-        // Test the new enum definition with automatic TryToFromBits implementation
+    #[should_panic(
+        expected = "assertion `left == right` failed: read back\nFlagRegister:\n  flag [0]: true -> false"
+    )]
+    fn test_assert_register_eq_panics() {
         bit_register! {
-            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub struct FlagRegister: u8 {
+                pub flag: bool => [0],
+                pub other: bool => [1],
+            }
+        }
+
+        assert_register_eq!(
+            FlagRegister {
+                flag: true,
+                other: true
+            },
+            FlagRegister {
+                flag: false,
+                other: true
+            },
+            "read back"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "defmt")]
+    fn test_defmt_format() {
+        extern crate std;
+        use std::vec::Vec;
+
+        bit_register! {
+            #[bit_register(partial)]
             pub enum OperationMode: u8 {
                 Idle = 0,
-                Active = 1,
                 LowPower = 2,
-                Sleep = 3
             }
         }
 
-        // Test conversion to bits
-        let mode = OperationMode::Active;
-        let bits: u8 = TryIntoBits::try_into_bits(mode).unwrap();
-        assert_eq!(bits, 1);
+        bit_register! {
+            pub struct ControlRegister: u16 {
+                pub enabled: bool => [0],
+                pub mode: OperationMode => [1:2],
+            }
+        }
 
-        // Test conversion from bits
-        let round_trip = OperationMode::try_from_bits(bits).unwrap();
-        assert_eq!(round_trip, OperationMode::Active);
+        bit_register! {
+            #[bit_register(defmt = raw)]
+            pub struct RawRegister: u16 {
+                pub count: u8 => [0:7],
+            }
+        }
 
-        // Test invalid value
-        let result = OperationMode::try_from_bits(4u8);
-        assert!(result.is_err());
+        // The mocked logger records the encoded bytes, which include `{=str}` arguments as is
+        fn encode<T: defmt::Format>(value: &T) -> Vec<u8> {
+            defmt::export::fetch_bytes();
+            defmt::export::istr(&T::_format_tag());
+            value._format_data();
+            defmt::export::fetch_bytes()
+        }
+        fn contains(bytes: &[u8], needle: &str) -> bool {
+            bytes
+                .windows(needle.len())
+                .any(|window| window == needle.as_bytes())
+        }
+
+        let bytes = encode(&ControlRegister {
+            enabled: true,
+            mode: OperationMode::LowPower,
+        });
+        for needle in ["ControlRegister", "enabled", "mode", "LowPower"] {
+            assert!(contains(&bytes, needle), "missing {}", needle);
+        }
+
+        let bytes = encode(&RawRegister { count: 0x2a });
+        assert!(contains(&bytes, "RawRegister"));
+        assert!(!contains(&bytes, "count"));
+        assert!(bytes.contains(&0x2a));
+    }
+
+    #[test]
+    #[cfg(feature = "ufmt")]
+    fn test_ufmt() {
+        use heapless::String;
+        use ufmt::uwrite;
 
-        // Test using the enum in a register
         bit_register! {
-            #[derive(Debug, PartialEq, Eq)]
-            pub struct ControlRegister: u32 {
+            #[bit_register(partial)]
+            pub enum OperationMode: u8 {
+                Idle = 0,
+                LowPower = 2,
+            }
+        }
+
+        bit_register! {
+            pub struct ControlRegister: u16 {
                 pub enabled: bool => [0],
                 pub mode: OperationMode => [1:2],
-                pub priority: u8 => [3:5]
+                pub count: u8 => [8:15],
+                pub channel: Option<u8> => [3:5] none 7,
             }
         }
 
-        let register = ControlRegister {
+        let mut register = ControlRegister {
             enabled: true,
             mode: OperationMode::LowPower,
-            priority: 3,
+            count: 42,
+            channel: None,
         };
 
-        // Test conversion to bits
-        let value: u32 = register.try_into().unwrap();
-        // Expected: bit 0 set (enabled), bits 1-2 = 2 (LowPower), bits 3-5 = 3 (priority)
-        let expected = 0b_000_011_10_1;
-        assert_eq!(value, expected);
-
-        // Test conversion from bits
-        let round_trip = ControlRegister::try_from(value).unwrap();
-        assert_eq!(round_trip.enabled, true);
-        assert_eq!(round_trip.mode, OperationMode::LowPower);
-        assert_eq!(round_trip.priority, 3);
+        let mut buffer = String::<80>::new();
+        uwrite!(buffer, "{}", register).unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "ControlRegister { enabled: true, mode: LowPower, count: 42, channel: None }"
+        );
+
+        buffer.clear();
+        register.channel = Some(3);
+        uwrite!(buffer, "{:?}", register).unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "ControlRegister { enabled: true, mode: LowPower, count: 42, channel: Some(3) }"
+        );
+
+        buffer.clear();
+        uwrite!(buffer, "{:#?}", OperationMode::Idle).unwrap();
+        assert_eq!(buffer.as_str(), "Idle");
+
+        // Output which does not fit in the buffer is an error
+        let mut small = String::<8>::new();
+        assert!(uwrite!(small, "{}", register).is_err());
     }
 }
 
@@ -688,7 +4469,6 @@ mod test {
 mod property_tests {
     extern crate std;
 
-    use super::*;
     use proptest::prelude::*;
 
     // Register with a boolean field
@@ -701,6 +4481,7 @@ mod property_tests {
 
     // Register with different numeric field widths
     bit_register! {
+        #[bit_register(const)]
         #[derive(Debug, PartialEq, Eq, Clone, Copy)]
         struct NumericRegister: u64 {
             pub u8_small: u8 => [0:3],   // 4 bits
@@ -732,6 +4513,7 @@ mod property_tests {
 
     // Register with multiple fields of different types
     bit_register! {
+        #[bit_register(const, partial, pattern)]
         #[derive(Debug, PartialEq, Eq, Clone, Copy)]
         struct MixedRegister: u32 {
             pub flag1: bool => [0],
@@ -1013,6 +4795,84 @@ mod property_tests {
         }
     }
 
+    // Tests that the const conversions agree with the trait based conversions
+    proptest! {
+        #[test]
+        fn const_conversions_match_traits(raw_value in 0u32..0x00FFFFFF) {
+            let decoded = MixedRegister::try_from(raw_value);
+            let const_decoded = MixedRegister::try_decode_const(raw_value);
+            assert_eq!(decoded, const_decoded);
+
+            if let Ok(register) = decoded {
+                let bits: Result<u32, _> = register.try_into();
+                assert_eq!(bits, register.try_encode_const());
+            }
+        }
+
+        #[test]
+        fn const_out_of_range_matches_traits(u8_small: u8, u16_small: u16) {
+            let register = NumericRegister {
+                u8_small,
+                u8_full: 0,
+                u16_small,
+                u16_full: 0,
+                u32_small: 0,
+                u32_full: 0
+            };
+            let bits: Result<u64, _> = register.try_into();
+            assert_eq!(bits.is_ok(), register.try_encode_const().is_ok());
+        }
+    }
+
+    // Tests that applying a partial register with every field set matches a full write
+    proptest! {
+        #[test]
+        fn full_partial_apply_matches_try_into(
+            base: u32,
+            flag1 in prop::bool::ANY,
+            small_num in 0u8..=15u8,
+            medium_num in 0u16..=65535u16
+        ) {
+            let register = MixedRegister {
+                flag1,
+                flag2: false,
+                small_num,
+                enum_field: TestEnum::Variant3,
+                medium_num
+            };
+            let bits: u32 = register.try_into().unwrap();
+
+            let partial = PartialMixedRegister::from(register);
+            let (mask, _) = partial.try_mask_value().unwrap();
+            assert_eq!(partial.try_apply(base).unwrap(), (base & !mask) | bits);
+            assert_eq!(partial.try_apply(base).unwrap() & mask, bits);
+        }
+    }
+
+    // Tests that an exact pattern agrees with its mask and with decoding
+    proptest! {
+        #[test]
+        fn exact_pattern_matches_mask_and_decode(
+            raw_value: u32,
+            small_num in 0u8..=15u8,
+            enum_variant in 0u8..=3u8
+        ) {
+            let enum_value = TestEnum::decode_const(enum_variant as u32);
+            let pattern = MixedRegisterPattern::new()
+                .with_small_num(small_num)
+                .with_enum_field(enum_value);
+            let (mask, expected) = pattern.try_mask_expected().unwrap();
+
+            assert_eq!(pattern.matches(raw_value), raw_value & mask == expected);
+
+            if let Ok(register) = MixedRegister::try_from(raw_value) {
+                let decoded_matches =
+                    register.small_num == small_num && register.enum_field == enum_value;
+                assert_eq!(pattern.matches(raw_value), decoded_matches);
+            }
+        }
+    }
+
     // Test boundary values
     #[test]
     fn boundary_values_handled_correctly() {
@@ -0,0 +1,10 @@
+/// Constraint on a single field of a register pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldPattern<'a, T> {
+    /// Any value of the field matches
+    Any,
+    /// Only this value matches
+    Exactly(T),
+    /// Any of these values match, an empty set never matches
+    AnyOf(&'a [T]),
+}
@@ -0,0 +1,300 @@
+use core::fmt;
+
+use crate::{FieldInfo, FieldValue, RegisterInfo, RegisterLayout};
+
+const FIELD_HEADER: &str = "field";
+const RESERVED: &str = "(reserved)";
+const BITS_HEADER: &str = "bits";
+const RAW_HEADER: &str = "raw";
+
+/// Renders a raw register value as a table of its bits, like the register figures in datasheets
+///
+/// Every bit of the register is shown from the most significant bit down, with the field name,
+/// raw field bits and decoded value of each field. Bits which are not mapped to any field are
+/// shown as reserved. Rendering does not allocate, so it can be written to any
+/// [`core::fmt::Write`].
+///
+/// ```text
+/// StatusRegister = 0x1a5b
+///  bits  | field      | raw        | value
+/// -------+------------+------------+-------
+///  15:8  | count      | 0b00011010 | 26
+///  7:6   | (reserved) | 0b01       |
+///  5:4   | mode       | 0b01       | Active
+///  3     | ready      | 0b1        | true
+///  2:0   | (reserved) | 0b011      |
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitLayout {
+    layout: RegisterInfo,
+    raw: u64,
+}
+
+impl BitLayout {
+    /// Create a bit layout for a raw value of a register described by `layout`
+    pub const fn new(layout: RegisterInfo, raw: u64) -> Self {
+        Self { layout, raw }
+    }
+
+    /// Create a bit layout for a raw value of register `R`
+    pub fn of<R: RegisterLayout>(raw: impl Into<u64>) -> Self {
+        Self::new(R::LAYOUT, raw.into())
+    }
+
+    /// Write the rendered table to `writer`
+    pub fn write_to<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        // First pass to size the columns
+        let mut name_width = FIELD_HEADER.len().max(RESERVED.len());
+        let mut raw_width = RAW_HEADER.len();
+        for segment in self.segments() {
+            if let Some(field) = segment.field {
+                name_width = name_width.max(field.name.len());
+            }
+            raw_width = raw_width.max(segment.raw_width());
+        }
+
+        let hex_width = (self.layout.bits as usize).div_ceil(4) + 2;
+        writeln!(
+            writer,
+            "{} = {:#0hex_width$x}",
+            self.layout.name,
+            self.raw,
+            hex_width = hex_width
+        )?;
+        writeln!(
+            writer,
+            " {:<5} | {:<name_width$} | {:<raw_width$} | value",
+            BITS_HEADER, FIELD_HEADER, RAW_HEADER
+        )?;
+        writeln!(
+            writer,
+            "-------+-{:-<name_width$}-+-{:-<raw_width$}-+-------",
+            "", ""
+        )?;
+
+        for segment in self.segments() {
+            // Bit range column
+            if segment.lsb == segment.msb {
+                write!(writer, " {:<5} | ", segment.lsb)?;
+            } else {
+                let mut bits = BitsColumn::default();
+                fmt::Write::write_fmt(&mut bits, format_args!("{}:{}", segment.msb, segment.lsb))?;
+                write!(writer, " {:<5} | ", bits.as_str())?;
+            }
+
+            write!(
+                writer,
+                "{:<name_width$} | ",
+                segment.field.map_or(RESERVED, |field| field.name)
+            )?;
+
+            let raw = segment.raw(self.raw);
+            let width = segment.raw_width();
+            if segment.uses_binary() {
+                write!(writer, "{:#0width$b}", raw, width = width)?;
+            } else {
+                write!(writer, "{:#0width$x}", raw, width = width)?;
+            }
+
+            match segment.field {
+                Some(field) => {
+                    write!(writer, "{:pad$} | ", "", pad = raw_width - width)?;
+                    writeln!(writer, "{}", FieldValue { field, raw })?;
+                }
+                None => writeln!(writer, "{:pad$} |", "", pad = raw_width - width)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Iterate over the fields and reserved ranges from the most significant bit down
+    fn segments(&self) -> Segments {
+        Segments {
+            fields: self.layout.fields,
+            next_bit: Some(self.layout.bits.saturating_sub(1)),
+        }
+    }
+}
+
+impl fmt::Display for BitLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+/// A field, or a range of reserved bits between fields
+struct Segment {
+    lsb: u32,
+    msb: u32,
+    field: Option<&'static FieldInfo>,
+}
+
+impl Segment {
+    fn width(&self) -> u32 {
+        self.msb - self.lsb + 1
+    }
+
+    fn raw(&self, register: u64) -> u64 {
+        let mask = if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width()) - 1
+        };
+        (register >> self.lsb) & mask
+    }
+
+    /// Narrow fields are shown in binary, wider ones in hex
+    fn uses_binary(&self) -> bool {
+        self.width() <= 16
+    }
+
+    /// Width of the raw bits column, including the `0b` or `0x` prefix
+    fn raw_width(&self) -> usize {
+        if self.uses_binary() {
+            self.width() as usize + 2
+        } else {
+            (self.width() as usize).div_ceil(4) + 2
+        }
+    }
+}
+
+struct Segments {
+    fields: &'static [FieldInfo],
+    next_bit: Option<u32>,
+}
+
+impl Iterator for Segments {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Segment> {
+        let msb = self.next_bit?;
+
+        let segment = match self
+            .fields
+            .iter()
+            .find(|field| field.lsb <= msb && msb <= field.msb)
+        {
+            Some(field) => Segment {
+                lsb: field.lsb,
+                msb,
+                field: Some(field),
+            },
+            None => {
+                // Reserved bits run down to the highest field below them
+                let lsb = self
+                    .fields
+                    .iter()
+                    .filter(|field| field.msb < msb)
+                    .map(|field| field.msb + 1)
+                    .max()
+                    .unwrap_or(0);
+                Segment {
+                    lsb,
+                    msb,
+                    field: None,
+                }
+            }
+        };
+
+        self.next_bit = segment.lsb.checked_sub(1);
+        Some(segment)
+    }
+}
+
+/// Fixed buffer for formatting the bit range column, which is padded as a whole
+#[derive(Default)]
+struct BitsColumn {
+    buf: [u8; 8],
+    len: usize,
+}
+
+impl BitsColumn {
+    fn as_str(&self) -> &str {
+        // Only ASCII digits and ':' are written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl fmt::Write for BitsColumn {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::string::{String, ToString};
+
+    use super::*;
+    use crate::fixtures::{BASE, LAYOUT};
+
+    #[test]
+    fn test_render_table() {
+        let rendered = BitLayout::new(LAYOUT, 0x1a5b).to_string();
+        assert_eq!(
+            rendered,
+            "StatusRegister = 0x1a5b\n\
+             \x20bits  | field      | raw        | value\n\
+             -------+------------+------------+-------\n\
+             \x2015:8  | count      | 0b00011010 | 26\n\
+             \x207:6   | (reserved) | 0b01       |\n\
+             \x205:4   | mode       | 0b01       | Active\n\
+             \x203     | ready      | 0b1        | true\n\
+             \x202:0   | (reserved) | 0b011      |\n"
+        );
+    }
+
+    #[test]
+    fn test_render_invalid_enum() {
+        let rendered = BitLayout::new(LAYOUT, 0x0030).to_string();
+        assert!(rendered.contains(" 5:4   | mode       | 0b11       | <invalid 0x3>\n"));
+    }
+
+    #[test]
+    fn test_render_wide_fields() {
+        const WIDE: RegisterInfo = RegisterInfo {
+            name: "WideRegister",
+            bits: 64,
+            fields: &[FieldInfo {
+                name: "low_word",
+                msb: 31,
+                ..BASE
+            }],
+            doc: &[],
+        };
+
+        let mut rendered = String::new();
+        BitLayout::new(WIDE, 0xFFFF_0000_0000_002A)
+            .write_to(&mut rendered)
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "WideRegister = 0xffff00000000002a\n\
+             \x20bits  | field      | raw        | value\n\
+             -------+------------+------------+-------\n\
+             \x2063:32 | (reserved) | 0xffff0000 |\n\
+             \x2031:0  | low_word   | 0x0000002a | 42\n"
+        );
+    }
+
+    #[test]
+    fn test_render_without_fields() {
+        const EMPTY: RegisterInfo = RegisterInfo {
+            name: "Empty",
+            bits: 8,
+            fields: &[],
+            doc: &[],
+        };
+
+        let rendered = BitLayout::new(EMPTY, 0x81).to_string();
+        assert!(rendered.ends_with(" 7:0   | (reserved) | 0b10000001 |\n"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Helpers for the serde implementations generated by [`bit_register!`](crate::bit_register)
+//!
+//! These are not part of the public API and may change at any time.
+
+use core::fmt;
+
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, Expected, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::{Deserialize, Serializer};
+
+/// Serialize an enum variant by name
+pub fn serialize_variant<S: Serializer>(
+    serializer: S,
+    enum_name: &'static str,
+    variants: &'static [&'static str],
+    variant: &'static str,
+) -> Result<S::Ok, S::Error> {
+    let index = variants
+        .iter()
+        .position(|name| *name == variant)
+        .unwrap_or_default();
+    serializer.serialize_unit_variant(enum_name, index as u32, variant)
+}
+
+/// Deserialize an enum variant by name, returning its index in `variants`
+pub fn deserialize_variant<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    enum_name: &'static str,
+    variants: &'static [&'static str],
+) -> Result<usize, D::Error> {
+    deserializer.deserialize_enum(
+        enum_name,
+        variants,
+        VariantVisitor {
+            enum_name,
+            variants,
+        },
+    )
+}
+
+/// Deserialize the next key of a register's field map, returning the field's name
+pub fn next_field<'de, A: MapAccess<'de>>(
+    map: &mut A,
+    fields: &'static [&'static str],
+) -> Result<Option<&'static str>, A::Error> {
+    let index = map.next_key_seed(Identifier {
+        names: fields,
+        kind: IdentifierKind::Field,
+    })?;
+    Ok(index.map(|index| fields[index]))
+}
+
+/// Deserialize the next field of a register serialized as a sequence
+pub fn next_element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(
+    seq: &mut A,
+    fields: &'static [&'static str],
+    field: &'static str,
+    expected: &dyn Expected,
+) -> Result<T, A::Error> {
+    match seq.next_element()? {
+        Some(value) => Ok(value),
+        None => {
+            let index = fields
+                .iter()
+                .position(|name| *name == field)
+                .unwrap_or_default();
+            Err(de::Error::invalid_length(index, expected))
+        }
+    }
+}
+
+struct VariantVisitor {
+    enum_name: &'static str,
+    variants: &'static [&'static str],
+}
+
+impl<'de> Visitor<'de> for VariantVisitor {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "enum {}", self.enum_name)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<usize, A::Error> {
+        let (index, variant) = data.variant_seed(Identifier {
+            names: self.variants,
+            kind: IdentifierKind::Variant,
+        })?;
+        variant.unit_variant()?;
+        Ok(index)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum IdentifierKind {
+    Field,
+    Variant,
+}
+
+/// Deserializes a field or variant identifier by name or by index
+#[derive(Clone, Copy)]
+struct Identifier {
+    names: &'static [&'static str],
+    kind: IdentifierKind,
+}
+
+impl Identifier {
+    fn index_of<E: de::Error>(&self, value: &str) -> Result<usize, E> {
+        match self.names.iter().position(|name| *name == value) {
+            Some(index) => Ok(index),
+            None => match self.kind {
+                IdentifierKind::Field => Err(E::unknown_field(value, self.names)),
+                IdentifierKind::Variant => Err(E::unknown_variant(value, self.names)),
+            },
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Identifier {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Identifier {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            IdentifierKind::Field => f.write_str("field identifier"),
+            IdentifierKind::Variant => f.write_str("variant identifier"),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<usize, E> {
+        match usize::try_from(value) {
+            Ok(index) if index < self.names.len() => Ok(index),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(value), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<usize, E> {
+        self.index_of(value)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<usize, E> {
+        match core::str::from_utf8(value) {
+            Ok(value) => self.index_of(value),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(value), &self)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use std::string::ToString;
+
+    use crate::bit_register;
+
+    bit_register! {
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        pub enum OperationMode: u8 {
+            Idle = 0,
+            Active = 1,
+            LowPower = 2,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct ControlRegister: u16 {
+            pub enabled: bool => [0],
+            pub mode: OperationMode => [1:2],
+            pub priority: u8 => [3:5],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        #[bit_register(serde = raw)]
+        pub enum RawMode: u8 {
+            Off = 0,
+            On = 3,
+        }
+    }
+
+    bit_register! {
+        /// Serialized as its underlying value
+        #[bit_register(serde = raw)]
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct RawRegister: u8 {
+            pub mode: RawMode => [0:1],
+            pub count: u8 => [4:7],
+        }
+    }
+
+    #[test]
+    fn test_field_map() {
+        let register = ControlRegister {
+            enabled: true,
+            mode: OperationMode::LowPower,
+            priority: 5,
+        };
+
+        let json = serde_json::to_string(&register).unwrap();
+        assert_eq!(json, r#"{"enabled":true,"mode":"LowPower","priority":5}"#);
+        assert_eq!(
+            serde_json::from_str::<ControlRegister>(&json).unwrap(),
+            register
+        );
+
+        // Fields may be given in any order
+        assert_eq!(
+            serde_json::from_str::<ControlRegister>(
+                r#"{"priority":5,"mode":"LowPower","enabled":true}"#
+            )
+            .unwrap(),
+            register
+        );
+    }
+
+    #[test]
+    fn test_field_map_validation() {
+        let error = |json| {
+            serde_json::from_str::<ControlRegister>(json)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error(r#"{"enabled":true,"mode":"Idle","priority":8}"#)
+            .starts_with("priority exceeds maximum value for its bit width"));
+        assert!(error(r#"{"enabled":true,"mode":"Turbo","priority":0}"#)
+            .starts_with("unknown variant `Turbo`"));
+        assert!(error(r#"{"enabled":true,"mode":"Idle"}"#).starts_with("missing field `priority`"));
+        assert!(
+            error(r#"{"enabled":true,"mode":"Idle","priority":0,"extra":1}"#)
+                .starts_with("unknown field `extra`")
+        );
+        assert!(
+            error(r#"{"enabled":true,"enabled":false,"mode":"Idle","priority":0}"#)
+                .starts_with("duplicate field `enabled`")
+        );
+
+        // Out of range values are not serialized either
+        let invalid = ControlRegister {
+            enabled: false,
+            mode: OperationMode::Idle,
+            priority: 8,
+        };
+        assert!(serde_json::to_string(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_option_fields() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct FaultRegister: u8 {
+                pub channel: Option<u8> => [0:3] none 0xf,
+                pub enabled: bool => [7],
+            }
+        }
+
+        let register = FaultRegister {
+            channel: None,
+            enabled: true,
+        };
+        let json = serde_json::to_string(&register).unwrap();
+        assert_eq!(json, r#"{"channel":null,"enabled":true}"#);
+        assert_eq!(
+            serde_json::from_str::<FaultRegister>(&json).unwrap(),
+            register
+        );
+        assert_eq!(
+            serde_json::from_str::<FaultRegister>(r#"{"channel":3,"enabled":true}"#).unwrap(),
+            FaultRegister {
+                channel: Some(3),
+                enabled: true,
+            }
+        );
+        assert!(
+            serde_json::from_str::<FaultRegister>(r#"{"channel":15,"enabled":true}"#)
+                .unwrap_err()
+                .to_string()
+                .starts_with("channel is the raw value reserved for None")
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        bit_register! {
+            #[derive(Debug, PartialEq, Eq)]
+            #[bit_register(validate = check_window)]
+            pub struct WindowRegister: u16 {
+                pub low: u8 => [0:7],
+                pub high: u8 => [8:15],
+            }
+        }
+
+        const fn check_window(window: &WindowRegister) -> Result<(), &'static str> {
+            if window.low > window.high {
+                Err("low must not exceed high")
+            } else {
+                Ok(())
+            }
+        }
+
+        assert_eq!(
+            serde_json::from_str::<WindowRegister>(r#"{"low":1,"high":2}"#).unwrap(),
+            WindowRegister { low: 1, high: 2 }
+        );
+        assert!(
+            serde_json::from_str::<WindowRegister>(r#"{"low":3,"high":2}"#)
+                .unwrap_err()
+                .to_string()
+                .starts_with("low must not exceed high")
+        );
+        assert!(serde_json::to_string(&WindowRegister { low: 3, high: 2 }).is_err());
+    }
+
+    #[test]
+    fn test_raw() {
+        let register = RawRegister {
+            mode: RawMode::On,
+            count: 9,
+        };
+
+        let json = serde_json::to_string(&register).unwrap();
+        assert_eq!(json, "147");
+        assert_eq!(
+            serde_json::from_str::<RawRegister>(&json).unwrap(),
+            register
+        );
+
+        assert_eq!(serde_json::to_string(&RawMode::On).unwrap(), "3");
+        assert_eq!(serde_json::from_str::<RawMode>("0").unwrap(), RawMode::Off);
+
+        // Raw values are validated like `TryFrom`
+        assert!(serde_json::from_str::<RawRegister>("2")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid value for enum RawMode"));
+        assert!(serde_json::from_str::<RawMode>("1").is_err());
+        assert!(serde_json::from_str::<RawRegister>("256").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
 use num_traits::{One, Zero};
 
 /// Trait for types that are a bit register which can be converted to and from an unsigned integer type.
@@ -46,6 +48,31 @@ macro_rules! impl_try_into_from_bits {
 
 impl_try_into_from_bits!(u8 => 1, u16 => 2, u32 => 4, u64 => 8);
 
+// Non-zero integers convert like their primitive, except that a bit pattern of zero is an error
+macro_rules! impl_non_zero_bits {
+    ($($t:ident => $primitive:ty),*) => {
+        $(
+            impl NumBytes for $t {
+                const NUM_BYTES: usize = <$primitive as NumBytes>::NUM_BYTES;
+            }
+            impl<T: TryFrom<$primitive>> TryIntoBits<T> for $t {
+                fn try_into_bits(self) -> Result<T, &'static str> {
+                    TryInto::try_into(self.get()).map_err(|_| concat!(stringify!($t), " value too large for target type"))
+                }
+            }
+            impl<T> TryFromBits<T> for $t where $primitive: TryFrom<T> {
+                fn try_from_bits(bits: T) -> Result<Self, &'static str> {
+                    let value = <$primitive as TryFromBits<T>>::try_from_bits(bits)
+                        .map_err(|_| concat!("bit pattern too large for target type ", stringify!($t)))?;
+                    $t::new(value).ok_or(concat!("bit pattern is zero for target type ", stringify!($t)))
+                }
+            }
+        )+
+    }
+}
+
+impl_non_zero_bits!(NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64);
+
 // Bool gets its own special impls
 impl NumBytes for bool {
     const NUM_BYTES: usize = 1;
@@ -67,6 +94,45 @@ impl<T: One + Zero + PartialEq<T>> TryFromBits<T> for bool {
     }
 }
 
+/// Trait for the integer types of scaled fields, which hold a physical value such as millivolts
+#[diagnostic::on_unimplemented(
+    message = "scaled fields need an integer type, not `{Self}`",
+    note = "fixed-point types are scaled by their own fraction bits, so they cannot have a `scale`"
+)]
+pub trait ScaledType: Sized {
+    /// Smallest value of the type
+    const MIN: i128;
+    /// Largest value of the type
+    const MAX: i128;
+
+    /// Convert a physical value to the type, if it fits
+    fn from_physical(physical: i128) -> Option<Self>;
+
+    /// Convert the value to a physical value
+    fn to_physical(self) -> i128;
+}
+
+macro_rules! impl_scaled_type {
+    ($($t:ty),*) => {
+        $(
+            impl ScaledType for $t {
+                const MIN: i128 = <$t>::MIN as i128;
+                const MAX: i128 = <$t>::MAX as i128;
+
+                fn from_physical(physical: i128) -> Option<Self> {
+                    TryFrom::try_from(physical).ok()
+                }
+
+                fn to_physical(self) -> i128 {
+                    self.into()
+                }
+            }
+        )*
+    }
+}
+
+impl_scaled_type!(u8, u16, u32, u64, i8, i16, i32, i64);
+
 #[cfg(test)]
 mod test {
     extern crate std;
@@ -86,12 +152,47 @@ mod test {
     fn test_bool() {
         assert_eq!(TryIntoBits::<u8>::try_into_bits(false).unwrap(), 0u8);
         assert_eq!(TryIntoBits::<u8>::try_into_bits(true).unwrap(), 1u8);
+        assert!(!<bool as TryFromBits<u8>>::try_from_bits(0u8).unwrap());
+        assert!(<bool as TryFromBits<u8>>::try_from_bits(1u8).unwrap());
+        assert!(<bool as TryFromBits<u8>>::try_from_bits(2u8).is_err());
+    }
+
+    #[test]
+    fn test_scaled_type() {
+        assert_eq!(<i8 as ScaledType>::MIN, -128);
+        assert_eq!(<u64 as ScaledType>::MAX, u64::MAX as i128);
+        assert_eq!(u8::from_physical(255), Some(255));
+        assert_eq!(u8::from_physical(256), None);
+        assert_eq!(i16::from_physical(-40), Some(-40));
+        assert_eq!((-40i16).to_physical(), -40);
+    }
+
+    #[test]
+    fn test_non_zero() {
+        let divider = NonZeroU8::new(3).unwrap();
+        assert_eq!(<NonZeroU8 as NumBytes>::NUM_BYTES, 1);
+        assert_eq!(TryIntoBits::<u16>::try_into_bits(divider), Ok(3u16));
+        assert_eq!(
+            <NonZeroU8 as TryFromBits<u16>>::try_from_bits(3u16),
+            Ok(divider)
+        );
+        assert_eq!(
+            <NonZeroU8 as TryFromBits<u16>>::try_from_bits(0u16),
+            Err("bit pattern is zero for target type NonZeroU8")
+        );
+        assert_eq!(
+            <NonZeroU8 as TryFromBits<u16>>::try_from_bits(0x100u16),
+            Err("bit pattern too large for target type NonZeroU8")
+        );
+        assert_eq!(
+            TryIntoBits::<u8>::try_into_bits(NonZeroU16::new(0x100).unwrap()),
+            Err("NonZeroU16 value too large for target type")
+        );
         assert_eq!(
-            <bool as TryFromBits<u8>>::try_from_bits(0u8).unwrap(),
-            false
+            <NonZeroU64 as TryFromBits<u64>>::try_from_bits(u64::MAX),
+            Ok(NonZeroU64::MAX)
         );
-        assert_eq!(<bool as TryFromBits<u8>>::try_from_bits(1u8).unwrap(), true);
-        assert_eq!(<bool as TryFromBits<u8>>::try_from_bits(2u8).is_err(), true);
+        assert_eq!(TryIntoBits::<u32>::try_into_bits(NonZeroU32::MIN), Ok(1u32));
     }
 
     proptest! {
@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::registry::parse_number;
+use crate::Registry;
+
+const USAGE: &str = "\
+Usage: bitreg [--description <FILE>]... <COMMAND>
+
+Decode and encode raw register values.
+
+Commands:
+  list                                 List the known registers
+  decode <REGISTER> <VALUE>            Show the fields of a raw value
+  encode <REGISTER> <FIELD=VALUE>...   Build a raw value from field values
+
+Options:
+  -d, --description <FILE>  Load registers from a .toml, .json, .svd or .rdl file, can be repeated
+  -f, --from <VALUE>        Start encoding from a raw value instead of zero
+  -h, --help                Print this message";
+
+enum Command {
+    List,
+    Decode {
+        register: String,
+        value: u64,
+    },
+    Encode {
+        register: String,
+        from: u64,
+        assignments: Vec<(String, String)>,
+    },
+}
+
+struct Args {
+    descriptions: Vec<PathBuf>,
+    command: Command,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut descriptions = Vec::new();
+    let mut from = None;
+    let mut positional = Vec::new();
+
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--description" => {
+                descriptions.push(PathBuf::from(
+                    args.next().ok_or("--description requires a file")?,
+                ));
+            }
+            "-f" | "--from" => {
+                let value = args.next().ok_or("--from requires a value")?;
+                from = Some(parse_value(&value)?);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("list") => Command::List,
+        Some("decode") => {
+            let register = positional.next().ok_or("decode requires a register")?;
+            let value = positional.next().ok_or("decode requires a value")?;
+            Command::Decode {
+                register,
+                value: parse_value(&value)?,
+            }
+        }
+        Some("encode") => {
+            let register = positional.next().ok_or("encode requires a register")?;
+            let assignments = positional
+                .by_ref()
+                .map(|assignment| {
+                    assignment
+                        .split_once('=')
+                        .map(|(field, value)| (field.to_string(), value.to_string()))
+                        .ok_or_else(|| format!("expected FIELD=VALUE, not {}", assignment))
+                })
+                .collect::<Result<_, _>>()?;
+            Command::Encode {
+                register,
+                from: from.take().unwrap_or(0),
+                assignments,
+            }
+        }
+        Some(command) => return Err(format!("unknown command {}", command)),
+        None => return Err("missing command".into()),
+    };
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument {}", extra));
+    }
+    if from.is_some() {
+        return Err("--from only applies to encode".into());
+    }
+    Ok(Some(Args {
+        descriptions,
+        command,
+    }))
+}
+
+fn parse_value(value: &str) -> Result<u64, String> {
+    parse_number(value).ok_or_else(|| format!("invalid value {}", value))
+}
+
+/// Run `bitreg` with command line arguments, excluding the program name
+///
+/// Registers from `--description` files are added to `registry`, so a binary can combine
+/// compiled-in `bit_register!` types with description files:
+///
+/// ```no_run
+/// # use bit_register::bit_register;
+/// # bit_register! {
+/// #     pub struct ChargerStatus: u16 {
+/// #         pub present: bool => [0],
+/// #     }
+/// # }
+/// let mut registry = bitreg::Registry::new();
+/// registry.add::<ChargerStatus, u16>();
+/// let exit_code = bitreg::run(registry, std::env::args().skip(1));
+/// # let _ = exit_code;
+/// ```
+pub fn run(mut registry: Registry, args: impl IntoIterator<Item = String>) -> ExitCode {
+    let args = match parse_args(args.into_iter()) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for path in &args.descriptions {
+        if let Err(error) = registry.load(path) {
+            eprintln!("error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let result = match &args.command {
+        Command::List => {
+            for name in registry.names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        Command::Decode { register, value } => {
+            registry
+                .decode(register, *value)
+                .and_then(|(layout, valid)| {
+                    print!("{}", layout);
+                    valid
+                })
+        }
+        Command::Encode {
+            register,
+            from,
+            assignments,
+        } => {
+            let assignments: Vec<(&str, &str)> = assignments
+                .iter()
+                .map(|(field, value)| (field.as_str(), value.as_str()))
+                .collect();
+            registry
+                .encode(register, *from, &assignments)
+                .and_then(|raw| {
+                    let bits = registry.layout(register)?.bits;
+                    println!("{:#0width$x}", raw, width = bits.div_ceil(4) as usize + 2);
+                    Ok(())
+                })
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
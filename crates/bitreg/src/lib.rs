@@ -0,0 +1,37 @@
+//! # bitreg
+//!
+//! Decodes raw register values into their fields and encodes field values back into raw
+//! values, for bring-up work where values come from logic analyzers and debug prints.
+//!
+//! ## Overview
+//!
+//! A [`Registry`] holds the registers which can be decoded. Registers are added from
+//! `bit_register!` types with [`Registry::add`], which validates values by converting them to
+//! the type and back exactly as firmware would, or from description files read by
+//! `bit-register-codegen` with [`Registry::load`]. Decoded values are shown as a
+//! [`BitLayout`](bit_register::BitLayout) table.
+//!
+//! ## Command Line
+//!
+//! The `bitreg` binary loads registers from description files:
+//!
+//! ```text
+//! $ bitreg --description charger.toml decode CHARGER_STATUS 0x1a05
+//! CHARGER.STATUS = 0x1a05
+//!  bits  | field      | raw        | value
+//! -------+------------+------------+-------
+//!  15:8  | CURRENT    | 0b00011010 | 26
+//!  7:3   | (reserved) | 0b00000    |
+//!  2:1   | STATE      | 0b10       | FAST
+//!  0     | PRESENT    | 0b1        | true
+//! $ bitreg --description charger.toml encode CHARGER_STATUS present=true state=fast current=26
+//! 0x1a05
+//! ```
+//!
+//! A crate with its own `bit_register!` types can build a binary with them compiled in by
+//! calling [`run`] with a registry of its types.
+
+mod cli;
+mod registry;
+pub use cli::*;
+pub use registry::*;
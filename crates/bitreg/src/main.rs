@@ -0,0 +1,7 @@
+//! Decode and encode raw register values from description files
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    bitreg::run(bitreg::Registry::new(), std::env::args().skip(1))
+}
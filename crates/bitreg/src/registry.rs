@@ -0,0 +1,707 @@
+use std::path::Path;
+
+use bit_register::{
+    Bcd, BitLayout, BitRegister, FieldInfo, FieldKind, FieldScale, Gray, RegisterInfo,
+    RegisterLayout, ValueRange, VariantInfo,
+};
+use bit_register_codegen::{Build, Device, Error, Register};
+
+/// Checks a raw value and returns it as the register would encode it again
+type Check = fn(&RegisterInfo, u64) -> Result<u64, String>;
+
+/// A register which can be decoded and encoded
+struct Entry {
+    layout: RegisterInfo,
+    check: Check,
+}
+
+/// The registers known to `bitreg`, from `bit_register!` types and description files
+///
+/// Registers are looked up by name ignoring case and underscores, so `CHARGER_STATUS` finds a
+/// type named `ChargerStatus`. Registers from descriptions are named `PERIPHERAL.REGISTER`,
+/// which can also be written `PERIPHERAL_REGISTER`, or just `REGISTER` when it is unique. A
+/// name which matches a register exactly always finds it.
+#[derive(Default)]
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `bit_register!` type whose underlying type is `U`
+    ///
+    /// Values are checked by converting them to the type and back, so they are validated
+    /// exactly as firmware using the type would validate them.
+    pub fn add<R, U>(&mut self) -> &mut Self
+    where
+        R: BitRegister<U> + RegisterLayout,
+        U: TryFrom<u64> + Into<u64>,
+    {
+        self.entries.push(Entry {
+            layout: R::LAYOUT,
+            check: check_type::<R, U>,
+        });
+        self
+    }
+
+    /// Add every register of a device
+    ///
+    /// There is no type to convert to, so values are checked against the layout instead: they
+    /// must fit in the register, and fields with named values must hold one of them. Layouts
+    /// are leaked to give them the `'static` lifetime of the library's metadata, which is fine
+    /// for the lifetime of a command.
+    pub fn add_device(&mut self, device: &Device) -> &mut Self {
+        for peripheral in &device.peripherals {
+            for register in &peripheral.registers {
+                let name = format!("{}.{}", peripheral.name, register.name);
+                self.entries.push(Entry {
+                    layout: leak_layout(name, register),
+                    check: check_layout,
+                });
+            }
+        }
+        self
+    }
+
+    /// Add every register of a description file, chosen by its extension
+    ///
+    /// `.toml`, `.json`, `.svd` and `.rdl` files are read with `bit-register-codegen`.
+    pub fn load(&mut self, path: &Path) -> Result<&mut Self, Error> {
+        let build = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Build::toml(path),
+            Some("json") => Build::json(path),
+            Some("svd") | Some("xml") => Build::svd(path),
+            Some("rdl") => Build::rdl(path),
+            _ => {
+                return Err(Error::new(format!(
+                    "{}: unknown description format, expected .toml, .json, .svd or .rdl",
+                    path.display()
+                )))
+            }
+        };
+        Ok(self.add_device(&build.device()?))
+    }
+
+    /// Names of every register, in the order they were added
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|entry| entry.layout.name)
+    }
+
+    /// Find the layout of a register by name
+    pub fn layout(&self, name: &str) -> Result<&RegisterInfo, Error> {
+        self.entry(name).map(|entry| &entry.layout)
+    }
+
+    /// Decode a raw value of a register into a bit layout
+    ///
+    /// The layout is returned even when the value is invalid, so the fields can still be shown
+    /// alongside the error.
+    pub fn decode(&self, name: &str, raw: u64) -> Result<(BitLayout, Result<(), Error>), Error> {
+        let entry = self.entry(name)?;
+        let valid = (entry.check)(&entry.layout, raw)
+            .map(|_| ())
+            .map_err(|message| Error::new(format!("{}: {}", entry.layout.name, message)));
+        Ok((BitLayout::new(entry.layout, raw), valid))
+    }
+
+    /// Encode field assignments into a raw value of a register, starting from `base`
+    ///
+    /// Values are numbers, `true` or `false` for single bit fields, the names of named values,
+    /// or `none` for `Option` fields. Scaled fields take their physical value, optionally
+    /// followed by its unit. Fixed-point, BCD and Gray code fields and fields with a lookup table
+    /// take a decimal value, or their raw bits in hex or binary. Fields which are not assigned
+    /// keep their bits from `base`, and bits which are not part of any field are cleared.
+    pub fn encode(
+        &self,
+        name: &str,
+        base: u64,
+        assignments: &[(&str, &str)],
+    ) -> Result<u64, Error> {
+        let entry = self.entry(name)?;
+        let error = |message: String| Error::new(format!("{}: {}", entry.layout.name, message));
+
+        let mut raw = base;
+        for (field_name, value) in assignments {
+            let field = entry
+                .layout
+                .fields
+                .iter()
+                .find(|field| same_name(field.name, field_name))
+                .ok_or_else(|| error(format!("no field named {}", field_name)))?;
+            let value = field_value(field, value).map_err(error)?;
+            if value > field.value_mask() {
+                // The library's message for a field which does not fit
+                return Err(error(format!(
+                    "{} exceeds maximum value for its bit width",
+                    field.name
+                )));
+            }
+            raw = (raw & !field.mask()) | (value << field.lsb);
+        }
+        (entry.check)(&entry.layout, raw).map_err(error)
+    }
+
+    fn entry(&self, name: &str) -> Result<&Entry, Error> {
+        if let Some(entry) = self.entries.iter().find(|entry| entry.layout.name == name) {
+            return Ok(entry);
+        }
+
+        let mut matches: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|entry| same_name(entry.layout.name, name))
+            .collect();
+        if matches.is_empty() {
+            // Registers from descriptions can be named without their peripheral
+            matches = self
+                .entries
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .layout
+                        .name
+                        .rsplit_once('.')
+                        .is_some_and(|(_, register)| same_name(register, name))
+                })
+                .collect();
+        }
+
+        match matches.as_slice() {
+            [entry] => Ok(entry),
+            [] => Err(Error::new(format!("no register named {}", name))),
+            entries => Err(Error::new(format!(
+                "{} is ambiguous, it could be {}",
+                name,
+                entries
+                    .iter()
+                    .map(|entry| entry.layout.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+fn check_type<R, U>(_: &RegisterInfo, raw: u64) -> Result<u64, String>
+where
+    R: BitRegister<U>,
+    U: TryFrom<u64> + Into<u64>,
+{
+    let bits = U::try_from(raw).map_err(|_| format!("{:#x} does not fit in the register", raw))?;
+    let register = R::try_from(bits)?;
+    let bits: U = register.try_into()?;
+    Ok(bits.into())
+}
+
+fn check_layout(layout: &RegisterInfo, raw: u64) -> Result<u64, String> {
+    if layout.bits < 64 && raw >> layout.bits != 0 {
+        return Err(format!(
+            "{:#x} does not fit in a {} bit register",
+            raw, layout.bits
+        ));
+    }
+    let mut encoded = 0;
+    for field in layout.fields {
+        let value = field.extract(raw);
+        let named = field.none == Some(value)
+            || match field.kind {
+                FieldKind::Enum(variants) => variants.iter().any(|variant| variant.value == value),
+                FieldKind::Bcd => Bcd::<u64>::try_decode_const(value).is_ok(),
+                FieldKind::Bool
+                | FieldKind::Unsigned
+                | FieldKind::Fixed { .. }
+                | FieldKind::Gray => true,
+            };
+        if !named || !field.is_valid(value) {
+            return Err(format!("invalid value {:#x} for {}", value, field.name));
+        }
+        encoded |= value << field.lsb;
+    }
+    Ok(encoded)
+}
+
+/// Parse the value assigned to a field
+fn field_value(field: &FieldInfo, value: &str) -> Result<u64, String> {
+    if let Some(none) = field.none.filter(|_| value.eq_ignore_ascii_case("none")) {
+        return Ok(none);
+    }
+    if let Some(scale) = field.scale {
+        return scaled_value(field, scale, value);
+    }
+    let lower = value.to_ascii_lowercase();
+    if !field.table.is_empty() && !lower.starts_with("0x") && !lower.starts_with("0b") {
+        return table_value(field, value);
+    }
+    match (field.kind, value.to_ascii_lowercase().as_str()) {
+        (FieldKind::Fixed { signed, frac, .. }, number)
+            if !number.starts_with("0x") && !number.starts_with("0b") =>
+        {
+            return fixed_value(field, signed, frac, value);
+        }
+        (FieldKind::Bcd, number) if !number.starts_with("0x") && !number.starts_with("0b") => {
+            return parse_number(value)
+                .and_then(Bcd::<u64>::new)
+                .map(|bcd| bcd.encode_const())
+                .ok_or_else(|| format!("invalid value {} for {}", value, field.name));
+        }
+        (FieldKind::Gray, number) if !number.starts_with("0x") && !number.starts_with("0b") => {
+            return parse_number(value)
+                .map(|number| Gray::<u64>::new(number).encode_const())
+                .ok_or_else(|| format!("invalid value {} for {}", value, field.name));
+        }
+        (FieldKind::Bool, "true") => return Ok(1),
+        (FieldKind::Bool, "false") => return Ok(0),
+        (FieldKind::Enum(variants), _) => {
+            if let Some(variant) = variants
+                .iter()
+                .find(|variant| same_name(variant.name, value))
+            {
+                return Ok(variant.value);
+            }
+        }
+        _ => {}
+    }
+    parse_number(value).ok_or_else(|| format!("invalid value {} for {}", value, field.name))
+}
+
+/// Parse the physical value assigned to a scaled field, which may be followed by its unit
+fn scaled_value(field: &FieldInfo, scale: FieldScale, value: &str) -> Result<u64, String> {
+    let number = value.strip_suffix(scale.unit).unwrap_or(value).trim();
+    let physical = match number.strip_prefix('-') {
+        Some(magnitude) => parse_number(magnitude).map(|magnitude| -i128::from(magnitude)),
+        None => parse_number(number).map(i128::from),
+    }
+    .ok_or_else(|| format!("invalid value {} for {}", value, field.name))?;
+    u64::try_from(scale.raw(physical))
+        .ok()
+        .filter(|raw| *raw <= field.value_mask())
+        .ok_or_else(|| {
+            // The library's message for a scaled field which does not fit
+            format!("{} is out of range for its scale and bit width", field.name)
+        })
+}
+
+/// Parse the decimal value assigned to a field with a lookup table, which is encoded as its index
+fn table_value(field: &FieldInfo, value: &str) -> Result<u64, String> {
+    let number = value.trim();
+    let number = match number.strip_prefix('-') {
+        Some(magnitude) => parse_number(magnitude).map(|magnitude| -i128::from(magnitude)),
+        None => parse_number(number).map(i128::from),
+    }
+    .ok_or_else(|| format!("invalid value {} for {}", value, field.name))?;
+    field
+        .table
+        .iter()
+        .position(|entry| *entry == number)
+        .map(|index| index as u64)
+        // The library's message for a value which is not in the table
+        .ok_or_else(|| format!("{} is not in its lookup table", field.name))
+}
+
+/// Parse the decimal value assigned to a fixed-point field, rounded to the nearest step
+fn fixed_value(field: &FieldInfo, signed: bool, frac: u32, value: &str) -> Result<u64, String> {
+    let number: f64 = value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|number: &f64| number.is_finite())
+        .ok_or_else(|| format!("invalid value {} for {}", value, field.name))?;
+    let scaled = number * (1u128 << frac) as f64;
+    let raw = scaled.round() as i128;
+    let width = field.width();
+    let (min, max) = if signed {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    };
+    if !(min..=max).contains(&raw) {
+        // The library's message for a field which does not fit
+        return Err(format!(
+            "{} exceeds maximum value for its bit width",
+            field.name
+        ));
+    }
+    Ok(raw as u64 & field.value_mask())
+}
+
+/// Parse a decimal, `0x` hex or `0b` binary number, which may contain `_` separators
+pub(crate) fn parse_number(raw: &str) -> Option<u64> {
+    let raw = raw.trim().replace('_', "");
+    if let Some(hex) = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = raw.strip_prefix("0b").or_else(|| raw.strip_prefix("0B")) {
+        u64::from_str_radix(binary, 2).ok()
+    } else {
+        raw.parse().ok()
+    }
+}
+
+/// Compare names ignoring case, underscores and the `.` after a peripheral
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| !matches!(c, '_' | '.'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    normalize(a) == normalize(b)
+}
+
+fn leak_layout(name: String, register: &Register) -> RegisterInfo {
+    let fields: Vec<FieldInfo> = register
+        .fields
+        .iter()
+        .map(|field| FieldInfo {
+            name: leak(&field.name),
+            lsb: field.lsb,
+            msb: field.msb,
+            kind: match &field.enumeration {
+                Some(enumeration) => FieldKind::Enum(Vec::leak(
+                    enumeration
+                        .variants
+                        .iter()
+                        .map(|variant| VariantInfo {
+                            name: leak(&variant.name),
+                            value: variant.value,
+                            doc: &[],
+                        })
+                        .collect(),
+                )),
+                None if field.width() == 1 => FieldKind::Bool,
+                None => FieldKind::Unsigned,
+            },
+            valid: Vec::leak(
+                field
+                    .valid
+                    .iter()
+                    .map(|range| ValueRange {
+                        min: *range.start(),
+                        max: *range.end(),
+                    })
+                    .collect(),
+            ),
+            scale: field.scale.as_ref().map(|scale| FieldScale {
+                scale: scale.scale,
+                offset: scale.offset,
+                unit: leak(&scale.unit),
+            }),
+            none: None,
+            table: &[],
+            doc: &[],
+        })
+        .collect();
+    RegisterInfo {
+        name: String::leak(name),
+        bits: register.size,
+        fields: Vec::leak(fields),
+        doc: &[],
+    }
+}
+
+fn leak(text: &str) -> &'static str {
+    String::leak(text.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use bit_register::bit_register;
+    use bit_register_codegen::description;
+
+    use super::*;
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ChargeState: u8 {
+            Idle = 0,
+            Trickle = 1,
+            Fast = 2,
+        }
+    }
+
+    type Gain = bit_register::Q<2, 6>;
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct AmplifierControl: u8 {
+            pub gain: Gain => [0:7],
+        }
+    }
+
+    type Digits = Bcd<u8>;
+    type Position = Gray<u8>;
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct TimerDial: u16 {
+            pub minutes: Digits => [0:6],
+            pub position: Position => [8:11],
+            pub alarm: Option<u8> => [12:15] none 0xf,
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct WatchdogControl: u8 {
+            pub timeout: u16 => [0:1] table [1, 4, 16, 64],
+            pub enabled: bool => [7],
+        }
+    }
+
+    bit_register! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ChargerStatus: u16 {
+            pub present: bool => [0],
+            pub state: ChargeState => [1:2],
+            pub limit: u16 => [3:7] scale 100 offset 3000 unit "mV",
+            pub current: u8 => [8:15],
+        }
+    }
+
+    const DESCRIPTION: &str = r#"
+name = "EC"
+
+[[peripherals]]
+name = "CHARGER"
+base_address = 0
+
+[[peripherals.registers]]
+name = "STATUS"
+offset = 0
+size = 16
+fields = [
+    { name = "PRESENT", bits = 0 },
+    { name = "STATE", bits = [1, 2], values = [
+        { name = "IDLE", value = 0 },
+        { name = "FAST", value = 2 },
+    ] },
+]
+
+[[peripherals]]
+name = "GAUGE"
+base_address = 0x100
+
+[[peripherals.registers]]
+name = "STATUS"
+offset = 0
+size = 8
+"#;
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.add::<ChargerStatus, u16>();
+        registry.add::<AmplifierControl, u8>();
+        registry.add::<TimerDial, u16>();
+        registry.add::<WatchdogControl, u8>();
+        registry.add_device(&description::parse_toml(DESCRIPTION).unwrap());
+        registry
+    }
+
+    #[test]
+    fn test_lookup() {
+        let registry = registry();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            [
+                "ChargerStatus",
+                "AmplifierControl",
+                "TimerDial",
+                "WatchdogControl",
+                "CHARGER.STATUS",
+                "GAUGE.STATUS"
+            ]
+        );
+        assert_eq!(registry.layout("ChargerStatus").unwrap().bits, 16);
+        assert_eq!(
+            registry.layout("CHARGER_STATUS").unwrap_err().message(),
+            "CHARGER_STATUS is ambiguous, it could be ChargerStatus, CHARGER.STATUS"
+        );
+        assert_eq!(registry.layout("gauge.status").unwrap().bits, 8);
+        assert_eq!(registry.layout("GAUGE_STATUS").unwrap().bits, 8);
+        assert_eq!(
+            registry.layout("STATUS").unwrap_err().message(),
+            "STATUS is ambiguous, it could be CHARGER.STATUS, GAUGE.STATUS"
+        );
+        assert!(registry.layout("MISSING").is_err());
+    }
+
+    #[test]
+    fn test_decode() {
+        let registry = registry();
+        let (layout, valid) = registry.decode("ChargerStatus", 0x1a05).unwrap();
+        assert!(valid.is_ok());
+        assert!(layout.to_string().contains("| Fast"));
+        assert!(layout.to_string().contains("| 3000 mV"));
+
+        // Invalid values still decode, alongside the library's error
+        let (_, valid) = registry.decode("ChargerStatus", 0x0006).unwrap();
+        assert_eq!(
+            valid.unwrap_err().message(),
+            "ChargerStatus: Invalid value for enum ChargeState"
+        );
+        let (_, valid) = registry.decode("ChargerStatus", 0x1_0000).unwrap();
+        assert_eq!(
+            valid.unwrap_err().message(),
+            "ChargerStatus: 0x10000 does not fit in the register"
+        );
+
+        let (_, valid) = registry.decode("CHARGER.STATUS", 0x0002).unwrap();
+        assert_eq!(
+            valid.unwrap_err().message(),
+            "CHARGER.STATUS: invalid value 0x1 for STATE"
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let registry = registry();
+        assert_eq!(
+            registry
+                .encode(
+                    "ChargerStatus",
+                    0,
+                    &[("present", "true"), ("state", "fast"), ("current", "0x1a")]
+                )
+                .unwrap(),
+            0x1a05
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0x1a05, &[("current", "0")])
+                .unwrap(),
+            0x0005
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("current", "256")])
+                .unwrap_err()
+                .message(),
+            "ChargerStatus: current exceeds maximum value for its bit width"
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("state", "3")])
+                .unwrap_err()
+                .message(),
+            "ChargerStatus: Invalid value for enum ChargeState"
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("limit", "3400mV")])
+                .unwrap(),
+            0x0020
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("limit", "3449")])
+                .unwrap(),
+            0x0020
+        );
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("limit", "-100")])
+                .unwrap_err()
+                .message(),
+            "ChargerStatus: limit is out of range for its scale and bit width"
+        );
+        assert_eq!(
+            registry
+                .encode("AmplifierControl", 0, &[("gain", "-0.5")])
+                .unwrap(),
+            0xe0
+        );
+        assert_eq!(
+            registry
+                .encode("AmplifierControl", 0, &[("gain", "0x60")])
+                .unwrap(),
+            0x60
+        );
+        assert_eq!(
+            registry
+                .encode("AmplifierControl", 0, &[("gain", "2")])
+                .unwrap_err()
+                .message(),
+            "AmplifierControl: gain exceeds maximum value for its bit width"
+        );
+        assert_eq!(
+            registry
+                .encode("TimerDial", 0, &[("minutes", "45"), ("position", "2")])
+                .unwrap(),
+            0x0345
+        );
+        assert_eq!(
+            registry
+                .encode("TimerDial", 0, &[("minutes", "0x59")])
+                .unwrap(),
+            0x59
+        );
+        assert_eq!(
+            registry
+                .encode("TimerDial", 0, &[("minutes", "0x5a")])
+                .unwrap_err()
+                .message(),
+            "TimerDial: bit pattern is not valid BCD"
+        );
+        assert_eq!(
+            registry
+                .encode("TimerDial", 0, &[("minutes", "80")])
+                .unwrap_err()
+                .message(),
+            "TimerDial: minutes exceeds maximum value for its bit width"
+        );
+        assert_eq!(
+            registry
+                .encode("TimerDial", 0, &[("alarm", "None")])
+                .unwrap(),
+            0xf000
+        );
+        let (layout, valid) = registry.decode("TimerDial", 0xf859).unwrap();
+        assert!(valid.is_ok());
+        assert!(layout.to_string().contains("| None"));
+        assert!(layout.to_string().contains("| 59"));
+        assert!(layout.to_string().contains("| 15"));
+        assert_eq!(
+            registry
+                .encode(
+                    "WatchdogControl",
+                    0,
+                    &[("timeout", "16"), ("enabled", "true")]
+                )
+                .unwrap(),
+            0x82
+        );
+        assert_eq!(
+            registry
+                .encode("WatchdogControl", 0, &[("timeout", "0b11")])
+                .unwrap(),
+            0x03
+        );
+        assert_eq!(
+            registry
+                .encode("WatchdogControl", 0, &[("timeout", "5")])
+                .unwrap_err()
+                .message(),
+            "WatchdogControl: timeout is not in its lookup table"
+        );
+        let (layout, _) = registry.decode("WatchdogControl", 0x83).unwrap();
+        assert!(layout.to_string().contains("| 64"));
+        let (layout, _) = registry.decode("AmplifierControl", 0xe0).unwrap();
+        assert!(layout.to_string().contains("| -0.5"));
+        assert_eq!(
+            registry
+                .encode("ChargerStatus", 0, &[("voltage", "3")])
+                .unwrap_err()
+                .message(),
+            "ChargerStatus: no field named voltage"
+        );
+        assert_eq!(
+            registry
+                .encode("CHARGER.STATUS", 0, &[("STATE", "FAST")])
+                .unwrap(),
+            0x0004
+        );
+    }
+}
@@ -0,0 +1,90 @@
+#![allow(missing_docs)]
+
+use std::process::{Command, Output};
+
+const DESCRIPTION: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/charger.toml");
+
+fn bitreg(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bitreg"))
+        .args(["--description", DESCRIPTION])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_list() {
+    let output = bitreg(&["list"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "CHARGER.STATUS\n");
+}
+
+#[test]
+fn test_decode() {
+    let output = bitreg(&["decode", "CHARGER_STATUS", "0x1a05"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "\
+CHARGER.STATUS = 0x1a05
+ bits  | field      | raw        | value
+-------+------------+------------+-------
+ 15:8  | CURRENT    | 0b00011010 | 26
+ 7:3   | (reserved) | 0b00000    |
+ 2:1   | STATE      | 0b10       | FAST
+ 0     | PRESENT    | 0b1        | true
+"
+    );
+}
+
+#[test]
+fn test_decode_invalid() {
+    let output = bitreg(&["decode", "STATUS", "0x0006"]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("| <invalid 0x3>"));
+    assert_eq!(
+        stderr(&output),
+        "error: CHARGER.STATUS: invalid value 0x3 for STATE\n"
+    );
+}
+
+#[test]
+fn test_encode() {
+    let output = bitreg(&[
+        "encode",
+        "status",
+        "present=true",
+        "state=fast",
+        "current=26",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "0x1a05\n");
+
+    let output = bitreg(&["encode", "status", "--from", "0x1a05", "current=0"]);
+    assert_eq!(stdout(&output), "0x0005\n");
+
+    let output = bitreg(&["encode", "status", "current=0x100"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        stderr(&output),
+        "error: CHARGER.STATUS: CURRENT exceeds maximum value for its bit width\n"
+    );
+}
+
+#[test]
+fn test_usage_errors() {
+    let output = bitreg(&["decode", "STATUS"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).starts_with("error: decode requires a value\n\nUsage: bitreg"));
+
+    let output = bitreg(&["decode", "TIMER", "0"]);
+    assert_eq!(stderr(&output), "error: no register named TIMER\n");
+}
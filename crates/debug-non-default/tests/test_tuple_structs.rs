@@ -8,7 +8,7 @@ struct Point(i32, i32, i32);
 
 // Tuple struct with String fields
 #[derive(DebugNonDefault, Default)]
-struct RGB(u8, u8, u8);
+struct Rgb(u8, u8, u8);
 
 // Unit struct
 #[derive(DebugNonDefault, Default)]
@@ -40,14 +40,14 @@ fn test_all_non_default_tuple() {
 
 #[test]
 fn test_rgb_tuple() {
-    let black = RGB::default(); // (0, 0, 0)
-    assert_eq!(format!("{:?}", black), "RGB(_, _, _)");
+    let black = Rgb::default(); // (0, 0, 0)
+    assert_eq!(format!("{:?}", black), "Rgb(_, _, _)");
 
-    let red = RGB(255, 0, 0);
-    assert_eq!(format!("{:?}", red), "RGB(255, _, _)");
+    let red = Rgb(255, 0, 0);
+    assert_eq!(format!("{:?}", red), "Rgb(255, _, _)");
 
-    let purple = RGB(255, 0, 255);
-    assert_eq!(format!("{:?}", purple), "RGB(255, _, 255)");
+    let purple = Rgb(255, 0, 255);
+    assert_eq!(format!("{:?}", purple), "Rgb(255, _, 255)");
 }
 
 #[test]